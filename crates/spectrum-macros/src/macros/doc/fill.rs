@@ -0,0 +1,123 @@
+use proc_macro2::Delimiter;
+use quote::ToTokens;
+use syn::{
+    bracketed, parenthesized,
+    parse::{Parse, ParseStream},
+    Expr, Ident,
+};
+
+use crate::macros::fragment::FragmentItem;
+
+use super::ParseShape;
+
+/// A paragraph of fragments that is reflowed to fit the page:
+///
+/// ```text
+/// fill [ "expected " (Green: "two specs") " to pass, but " {failed} " failed" ]
+/// ```
+///
+/// A `;` is a line break that is always taken. To configure the fill (for example, to give it
+/// a hanging indent), start from an existing `spectrum::Fill` by passing it in parens:
+/// `fill(Fill::default().hang(Nesting::Configured(1))) [ ... ]`.
+#[derive(Debug)]
+pub(crate) struct FillItem {
+    fill: Option<Expr>,
+    items: Vec<FragmentItem>,
+}
+
+sealed!(FillItem);
+
+impl ParseShape for FillItem {
+    fn is_valid_hint(input: ParseStream) -> bool {
+        let cursor = input.fork().cursor();
+
+        if let Some((ident, rest)) = cursor.ident() {
+            if ident == "fill"
+                && (rest.group(Delimiter::Bracket).is_some()
+                    || rest.group(Delimiter::Parenthesis).is_some())
+            {
+                return true;
+            }
+        }
+
+        false
+    }
+}
+
+impl Parse for FillItem {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let _: Ident = input.parse()?;
+
+        let fill = if input.peek(syn::token::Paren) {
+            let content;
+            parenthesized!(content in input);
+            Some(content.parse::<Expr>()?)
+        } else {
+            None
+        };
+
+        let content;
+        bracketed!(content in input);
+
+        let mut items = vec![];
+
+        while !content.is_empty() {
+            items.push(FragmentItem::maybe_parse(&content).must("expected a fragment")?);
+        }
+
+        Ok(FillItem { fill, items })
+    }
+}
+
+impl ToTokens for FillItem {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        let Self { fill, items } = self;
+
+        let fill = match fill {
+            Some(fill) => quote::quote! { #fill },
+            None => quote_using! {
+                [spectrum::Fill] => {
+                    #Fill::default()
+                }
+            },
+        };
+
+        let pieces = items.iter().map(|item| match item {
+            FragmentItem::Bracketed(bracketed) => {
+                let style = &bracketed.style;
+                let value = &bracketed.value;
+
+                let style = quote_using! {
+                    [spectrum::Style] => {
+                        let style: #Style = #style;
+                        style
+                    }
+                };
+
+                quote::quote! { .styled((#value), #style) }
+            }
+            FragmentItem::String(expr) => quote::quote! { .plain(#expr) },
+            FragmentItem::Expr(expr) => quote::quote! {
+                .plain({
+                    #[allow(unused_braces)]
+                    let text = #expr;
+                    text
+                })
+            },
+            FragmentItem::Newline(_) => quote::quote! { .line_break() },
+            FragmentItem::Error => quote::quote! { .plain("[ERROR]") },
+        });
+
+        tokens.extend(quote_using! {
+            [spectrum::Fill, spectrum::Doc] => {
+                {
+                    use #Doc;
+
+                    let fill: #Fill = #fill;
+
+                    fill #(#pieces)* .boxed()
+                }
+            }
+        })
+    }
+}
@@ -5,7 +5,10 @@ use syn::Ident;
 
 use crate::macros::fragment::FragmentItem;
 
-use super::{group::GroupItem, group::ParenItem, nested::NestedItem, ParseShape};
+use super::{
+    fill::FillItem, group::GroupItem, group::ParenItem, nested::NestedItem, table::TableItem,
+    ParseShape,
+};
 
 #[allow(unused)]
 #[derive(Debug)]
@@ -14,6 +17,8 @@ pub(crate) enum DocItem {
     List(ListItem),
     Group(GroupItem),
     Nested(NestedItem),
+    Table(TableItem),
+    Fill(FillItem),
     Either {
         inline: Box<DocItem>,
         block: Box<DocItem>,
@@ -34,6 +39,8 @@ impl Parse for DocItem {
                 },
 
                 // GroupItem(item) => DocItem::Group(item),
+                TableItem => |item| DocItem::Table(item),
+                FillItem => |item| DocItem::Fill(item),
                 SingleItem => |item| DocItem::Single(item),
                 FragmentItem => |item| DocItem::Item(item),
             }
@@ -56,6 +63,8 @@ impl ToTokens for DocItem {
             DocItem::List(_) => todo!(),
             DocItem::Group(group) => group.to_tokens(tokens),
             DocItem::Nested(nested) => nested.to_tokens(tokens),
+            DocItem::Table(table) => table.to_tokens(tokens),
+            DocItem::Fill(fill) => fill.to_tokens(tokens),
             DocItem::Either {
                 inline: _inline,
                 block: _block,
@@ -1,10 +1,12 @@
 #[macro_use]
 mod helper_macros;
 
+mod fill;
 mod group;
 mod item;
 pub(crate) mod maybe;
 mod nested;
+mod table;
 
 use syn::parse::{Parse, ParseStream};
 
@@ -0,0 +1,204 @@
+use proc_macro2::Delimiter;
+use quote::ToTokens;
+use syn::{
+    bracketed, parenthesized,
+    parse::{Parse, ParseStream},
+    Expr, Ident, Token,
+};
+
+use super::{item::DocItem, ParseShape};
+
+/// A table, with an optional header row, and rows of cells separated by `|`:
+///
+/// ```text
+/// table [
+///     header [ "name" | "status" ]
+///     [ "parses input" | (Green: "ok") ]
+///     [ "emits" | (Red: "failed") ]
+/// ]
+/// ```
+///
+/// To configure the columns, border and so on, start from an existing `spectrum::Table` by
+/// passing it in parens: `table(Table::new(columns).border(Border::unicode())) [ ... ]`.
+#[derive(Debug)]
+pub(crate) struct TableItem {
+    table: Option<Expr>,
+    header: Option<TableRow>,
+    rows: Vec<TableRow>,
+}
+
+sealed!(TableItem);
+
+impl ParseShape for TableItem {
+    fn is_valid_hint(input: ParseStream) -> bool {
+        let cursor = input.fork().cursor();
+
+        if let Some((ident, rest)) = cursor.ident() {
+            if ident == "table"
+                && (rest.group(Delimiter::Bracket).is_some()
+                    || rest.group(Delimiter::Parenthesis).is_some())
+            {
+                return true;
+            }
+        }
+
+        false
+    }
+}
+
+impl Parse for TableItem {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let _: Ident = input.parse()?;
+
+        let table = if input.peek(syn::token::Paren) {
+            let content;
+            parenthesized!(content in input);
+            Some(content.parse::<Expr>()?)
+        } else {
+            None
+        };
+
+        let content;
+        bracketed!(content in input);
+
+        let mut header = None;
+        let mut rows = vec![];
+
+        while !content.is_empty() {
+            if content.peek(Ident) {
+                let ident: Ident = content.parse()?;
+
+                if ident != "header" {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        "expected `header [ ... ]` or a row (`[ ... ]`)",
+                    ));
+                }
+
+                if header.is_some() || !rows.is_empty() {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        "the header must be the first row of a table",
+                    ));
+                }
+
+                header = Some(content.parse()?);
+            } else {
+                rows.push(content.parse()?);
+            }
+        }
+
+        Ok(TableItem {
+            table,
+            header,
+            rows,
+        })
+    }
+}
+
+impl ToTokens for TableItem {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        let Self {
+            table,
+            header,
+            rows,
+        } = self;
+
+        let table = match table {
+            Some(table) => quote::quote! { #table },
+            None => quote_using! {
+                [spectrum::Table] => {
+                    #Table::default()
+                }
+            },
+        };
+
+        let header = header.iter();
+
+        tokens.extend(quote_using! {
+            [spectrum::Table, spectrum::Doc] => {
+                {
+                    use #Doc;
+
+                    let table: #Table = #table;
+
+                    table
+                        #( .header(#header) )*
+                        #( .row(#rows) )*
+                        .boxed()
+                }
+            }
+        })
+    }
+}
+
+/// A row of cells, separated by `|`. A cell can contain any number of doc items, including
+/// none at all.
+#[derive(Debug)]
+pub(crate) struct TableRow {
+    cells: Vec<TableCell>,
+}
+
+impl Parse for TableRow {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+        bracketed!(content in input);
+
+        let mut cells = vec![TableCell { items: vec![] }];
+
+        while !content.is_empty() {
+            if content.peek(Token![|]) {
+                let _: Token![|] = content.parse()?;
+                cells.push(TableCell { items: vec![] });
+            } else {
+                let item = DocItem::parse(&content)?;
+                cells.last_mut().unwrap().items.push(item);
+            }
+        }
+
+        Ok(TableRow { cells })
+    }
+}
+
+impl ToTokens for TableRow {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        let cells = &self.cells;
+
+        tokens.extend(quote::quote! {
+            vec![#(#cells),*]
+        })
+    }
+}
+
+#[derive(Debug)]
+struct TableCell {
+    items: Vec<DocItem>,
+}
+
+impl ToTokens for TableCell {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        let items = &self.items;
+
+        tokens.extend(match &items[..] {
+            [] => quote_using! {
+                [spectrum::empty, spectrum::Doc] => {
+                    #Doc::boxed(#empty())
+                }
+            },
+            [item] => quote_using! {
+                [spectrum::Doc] => {
+                    #Doc::boxed(#item)
+                }
+            },
+            items => quote_using! {
+                [spectrum::Group, spectrum::Doc] => {
+                    {
+                        use #Doc;
+
+                        #Group::new(vec![#(#items.boxed()),*]).boxed()
+                    }
+                }
+            },
+        })
+    }
+}
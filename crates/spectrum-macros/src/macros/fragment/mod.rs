@@ -1,6 +1,6 @@
 #![allow(non_snake_case)]
 
-mod style;
+pub(crate) mod style;
 
 use proc_macro2::TokenStream;
 use proc_macro_error::emit_error;
@@ -97,7 +97,9 @@ impl ToTokens for FragmentItem {
                     [spectrum::plain, spectrum::Doc] => {
                         use #Doc;
 
-                        #plain(#expr).boxed()
+                        #[allow(unused_braces)]
+                        let fragment = #plain(#expr);
+                        fragment.boxed()
                     }
                 });
             }
@@ -121,8 +123,8 @@ impl ToTokens for FragmentItem {
 
 #[derive(Debug)]
 pub(crate) struct Bracketed {
-    style: StyleDescription,
-    value: Expr,
+    pub(crate) style: StyleDescription,
+    pub(crate) value: Expr,
 }
 
 #[allow(unused)]
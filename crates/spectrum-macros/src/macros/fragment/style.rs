@@ -1,7 +1,10 @@
+use proc_macro2::Span;
 use quote::{quote, ToTokens};
 use syn::{
+    parenthesized,
     parse::{Parse, ParseStream},
-    token, Block, Ident,
+    punctuated::Punctuated,
+    token, Block, Expr, Ident, LitStr, Token,
 };
 
 use crate::macros::doc::ParseShape;
@@ -10,6 +13,7 @@ use crate::macros::doc::ParseShape;
 pub enum StyleDescription {
     Color(Ident),
     Expr(Block),
+    Palette(Box<PaletteColor>),
 }
 
 sealed!(StyleDescription);
@@ -19,6 +23,7 @@ impl ToTokens for StyleDescription {
         match self {
             StyleDescription::Color(style) => tokens.extend(quote! { (#style).into() }),
             StyleDescription::Expr(expr) => tokens.extend(quote! { (#expr).into() }),
+            StyleDescription::Palette(color) => tokens.extend(quote! { (#color).into() }),
         }
     }
 }
@@ -27,7 +32,7 @@ impl ParseShape for StyleDescription {
     fn is_valid_hint(input: ParseStream) -> bool {
         let next = input.lookahead1();
 
-        next.peek(Ident) || next.peek(token::Brace)
+        next.peek(Ident) || next.peek(token::Brace) || next.peek(LitStr)
     }
 }
 
@@ -36,6 +41,7 @@ impl Parse for StyleDescription {
         try_parse! {
             in input;
             {
+            PaletteColor => |color| StyleDescription::Palette(Box::new(color)),
             Ident => |ident| StyleDescription::Color(ident),
             Block => |block| StyleDescription::Expr(block),
             }
@@ -43,3 +49,110 @@ impl Parse for StyleDescription {
         }
     }
 }
+
+/// A color that isn't one of the named ANSI colors:
+///
+/// - `rgb(r, g, b)`: a 24-bit color
+/// - `ansi256(n)`: a color from the 256-color palette
+/// - `"#rrggbb"`: a 24-bit color, written as a hex string
+#[derive(Debug)]
+pub enum PaletteColor {
+    Rgb(Box<[Expr; 3]>),
+    Ansi256(Box<Expr>),
+    Hex(u8, u8, u8),
+}
+
+sealed!(PaletteColor);
+
+impl ParseShape for PaletteColor {
+    fn is_valid_hint(input: ParseStream) -> bool {
+        if input.peek(LitStr) {
+            return true;
+        }
+
+        let cursor = input.fork().cursor();
+
+        if let Some((ident, rest)) = cursor.ident() {
+            if (ident == "rgb" || ident == "ansi256")
+                && rest.group(proc_macro2::Delimiter::Parenthesis).is_some()
+            {
+                return true;
+            }
+        }
+
+        false
+    }
+}
+
+impl Parse for PaletteColor {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(LitStr) {
+            let lit: LitStr = input.parse()?;
+            return parse_hex(&lit.value(), lit.span());
+        }
+
+        let ident: Ident = input.parse()?;
+        let content;
+        parenthesized!(content in input);
+        let args: Punctuated<Expr, Token![,]> = content.parse_terminated(Expr::parse)?;
+        let mut args = args.into_iter();
+
+        match (ident.to_string().as_str(), args.len()) {
+            ("rgb", 3) => Ok(PaletteColor::Rgb(Box::new([
+                args.next().unwrap(),
+                args.next().unwrap(),
+                args.next().unwrap(),
+            ]))),
+            ("ansi256", 1) => Ok(PaletteColor::Ansi256(Box::new(args.next().unwrap()))),
+            ("rgb", _) => Err(syn::Error::new(ident.span(), "rgb(...) takes 3 arguments")),
+            _ => Err(syn::Error::new(
+                ident.span(),
+                "ansi256(...) takes 1 argument",
+            )),
+        }
+    }
+}
+
+fn parse_hex(value: &str, span: Span) -> syn::Result<PaletteColor> {
+    let error = || syn::Error::new(span, "expected a hex color like \"#ff8700\"");
+
+    let hex = value.strip_prefix('#').ok_or_else(error)?;
+
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(error());
+    }
+
+    let channel = |range: std::ops::Range<usize>| u8::from_str_radix(&hex[range], 16).unwrap();
+
+    Ok(PaletteColor::Hex(
+        channel(0..2),
+        channel(2..4),
+        channel(4..6),
+    ))
+}
+
+impl ToTokens for PaletteColor {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        tokens.extend(match self {
+            PaletteColor::Rgb(rgb) => {
+                let [r, g, b] = &**rgb;
+
+                quote_using! {
+                    [spectrum::Color] => {
+                        #Color::Rgb(#r, #g, #b)
+                    }
+                }
+            }
+            PaletteColor::Ansi256(index) => quote_using! {
+                [spectrum::Color] => {
+                    #Color::Color256(#index)
+                }
+            },
+            PaletteColor::Hex(r, g, b) => quote_using! {
+                [spectrum::Color] => {
+                    #Color::Rgb(#r, #g, #b)
+                }
+            },
+        })
+    }
+}
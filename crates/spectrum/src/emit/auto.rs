@@ -0,0 +1,252 @@
+use std::fmt::Formatter;
+
+use derive_new::new;
+
+use crate::{ColorDepth, EmitBackendTrait, EmitError, EmitResult, Style};
+
+use super::backend::emit_ansi;
+
+/// A snapshot of everything that decides whether (and how richly) an output stream should be
+/// colored.
+///
+/// `ColorEnv::stdout()` and `ColorEnv::stderr()` read the real process environment. Tests can
+/// construct a `ColorEnv` by hand to get deterministic results.
+#[derive(Debug, Clone, Default, new)]
+pub struct ColorEnv {
+    /// Is the stream connected to a terminal?
+    pub is_tty: bool,
+    #[new(default)]
+    pub no_color: Option<String>,
+    #[new(default)]
+    pub clicolor: Option<String>,
+    #[new(default)]
+    pub clicolor_force: Option<String>,
+    #[new(default)]
+    pub term: Option<String>,
+    #[new(default)]
+    pub colorterm: Option<String>,
+}
+
+impl ColorEnv {
+    pub fn stdout() -> ColorEnv {
+        ColorEnv::from_process(console::Term::stdout().features().is_attended())
+    }
+
+    pub fn stderr() -> ColorEnv {
+        ColorEnv::from_process(console::Term::stderr().features().is_attended())
+    }
+
+    /// Read the color-related environment variables of the current process, for a stream whose
+    /// tty-ness is `is_tty`.
+    pub fn from_process(is_tty: bool) -> ColorEnv {
+        let var = |name: &str| std::env::var(name).ok();
+
+        ColorEnv {
+            is_tty,
+            no_color: var("NO_COLOR"),
+            clicolor: var("CLICOLOR"),
+            clicolor_force: var("CLICOLOR_FORCE"),
+            term: var("TERM"),
+            colorterm: var("COLORTERM"),
+        }
+    }
+
+    /// Decide whether to emit colors, and at what depth.
+    ///
+    /// - `NO_COLOR` (set to anything but the empty string) always disables colors
+    /// - `CLICOLOR_FORCE` (set to anything but `0`) enables colors, even when the stream isn't
+    ///   a terminal
+    /// - otherwise, colors are enabled for terminals, unless `TERM=dumb` or `CLICOLOR=0`
+    pub fn color_support(&self) -> Option<ColorDepth> {
+        let is_set = |var: &Option<String>, off: &str| match var {
+            Some(value) => value != off,
+            None => false,
+        };
+
+        if is_set(&self.no_color, "") {
+            return None;
+        }
+
+        if is_set(&self.clicolor_force, "0") {
+            return Some(self.depth());
+        }
+
+        let dumb = self.term.as_deref() == Some("dumb");
+        let disabled = self.clicolor.as_deref() == Some("0");
+
+        if !self.is_tty || dumb || disabled {
+            None
+        } else {
+            Some(self.depth())
+        }
+    }
+
+    /// The color depth supported by the terminal, based on `COLORTERM` and `TERM`.
+    pub fn depth(&self) -> ColorDepth {
+        match self.colorterm.as_deref() {
+            Some("truecolor") | Some("24bit") => return ColorDepth::TrueColor,
+            _ => {}
+        }
+
+        match self.term.as_deref() {
+            Some(term) if term.contains("256color") => ColorDepth::Ansi256,
+            _ => ColorDepth::Ansi16,
+        }
+    }
+}
+
+/// An implementation of `EmitBackendTrait` that decides between colored and plain output based
+/// on a `ColorEnv`.
+///
+/// `EmitAuto::stdout()` and `EmitAuto::stderr()` are the usual entry points. Use
+/// `EmitAuto::detect` with a hand-built `ColorEnv` (or `EmitAuto::with_depth`) to override the
+/// detection in tests.
+#[derive(Debug, Copy, Clone)]
+pub struct EmitAuto {
+    depth: Option<ColorDepth>,
+}
+
+impl EmitAuto {
+    pub fn stdout() -> EmitAuto {
+        EmitAuto::detect(&ColorEnv::stdout())
+    }
+
+    pub fn stderr() -> EmitAuto {
+        EmitAuto::detect(&ColorEnv::stderr())
+    }
+
+    pub fn detect(env: &ColorEnv) -> EmitAuto {
+        EmitAuto {
+            depth: env.color_support(),
+        }
+    }
+
+    /// Skip detection altogether. `None` produces plain output.
+    pub fn with_depth(depth: Option<ColorDepth>) -> EmitAuto {
+        EmitAuto { depth }
+    }
+
+    pub fn depth(&self) -> Option<ColorDepth> {
+        self.depth
+    }
+
+    pub fn is_colored(&self) -> bool {
+        self.depth.is_some()
+    }
+}
+
+impl EmitBackendTrait for EmitAuto {
+    fn emit(&self, f: &mut Formatter<'_>, fragment: &str, style: Style) -> EmitResult {
+        match self.depth {
+            Some(depth) => emit_ansi(f, fragment, style, depth),
+            None => write!(f, "{}", fragment).map_err(EmitError::new),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Color;
+
+    use super::*;
+
+    fn tty() -> ColorEnv {
+        ColorEnv::new(true)
+    }
+
+    fn pipe() -> ColorEnv {
+        ColorEnv::new(false)
+    }
+
+    #[test]
+    fn color_support() {
+        assert_eq!(tty().color_support(), Some(ColorDepth::Ansi16));
+        assert_eq!(pipe().color_support(), None);
+
+        let dumb = ColorEnv {
+            term: Some("dumb".to_string()),
+            ..tty()
+        };
+        assert_eq!(dumb.color_support(), None);
+
+        let no_color = ColorEnv {
+            no_color: Some("1".to_string()),
+            ..tty()
+        };
+        assert_eq!(no_color.color_support(), None);
+
+        let empty_no_color = ColorEnv {
+            no_color: Some("".to_string()),
+            ..tty()
+        };
+        assert_eq!(empty_no_color.color_support(), Some(ColorDepth::Ansi16));
+
+        let clicolor_off = ColorEnv {
+            clicolor: Some("0".to_string()),
+            ..tty()
+        };
+        assert_eq!(clicolor_off.color_support(), None);
+    }
+
+    #[test]
+    fn clicolor_force() {
+        let forced = ColorEnv {
+            clicolor_force: Some("1".to_string()),
+            term: Some("dumb".to_string()),
+            ..pipe()
+        };
+        assert_eq!(forced.color_support(), Some(ColorDepth::Ansi16));
+
+        let not_forced = ColorEnv {
+            clicolor_force: Some("0".to_string()),
+            ..pipe()
+        };
+        assert_eq!(not_forced.color_support(), None);
+
+        let no_color_wins = ColorEnv {
+            no_color: Some("1".to_string()),
+            ..forced
+        };
+        assert_eq!(no_color_wins.color_support(), None);
+    }
+
+    #[test]
+    fn color_depth() {
+        let ansi256 = ColorEnv {
+            term: Some("xterm-256color".to_string()),
+            ..tty()
+        };
+        assert_eq!(ansi256.color_support(), Some(ColorDepth::Ansi256));
+
+        let truecolor = ColorEnv {
+            colorterm: Some("truecolor".to_string()),
+            ..ansi256
+        };
+        assert_eq!(truecolor.color_support(), Some(ColorDepth::TrueColor));
+    }
+
+    #[test]
+    fn emit_auto() {
+        let style: Style = Color::rgb(0xff, 0x87, 0x00).into();
+
+        assert_eq!(
+            EmitAuto::detect(&pipe()).emit_string("hello", style),
+            "hello"
+        );
+
+        let env = ColorEnv {
+            colorterm: Some("24bit".to_string()),
+            ..tty()
+        };
+
+        assert_eq!(
+            EmitAuto::detect(&env).emit_string("hello", style),
+            "\u{1b}[38;2;255;135;0mhello\u{1b}[0m"
+        );
+
+        assert_eq!(
+            EmitAuto::with_depth(Some(ColorDepth::Ansi16)).emit_string("hello", style),
+            "\u{1b}[33mhello\u{1b}[0m"
+        );
+    }
+}
@@ -1,5 +1,7 @@
 use std::{fmt, fmt::Formatter, io::Write};
 
+use lazy_static::lazy_static;
+
 use crate::{
     compose::{Doc, StyledArena},
     string::intern::Intern,
@@ -8,6 +10,7 @@ use crate::{
 
 use super::{
     buf::Buf,
+    color::ColorDepth,
     error::{EmitError, EmitResult},
     style::Style,
 };
@@ -37,13 +40,38 @@ pub trait EmitBackendTrait: 'static {
         .unwrap()
     }
 
+    /// Render a doc whose fragments are all `&'static str` literals.
+    ///
+    /// Docs built from runtime strings should use `render_with_intern` instead.
     fn render(self, text: &impl Doc, page_size: usize) -> Result<String, std::fmt::Error>
+    where
+        Self: Sized,
+    {
+        self.render_with_intern(text, &Intern::new(), page_size)
+    }
+
+    /// Render a doc whose fragments were interned into `intern`.
+    ///
+    /// ```
+    /// use spectrum::{group, plain, EmitBackendTrait, EmitPlain, Intern, GAP};
+    ///
+    /// let mut intern = Intern::new();
+    /// let name = intern.intern(format!("user{}", 1));
+    /// let doc = group![plain("hello"), GAP(), plain(name)];
+    ///
+    /// assert_eq!(EmitPlain.render_with_intern(&doc, &intern, 80).unwrap(), "hello user1");
+    /// ```
+    fn render_with_intern(
+        self,
+        text: &impl Doc,
+        intern: &Intern,
+        page_size: usize,
+    ) -> Result<String, std::fmt::Error>
     where
         Self: Sized,
     {
         Buf::collect_string(|writer| {
-            let intern = Intern::new();
-            let arena = StyledArena::new(&intern);
+            let arena = StyledArena::new(intern);
             let mut context = RenderContext::new(arena);
             context.render(text, self, writer, RenderConfig::width(page_size))?;
 
@@ -52,12 +80,44 @@ pub trait EmitBackendTrait: 'static {
     }
 }
 
+/// A shared backend, so that a backend chosen at runtime can be handed to code that renders
+/// docs in several places.
+impl<B> EmitBackendTrait for std::sync::Arc<B>
+where
+    B: EmitBackendTrait + ?Sized,
+{
+    fn emit(&self, f: &mut Formatter<'_>, fragment: &str, style: Style) -> EmitResult {
+        (**self).emit(f, fragment, style)
+    }
+}
+
+lazy_static! {
+    static ref ENV_COLOR_DEPTH: ColorDepth = ColorDepth::from_env();
+}
+
+/// Emits ANSI escape codes, downgrading colors that the terminal can't display (according to
+/// `ColorDepth::from_env`) to the nearest color it can.
 #[derive(Debug, Copy, Clone)]
 pub struct EmitColored;
 
 impl EmitBackendTrait for EmitColored {
     fn emit(&self, f: &mut Formatter<'_>, fragment: &str, style: Style) -> EmitResult {
-        write!(f, "{}", style.apply_to(fragment)).map_err(EmitError::new)
+        emit_ansi(f, fragment, style, *ENV_COLOR_DEPTH)
+    }
+}
+
+pub(crate) fn emit_ansi(
+    f: &mut Formatter<'_>,
+    fragment: &str,
+    style: Style,
+    depth: ColorDepth,
+) -> EmitResult {
+    let prefix = style.ansi_prefix(depth);
+
+    if prefix.is_empty() {
+        write!(f, "{}", fragment).map_err(EmitError::new)
+    } else {
+        write!(f, "{}{}\u{1b}[0m", prefix, fragment).map_err(EmitError::new)
     }
 }
 
@@ -81,10 +141,9 @@ pub fn write_into(
 #[cfg(test)]
 mod tests {
 
-    use crate::EmitForTest;
+    use crate::{Color, EmitForTest};
 
     use super::*;
-    use console::Color;
 
     #[test]
     fn emit_test() {
@@ -109,4 +168,57 @@ mod tests {
             "\u{1b}[31mhello emitter world\u{1b}[0m"
         );
     }
+
+    #[test]
+    fn emit_test_exact_colors() {
+        assert_eq!(
+            EmitForTest.emit_string("hello", Color::rgb(0xff, 0x87, 0x00).into()),
+            "[#ff8700:hello]"
+        );
+
+        assert_eq!(
+            EmitForTest.emit_string(
+                "hello",
+                Style::default()
+                    .fg(Color::Color256(42))
+                    .bg(Color::rgb(0, 0, 0))
+            ),
+            "[Color256(42) on #000000:hello]"
+        );
+    }
+
+    fn emit_at(depth: ColorDepth, style: Style) -> String {
+        format!(
+            "{}",
+            format::Display(move |f| Ok(emit_ansi(f, "x", style, depth)?))
+        )
+    }
+
+    #[test]
+    fn emit_colored_depths() {
+        let orange: Style = Color::rgb(0xff, 0x87, 0x00).into();
+
+        assert_eq!(
+            emit_at(ColorDepth::TrueColor, orange),
+            "\u{1b}[38;2;255;135;0mx\u{1b}[0m"
+        );
+        assert_eq!(
+            emit_at(ColorDepth::Ansi256, orange),
+            "\u{1b}[38;5;208mx\u{1b}[0m"
+        );
+        assert_eq!(emit_at(ColorDepth::Ansi16, orange), "\u{1b}[33mx\u{1b}[0m");
+
+        let bright = Style::default().bg(Color::Color256(196)).bold();
+
+        assert_eq!(
+            emit_at(ColorDepth::Ansi256, bright),
+            "\u{1b}[1;48;5;196mx\u{1b}[0m"
+        );
+        assert_eq!(
+            emit_at(ColorDepth::Ansi16, bright),
+            "\u{1b}[1;101mx\u{1b}[0m"
+        );
+
+        assert_eq!(emit_at(ColorDepth::Ansi16, Style::default()), "x");
+    }
 }
@@ -0,0 +1,216 @@
+use std::fmt::{self, Debug, Formatter};
+
+use super::{auto::ColorEnv, palette::ansi256_to_rgb};
+
+/// A terminal color.
+///
+/// In addition to the 8 basic ANSI colors, a `Color` can be a value from the xterm 256-color
+/// palette or an arbitrary 24-bit RGB value. Backends that can't represent a color downgrade it
+/// to the nearest color they support (see `Color::downgrade`).
+#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    Color256(u8),
+    Rgb(u8, u8, u8),
+}
+
+/// The number of colors a terminal is able to display.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum ColorDepth {
+    /// The 8 basic colors and their bright variants
+    Ansi16,
+    /// The xterm 256-color palette
+    Ansi256,
+    /// 24-bit RGB colors
+    TrueColor,
+}
+
+impl ColorDepth {
+    /// Detect the color depth from the `COLORTERM` and `TERM` environment variables.
+    pub fn from_env() -> ColorDepth {
+        ColorEnv::from_process(true).depth()
+    }
+}
+
+impl Color {
+    pub fn rgb(r: u8, g: u8, b: u8) -> Color {
+        Color::Rgb(r, g, b)
+    }
+
+    pub fn ansi256(index: u8) -> Color {
+        Color::Color256(index)
+    }
+
+    /// The RGB value of this color, using xterm's default palette for non-RGB colors.
+    pub fn to_rgb(self) -> (u8, u8, u8) {
+        match self {
+            Color::Rgb(r, g, b) => (r, g, b),
+            other => ansi256_to_rgb(other.palette_index().unwrap()),
+        }
+    }
+
+    /// The index of this color in the 256-color palette, if it has one.
+    pub fn palette_index(self) -> Option<u8> {
+        match self {
+            Color::Black => Some(0),
+            Color::Red => Some(1),
+            Color::Green => Some(2),
+            Color::Yellow => Some(3),
+            Color::Blue => Some(4),
+            Color::Magenta => Some(5),
+            Color::Cyan => Some(6),
+            Color::White => Some(7),
+            Color::Color256(index) => Some(index),
+            Color::Rgb(..) => None,
+        }
+    }
+
+    /// Convert this color into the nearest color that can be displayed at `depth`.
+    ///
+    /// When downgrading to `ColorDepth::Ansi16`, the result is either one of the basic colors or
+    /// a `Color256` in the range `8..16` (a bright variant of a basic color).
+    pub fn downgrade(self, depth: ColorDepth) -> Color {
+        match (depth, self) {
+            (ColorDepth::TrueColor, color) => color,
+            (ColorDepth::Ansi256, Color::Rgb(..)) => Color::from_index(nearest(self.to_rgb(), 256)),
+            (ColorDepth::Ansi256, color) => color,
+            (ColorDepth::Ansi16, color) => match color.palette_index() {
+                Some(index) if index < 16 => Color::from_index(index),
+                _ => Color::from_index(nearest(self.to_rgb(), 16)),
+            },
+        }
+    }
+
+    fn from_index(index: u8) -> Color {
+        match index {
+            0 => Color::Black,
+            1 => Color::Red,
+            2 => Color::Green,
+            3 => Color::Yellow,
+            4 => Color::Blue,
+            5 => Color::Magenta,
+            6 => Color::Cyan,
+            7 => Color::White,
+            index => Color::Color256(index),
+        }
+    }
+}
+
+/// Find the palette index (among the first `candidates` entries) that is closest to `rgb`.
+fn nearest(rgb: (u8, u8, u8), candidates: usize) -> u8 {
+    let distance = |index: u8| {
+        let (r, g, b) = ansi256_to_rgb(index);
+        let dr = r as i32 - rgb.0 as i32;
+        let dg = g as i32 - rgb.1 as i32;
+        let db = b as i32 - rgb.2 as i32;
+
+        dr * dr + dg * dg + db * db
+    };
+
+    (0..candidates)
+        .map(|index| index as u8)
+        .min_by_key(|index| distance(*index))
+        .unwrap()
+}
+
+impl Debug for Color {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Color::Black => write!(f, "Black"),
+            Color::Red => write!(f, "Red"),
+            Color::Green => write!(f, "Green"),
+            Color::Yellow => write!(f, "Yellow"),
+            Color::Blue => write!(f, "Blue"),
+            Color::Magenta => write!(f, "Magenta"),
+            Color::Cyan => write!(f, "Cyan"),
+            Color::White => write!(f, "White"),
+            Color::Color256(index) => write!(f, "Color256({})", index),
+            Color::Rgb(r, g, b) => write!(f, "#{:02x}{:02x}{:02x}", r, g, b),
+        }
+    }
+}
+
+impl From<console::Color> for Color {
+    fn from(color: console::Color) -> Self {
+        match color {
+            console::Color::Black => Color::Black,
+            console::Color::Red => Color::Red,
+            console::Color::Green => Color::Green,
+            console::Color::Yellow => Color::Yellow,
+            console::Color::Blue => Color::Blue,
+            console::Color::Magenta => Color::Magenta,
+            console::Color::Cyan => Color::Cyan,
+            console::Color::White => Color::White,
+            console::Color::Color256(index) => Color::Color256(index),
+        }
+    }
+}
+
+/// `console` doesn't support 24-bit colors, so RGB colors are downgraded to the 256-color palette.
+impl From<Color> for console::Color {
+    fn from(color: Color) -> Self {
+        match color.downgrade(ColorDepth::Ansi256) {
+            Color::Black => console::Color::Black,
+            Color::Red => console::Color::Red,
+            Color::Green => console::Color::Green,
+            Color::Yellow => console::Color::Yellow,
+            Color::Blue => console::Color::Blue,
+            Color::Magenta => console::Color::Magenta,
+            Color::Cyan => console::Color::Cyan,
+            Color::White => console::Color::White,
+            Color::Color256(index) => console::Color::Color256(index),
+            Color::Rgb(..) => unreachable!("RGB colors are downgraded to the 256-color palette"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn downgrade_rgb() {
+        let orange = Color::rgb(0xff, 0x87, 0x00);
+
+        assert_eq!(orange.downgrade(ColorDepth::TrueColor), orange);
+        assert_eq!(orange.downgrade(ColorDepth::Ansi256), Color::Color256(208));
+        assert_eq!(orange.downgrade(ColorDepth::Ansi16), Color::Yellow);
+
+        assert_eq!(
+            Color::rgb(0xfe, 0x01, 0x02).downgrade(ColorDepth::Ansi16),
+            Color::Color256(9)
+        );
+        assert_eq!(
+            Color::rgb(0x10, 0x10, 0x10).downgrade(ColorDepth::Ansi16),
+            Color::Black
+        );
+    }
+
+    #[test]
+    fn downgrade_palette() {
+        assert_eq!(
+            Color::Color256(196).downgrade(ColorDepth::Ansi256),
+            Color::Color256(196)
+        );
+        assert_eq!(
+            Color::Color256(196).downgrade(ColorDepth::Ansi16),
+            Color::Color256(9)
+        );
+        assert_eq!(Color::Color256(1).downgrade(ColorDepth::Ansi16), Color::Red);
+        assert_eq!(Color::Red.downgrade(ColorDepth::Ansi16), Color::Red);
+    }
+
+    #[test]
+    fn debug_color() {
+        assert_eq!(format!("{:?}", Color::Red), "Red");
+        assert_eq!(format!("{:?}", Color::Color256(42)), "Color256(42)");
+        assert_eq!(format!("{:?}", Color::rgb(0xff, 0x87, 0x00)), "#ff8700");
+    }
+}
@@ -0,0 +1,286 @@
+use std::fmt::{Formatter, Write};
+
+use crate::{Attributes, Color, EmitBackendTrait, EmitResult, Style};
+
+/// Controls how `EmitHtml` describes a fragment's `Style`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum HtmlStyleMode {
+    /// Describe styles as classes (`fg-red`, `bg-256-42`, `fg-rgb-ff8700`, `bold`, ...). The
+    /// embedding page is responsible for supplying the CSS.
+    Classes,
+    /// Describe styles as inline CSS, so the output is self-contained.
+    Inline,
+}
+
+/// An implementation of `EmitBackendTrait` that emits HTML markup.
+///
+/// Every styled fragment becomes an escaped `<span>`, while unstyled text (including the
+/// whitespace and newlines produced by layout) is emitted as escaped text. The output is meant
+/// to be placed inside a `<pre>` (or any element with `white-space: pre`), so that the line
+/// breaks produced by `render` are preserved.
+#[derive(Debug, Copy, Clone)]
+pub struct EmitHtml {
+    mode: HtmlStyleMode,
+}
+
+impl Default for EmitHtml {
+    fn default() -> Self {
+        EmitHtml::classes()
+    }
+}
+
+impl EmitHtml {
+    pub fn classes() -> EmitHtml {
+        EmitHtml {
+            mode: HtmlStyleMode::Classes,
+        }
+    }
+
+    pub fn inline() -> EmitHtml {
+        EmitHtml {
+            mode: HtmlStyleMode::Inline,
+        }
+    }
+
+    pub fn mode(&self) -> HtmlStyleMode {
+        self.mode
+    }
+}
+
+impl EmitBackendTrait for EmitHtml {
+    fn emit(&self, f: &mut Formatter<'_>, fragment: &str, style: Style) -> EmitResult {
+        if style.is_plain() {
+            return Ok(escape(f, fragment)?);
+        }
+
+        match self.mode {
+            HtmlStyleMode::Classes => write!(f, "<span class=\"{}\">", classes(style))?,
+            HtmlStyleMode::Inline => write!(f, "<span style=\"{}\">", inline_css(style))?,
+        }
+
+        escape(f, fragment)?;
+        write!(f, "</span>")?;
+
+        Ok(())
+    }
+}
+
+fn escape(f: &mut Formatter<'_>, fragment: &str) -> std::fmt::Result {
+    for c in fragment.chars() {
+        match c {
+            '&' => f.write_str("&amp;")?,
+            '<' => f.write_str("&lt;")?,
+            '>' => f.write_str("&gt;")?,
+            '"' => f.write_str("&quot;")?,
+            '\'' => f.write_str("&#39;")?,
+            c => f.write_char(c)?,
+        }
+    }
+
+    Ok(())
+}
+
+fn color_name(color: Color) -> String {
+    match color {
+        Color::Black => "black".to_string(),
+        Color::Red => "red".to_string(),
+        Color::Green => "green".to_string(),
+        Color::Yellow => "yellow".to_string(),
+        Color::Blue => "blue".to_string(),
+        Color::Magenta => "magenta".to_string(),
+        Color::Cyan => "cyan".to_string(),
+        Color::White => "white".to_string(),
+        Color::Color256(index) => format!("256-{}", index),
+        Color::Rgb(r, g, b) => format!("rgb-{:02x}{:02x}{:02x}", r, g, b),
+    }
+}
+
+fn color_css(color: Color) -> String {
+    let (r, g, b) = color.to_rgb();
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+fn attr_names(attrs: Attributes) -> Vec<&'static str> {
+    let mut out: Vec<&'static str> = vec![];
+
+    match_attr!(attrs, {
+        bold => "bold",
+        dim => "dim",
+        italic => "italic",
+        underlined => "underlined",
+        blink => "blink",
+        reverse => "reverse",
+        hidden => "hidden"
+    } => out.push);
+
+    out
+}
+
+fn classes(style: Style) -> String {
+    let mut classes: Vec<String> = vec![];
+
+    if let Some(fg) = style.foreground() {
+        classes.push(format!("fg-{}", color_name(fg)));
+    }
+
+    if let Some(bg) = style.background() {
+        classes.push(format!("bg-{}", color_name(bg)));
+    }
+
+    classes.extend(attr_names(style.attributes()).into_iter().map(String::from));
+
+    classes.join(" ")
+}
+
+fn inline_css(style: Style) -> String {
+    let attrs = style.attributes();
+    let mut fg = style.foreground().map(color_css);
+    let mut bg = style.background().map(color_css);
+
+    // There's no CSS property for reversed video, so swap the colors instead, falling back to the
+    // page's own colors when one side isn't specified.
+    if attrs.reverse() {
+        let reversed_fg = bg.take().unwrap_or_else(|| "Canvas".to_string());
+        let reversed_bg = fg.take().unwrap_or_else(|| "CanvasText".to_string());
+        fg = Some(reversed_fg);
+        bg = Some(reversed_bg);
+    }
+
+    let mut css: Vec<String> = vec![];
+
+    if let Some(fg) = fg {
+        css.push(format!("color:{}", fg));
+    }
+
+    if let Some(bg) = bg {
+        css.push(format!("background-color:{}", bg));
+    }
+
+    if attrs.bold() {
+        css.push("font-weight:bold".to_string());
+    }
+
+    if attrs.dim() {
+        css.push("opacity:0.5".to_string());
+    }
+
+    if attrs.italic() {
+        css.push("font-style:italic".to_string());
+    }
+
+    match (attrs.underlined(), attrs.blink()) {
+        (true, true) => css.push("text-decoration:underline blink".to_string()),
+        (true, false) => css.push("text-decoration:underline".to_string()),
+        (false, true) => css.push("text-decoration:blink".to_string()),
+        (false, false) => {}
+    }
+
+    if attrs.hidden() {
+        css.push("visibility:hidden".to_string());
+    }
+
+    css.join(";")
+}
+
+#[cfg(test)]
+mod tests {
+    use console::Attribute;
+
+    use crate::{group, prelude::test::*, styled, EmitPlain, GAP};
+
+    use super::*;
+
+    #[test]
+    fn emit_html_plain() {
+        assert_eq!(
+            EmitHtml::classes().emit_string("a < b && \"c\"", Style::default()),
+            "a &lt; b &amp;&amp; &quot;c&quot;"
+        );
+    }
+
+    #[test]
+    fn emit_html_classes() {
+        assert_eq!(
+            EmitHtml::classes().emit_string("<hello>", Color::Red.into()),
+            "<span class=\"fg-red\">&lt;hello&gt;</span>"
+        );
+
+        let style = Style::default()
+            .fg(Color::Color256(42))
+            .bg(Color::Blue)
+            .attr(Attribute::Bold)
+            .attr(Attribute::Underlined)
+            .attr(Attribute::Hidden);
+
+        assert_eq!(
+            EmitHtml::classes().emit_string("hello", style),
+            "<span class=\"fg-256-42 bg-blue bold underlined hidden\">hello</span>"
+        );
+
+        assert_eq!(
+            EmitHtml::classes().emit_string("hello", Color::rgb(0xff, 0x87, 0x00).into()),
+            "<span class=\"fg-rgb-ff8700\">hello</span>"
+        );
+    }
+
+    #[test]
+    fn emit_html_inline() {
+        let style = Style::default()
+            .fg(Color::Red)
+            .attr(Attribute::Italic)
+            .attr(Attribute::Underlined)
+            .attr(Attribute::Blink);
+
+        assert_eq!(
+            EmitHtml::inline().emit_string("hello", style),
+            "<span style=\"color:#cd0000;font-style:italic;text-decoration:underline blink\">hello</span>"
+        );
+
+        assert_eq!(
+            EmitHtml::inline().emit_string("hello", Style::default().bg(Color::rgb(1, 2, 3))),
+            "<span style=\"background-color:#010203\">hello</span>"
+        );
+
+        assert_eq!(
+            EmitHtml::inline().emit_string(
+                "hello",
+                Style::default()
+                    .fg(Color::Color256(42))
+                    .bg(Color::rgb(1, 2, 3))
+            ),
+            "<span style=\"color:#00d787;background-color:#010203\">hello</span>"
+        );
+
+        let reversed = Style::default().fg(Color::Red).attr(Attribute::Reverse);
+
+        assert_eq!(
+            EmitHtml::inline().emit_string("hello", reversed),
+            "<span style=\"color:Canvas;background-color:#cd0000\">hello</span>"
+        );
+    }
+
+    #[test]
+    fn render_html_matches_plain_layout() -> TestResult {
+        let doc = group![
+            styled("<function>", Color::Red.into()),
+            GAP(),
+            styled("\"hello\"", Style::default().bold()),
+            GAP(),
+            "world"
+        ];
+
+        assert_eq!(
+            EmitHtml::classes().render(&doc, 80)?,
+            "<span class=\"fg-red\">&lt;function&gt;</span> <span class=\"bold\">&quot;hello&quot;</span> world"
+        );
+
+        assert_eq!(
+            EmitHtml::classes().render(&doc, 10)?,
+            "<span class=\"fg-red\">&lt;function&gt;</span>\n<span class=\"bold\">&quot;hello&quot;</span>\nworld"
+        );
+
+        assert_eq!(EmitPlain.render(&doc, 10)?, "<function>\n\"hello\"\nworld");
+
+        Ok(())
+    }
+}
@@ -1,11 +1,17 @@
 #[macro_use]
 mod macros;
 
+#[macro_use]
+pub mod style;
+
+pub mod auto;
 pub mod backend;
 pub mod buf;
+pub mod color;
 pub mod error;
+pub mod html;
 pub mod into;
-pub mod style;
+mod palette;
 pub mod test;
 
 // pub mod write;
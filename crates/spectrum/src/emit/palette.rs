@@ -0,0 +1,58 @@
+//! The xterm 256-color palette, used by backends that need to turn an ANSI color index into an
+//! actual RGB value.
+
+/// The 16 system colors, using xterm's default values.
+const SYSTEM: [(u8, u8, u8); 16] = [
+    (0x00, 0x00, 0x00),
+    (0xcd, 0x00, 0x00),
+    (0x00, 0xcd, 0x00),
+    (0xcd, 0xcd, 0x00),
+    (0x00, 0x00, 0xee),
+    (0xcd, 0x00, 0xcd),
+    (0x00, 0xcd, 0xcd),
+    (0xe5, 0xe5, 0xe5),
+    (0x7f, 0x7f, 0x7f),
+    (0xff, 0x00, 0x00),
+    (0x00, 0xff, 0x00),
+    (0xff, 0xff, 0x00),
+    (0x5c, 0x5c, 0xff),
+    (0xff, 0x00, 0xff),
+    (0x00, 0xff, 0xff),
+    (0xff, 0xff, 0xff),
+];
+
+/// The intensity steps used by the 6x6x6 color cube (indexes 16..=231).
+const CUBE: [u8; 6] = [0x00, 0x5f, 0x87, 0xaf, 0xd7, 0xff];
+
+pub(crate) fn ansi256_to_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => SYSTEM[index as usize],
+        16..=231 => {
+            let index = index - 16;
+            (
+                CUBE[(index / 36) as usize],
+                CUBE[((index / 6) % 6) as usize],
+                CUBE[(index % 6) as usize],
+            )
+        }
+        _ => {
+            let level = 8 + (index - 232) * 10;
+            (level, level, level)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn palette() {
+        assert_eq!(ansi256_to_rgb(1), (0xcd, 0x00, 0x00));
+        assert_eq!(ansi256_to_rgb(16), (0x00, 0x00, 0x00));
+        assert_eq!(ansi256_to_rgb(196), (0xff, 0x00, 0x00));
+        assert_eq!(ansi256_to_rgb(231), (0xff, 0xff, 0xff));
+        assert_eq!(ansi256_to_rgb(232), (0x08, 0x08, 0x08));
+        assert_eq!(ansi256_to_rgb(255), (0xee, 0xee, 0xee));
+    }
+}
@@ -6,17 +6,19 @@ use derive_new::new;
 use modular_bitfield::bitfield;
 use std::{fmt::Debug, fmt::Display, hash::Hash, ops::BitOr, ops::BitOrAssign};
 
+use super::color::{Color, ColorDepth};
+
 #[bitfield]
 #[repr(u8)]
 #[derive(Copy, Clone, PartialEq, Eq, Ord, PartialOrd, Hash, Debug, Default)]
 pub struct Attributes {
-    bold: bool,
-    dim: bool,
-    italic: bool,
-    underlined: bool,
-    blink: bool,
-    reverse: bool,
-    hidden: bool,
+    pub bold: bool,
+    pub dim: bool,
+    pub italic: bool,
+    pub underlined: bool,
+    pub blink: bool,
+    pub reverse: bool,
+    pub hidden: bool,
     #[skip]
     __: bool,
 }
@@ -150,7 +152,7 @@ macro_rules! iterate_attr {
     ($self:ident, $field:ident, $set_field:ident, $attr:ident) => {
         if $self.seen.$field() == false {
             $self.seen.$set_field(true);
-            if $self.iterating.bold() {
+            if $self.iterating.$field() {
                 return Some(console::Attribute::$attr);
             }
         }
@@ -163,7 +165,7 @@ impl Iterator for AttributesIterator {
     fn next(&mut self) -> Option<Self::Item> {
         iterate_attr!(self, bold, set_bold, Bold);
         iterate_attr!(self, dim, set_dim, Dim);
-        iterate_attr!(self, italic, set_italic, Bold);
+        iterate_attr!(self, italic, set_italic, Italic);
         iterate_attr!(self, underlined, set_underlined, Underlined);
         iterate_attr!(self, blink, set_blink, Blink);
         iterate_attr!(self, reverse, set_reverse, Reverse);
@@ -173,16 +175,22 @@ impl Iterator for AttributesIterator {
     }
 }
 
-#[derive(Copy, Clone, new)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, new)]
 pub struct Style {
     #[new(value = "None")]
-    fg: Option<console::Color>,
+    fg: Option<Color>,
     #[new(value = "None")]
-    bg: Option<console::Color>,
+    bg: Option<Color>,
     #[new(default)]
     attrs: Attributes,
 }
 
+impl From<Color> for Style {
+    fn from(color: Color) -> Self {
+        Style::default().fg(color)
+    }
+}
+
 impl From<console::Color> for Style {
     fn from(color: console::Color) -> Self {
         Style::default().fg(color)
@@ -213,12 +221,12 @@ impl Style {
         style.apply_to(fragment)
     }
 
-    pub fn fg(mut self, color: impl Into<console::Color>) -> Style {
+    pub fn fg(mut self, color: impl Into<Color>) -> Style {
         self.fg = Some(color.into());
         self
     }
 
-    pub fn bg(mut self, color: impl Into<console::Color>) -> Style {
+    pub fn bg(mut self, color: impl Into<Color>) -> Style {
         self.bg = Some(color.into());
         self
     }
@@ -236,6 +244,68 @@ impl Style {
         self.attrs |= attrs.into();
         self
     }
+
+    pub fn foreground(self) -> Option<Color> {
+        self.fg
+    }
+
+    pub fn background(self) -> Option<Color> {
+        self.bg
+    }
+
+    pub fn attributes(self) -> Attributes {
+        self.attrs
+    }
+
+    /// A plain style has no colors and no attributes
+    pub fn is_plain(self) -> bool {
+        self.fg.is_none() && self.bg.is_none() && self.attrs.is_empty()
+    }
+
+    /// The ANSI escape sequence that turns on this style, with colors downgraded to `depth`. A
+    /// plain style produces an empty string.
+    pub fn ansi_prefix(self, depth: ColorDepth) -> String {
+        let mut attrs: Vec<&'static str> = vec![];
+
+        match_attr!(self.attrs, {
+            bold => "1",
+            dim => "2",
+            italic => "3",
+            underlined => "4",
+            blink => "5",
+            reverse => "7",
+            hidden => "8"
+        } => attrs.push);
+
+        let mut codes: Vec<String> = attrs.into_iter().map(String::from).collect();
+
+        if let Some(fg) = self.fg {
+            codes.push(ansi_color(fg, depth, 30));
+        }
+
+        if let Some(bg) = self.bg {
+            codes.push(ansi_color(bg, depth, 40));
+        }
+
+        if codes.is_empty() {
+            String::new()
+        } else {
+            format!("\u{1b}[{}m", codes.join(";"))
+        }
+    }
+}
+
+/// The SGR parameter for `color`, where `base` is 30 for foreground colors and 40 for
+/// background colors.
+fn ansi_color(color: Color, depth: ColorDepth, base: u8) -> String {
+    match color.downgrade(depth) {
+        Color::Color256(index) if depth == ColorDepth::Ansi16 && index < 16 => {
+            format!("{}", base + 60 + index - 8)
+        }
+        Color::Color256(index) => format!("{};5;{}", base + 8, index),
+        Color::Rgb(r, g, b) => format!("{};2;{};{};{}", base + 8, r, g, b),
+        named => format!("{}", base + named.palette_index().unwrap()),
+    }
 }
 
 impl From<Style> for console::Style {
@@ -243,11 +313,11 @@ impl From<Style> for console::Style {
         let mut console_style = console::Style::new();
 
         if let Some(fg) = style.fg {
-            console_style = console_style.fg(fg);
+            console_style = console_style.fg(fg.into());
         }
 
         if let Some(bg) = style.bg {
-            console_style = console_style.bg(bg);
+            console_style = console_style.bg(bg.into());
         }
 
         for attr in style.attrs {
@@ -7,16 +7,24 @@ pub mod string;
 #[macro_use]
 pub mod structure;
 
-pub use console::Color;
+pub use emit::auto::{ColorEnv, EmitAuto};
 pub use emit::backend::*;
+pub use emit::color::{Color, ColorDepth};
 pub use emit::error::*;
+pub use emit::html::{EmitHtml, HtmlStyleMode};
 pub use emit::into::ToStyledString;
 pub use emit::style::*;
 pub use emit::test::EmitForTest;
+pub use string::doc_string::DocString;
 pub use string::intern::Intern;
+pub use structure::compose::diff::{Diff, DiffMode, Granularity};
 pub use structure::compose::docs::{self, *};
+pub use structure::compose::fill::{fill, Fill};
 pub use structure::compose::list::{DocList, Group, Nested};
 pub use structure::compose::render_context::RenderContext;
+pub use structure::compose::snippet::{LabelKind, Snippet, SnippetLabel};
+pub use structure::compose::table::{Align, Border, Column, Overflow, Table};
+pub use structure::compose::tree::{Tree, TreeGuides};
 pub use structure::compose::InternedBoxedDoc;
 pub use structure::compose::{BoxedDoc, Doc, Fragment};
 pub use structure::render::RenderConfig;
@@ -0,0 +1,346 @@
+//! Diffing text at the granularity of lines, words or characters.
+//!
+//! Text is first split into tokens, and the tokens are diffed with the linear space version of
+//! Myers' algorithm, so that a line or word can only ever be kept, deleted or inserted as a whole.
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) enum Change<'t> {
+    Equal(&'t str),
+    Delete(&'t str),
+    Insert(&'t str),
+}
+
+/// The lines of `text`, without their line endings.
+pub(crate) fn lines(text: &str) -> Vec<&str> {
+    text.split('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .collect()
+}
+
+/// `text`, split into alternating runs of whitespace and non-whitespace.
+pub(crate) fn words(text: &str) -> Vec<&str> {
+    let mut words = vec![];
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        let is_space = c.is_whitespace();
+        let end = rest
+            .find(|c: char| c.is_whitespace() != is_space)
+            .unwrap_or(rest.len());
+
+        words.push(&rest[..end]);
+        rest = &rest[end..];
+    }
+
+    words
+}
+
+/// `text`, split into characters.
+pub(crate) fn chars(text: &str) -> Vec<&str> {
+    text.char_indices()
+        .map(|(index, c)| &text[index..index + c.len_utf8()])
+        .collect()
+}
+
+/// How many tokens a search for a middle snake may compare, roughly, before it gives up. The
+/// tokens that it was looking at are then reported as deleted and inserted, which is a correct
+/// diff, if not the shortest one, so that diffing very large, very different values stays fast.
+const MAX_SEARCH_COST: usize = 1 << 24;
+
+/// Diff two lists of tokens. Each change holds a single token.
+pub(crate) fn diff_tokens<'t>(old: &[&'t str], new: &[&'t str]) -> Vec<Change<'t>> {
+    let max_d = max_d(old.len(), new.len());
+    let mut forward = V::new(max_d);
+    let mut backward = V::new(max_d);
+    let mut changes = vec![];
+
+    conquer(old, new, &mut forward, &mut backward, &mut changes);
+
+    // Within each run of edits, the deletions come before the insertions
+    for run in changes.split_mut(|change| matches!(change, Change::Equal(_))) {
+        run.sort_by_key(|change| matches!(change, Change::Insert(_)));
+    }
+
+    changes
+}
+
+/// The most edits that a search for a middle snake between `n` and `m` tokens needs.
+fn max_d(n: usize, m: usize) -> usize {
+    (n + m).div_ceil(2) + 1
+}
+
+/// The furthest `x` reached on each diagonal `k` (where `k = x - y`), for `k` in `-max_d..max_d`.
+struct V {
+    offset: isize,
+    v: Vec<usize>,
+}
+
+impl V {
+    fn new(max_d: usize) -> V {
+        V {
+            offset: max_d as isize,
+            v: vec![0; 2 * max_d],
+        }
+    }
+}
+
+impl std::ops::Index<isize> for V {
+    type Output = usize;
+
+    fn index(&self, k: isize) -> &usize {
+        &self.v[(k + self.offset) as usize]
+    }
+}
+
+impl std::ops::IndexMut<isize> for V {
+    fn index_mut(&mut self, k: isize) -> &mut usize {
+        &mut self.v[(k + self.offset) as usize]
+    }
+}
+
+fn common_prefix(old: &[&str], new: &[&str]) -> usize {
+    old.iter()
+        .zip(new)
+        .take_while(|(old, new)| old == new)
+        .count()
+}
+
+fn common_suffix(old: &[&str], new: &[&str]) -> usize {
+    old.iter()
+        .rev()
+        .zip(new.iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count()
+}
+
+/// Myers' linear space diff: keep the common prefix and suffix, find the middle snake of the
+/// shortest edit path between what's left, and diff the tokens on either side of it the same
+/// way. Only the two `V`s are kept, instead of one for every number of edits, so the memory it
+/// needs grows with the number of tokens rather than with the tokens times the edits.
+fn conquer<'t>(
+    old: &[&'t str],
+    new: &[&'t str],
+    forward: &mut V,
+    backward: &mut V,
+    changes: &mut Vec<Change<'t>>,
+) {
+    let prefix = common_prefix(old, new);
+    changes.extend(old[..prefix].iter().map(|t| Change::Equal(t)));
+
+    let (old, new) = (&old[prefix..], &new[prefix..]);
+    let suffix = common_suffix(old, new);
+    let (old, new, equal) = (
+        &old[..old.len() - suffix],
+        &new[..new.len() - suffix],
+        &old[old.len() - suffix..],
+    );
+
+    if old.is_empty() || new.is_empty() {
+        changes.extend(old.iter().map(|t| Change::Delete(t)));
+        changes.extend(new.iter().map(|t| Change::Insert(t)));
+    } else if let Some((x, y)) = middle_snake(old, new, forward, backward) {
+        conquer(&old[..x], &new[..y], forward, backward, changes);
+        conquer(&old[x..], &new[y..], forward, backward, changes);
+    } else {
+        changes.extend(old.iter().map(|t| Change::Delete(t)));
+        changes.extend(new.iter().map(|t| Change::Insert(t)));
+    }
+
+    changes.extend(equal.iter().map(|t| Change::Equal(t)));
+}
+
+/// Find where a shortest edit path from `old` to `new` can be split in two: search for it from
+/// the start and from the end at once, one more edit at a time, until the two searches overlap.
+/// Returns `None` if that would take longer than `MAX_SEARCH_COST`.
+fn middle_snake(
+    old: &[&str],
+    new: &[&str],
+    forward: &mut V,
+    backward: &mut V,
+) -> Option<(usize, usize)> {
+    let (n, m) = (old.len(), new.len());
+    let delta = n as isize - m as isize;
+    let odd = delta & 1 == 1;
+
+    forward[1] = 0;
+    backward[1] = 0;
+
+    for d in 0..max_d(n, m) as isize {
+        if d as usize * (n + m) > MAX_SEARCH_COST {
+            return None;
+        }
+
+        for k in (-d..=d).rev().step_by(2) {
+            let mut x = if k == -d || (k != d && forward[k - 1] < forward[k + 1]) {
+                forward[k + 1]
+            } else {
+                forward[k - 1] + 1
+            };
+            let y = (x as isize - k) as usize;
+            let (start_x, start_y) = (x, y);
+
+            if x < n && y < m {
+                x += common_prefix(&old[x..], &new[y..]);
+            }
+
+            forward[k] = x;
+
+            if odd && (k - delta).abs() < d && forward[k] + backward[-(k - delta)] >= n {
+                return Some((start_x, start_y));
+            }
+        }
+
+        for k in (-d..=d).rev().step_by(2) {
+            let mut x = if k == -d || (k != d && backward[k - 1] < backward[k + 1]) {
+                backward[k + 1]
+            } else {
+                backward[k - 1] + 1
+            };
+            let mut y = (x as isize - k) as usize;
+
+            if x < n && y < m {
+                let advance = common_suffix(&old[..n - x], &new[..m - y]);
+                x += advance;
+                y += advance;
+            }
+
+            backward[k] = x;
+
+            if !odd && (k - delta).abs() <= d && backward[k] + forward[-(k - delta)] >= n {
+                return Some((n - x, m - y));
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_lines() {
+        let old = lines("a\nb\nc\nd");
+        let new = lines("a\nc\nx\nd");
+
+        assert_eq!(
+            diff_tokens(&old, &new),
+            vec![
+                Change::Equal("a"),
+                Change::Delete("b"),
+                Change::Equal("c"),
+                Change::Insert("x"),
+                Change::Equal("d"),
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_words() {
+        let old = words("the quick brown fox");
+        let new = words("the slow brown  fox");
+
+        assert_eq!(
+            diff_tokens(&old, &new),
+            vec![
+                Change::Equal("the"),
+                Change::Equal(" "),
+                Change::Delete("quick"),
+                Change::Insert("slow"),
+                Change::Equal(" "),
+                Change::Equal("brown"),
+                Change::Delete(" "),
+                Change::Insert("  "),
+                Change::Equal("fox"),
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_unicode() {
+        let old = lines("[\n    \"é\",\n    \"ü\",\n]");
+        let new = lines("[\n    \"ü\",\n    \"ö\",\n]");
+
+        assert_eq!(
+            diff_tokens(&old, &new),
+            vec![
+                Change::Equal("["),
+                Change::Delete("    \"é\","),
+                Change::Equal("    \"ü\","),
+                Change::Insert("    \"ö\","),
+                Change::Equal("]"),
+            ]
+        );
+    }
+
+    #[test]
+    fn many_distinct_tokens() {
+        let old: Vec<String> = (0..10_000).map(|i| i.to_string()).collect();
+        let old: Vec<&str> = old.iter().map(|token| &token[..]).collect();
+        let new = &old[1..];
+
+        let changes = diff_tokens(&old, new);
+
+        assert_eq!(changes[0], Change::Delete("0"));
+        assert!(changes[1..]
+            .iter()
+            .all(|change| matches!(change, Change::Equal(_))));
+    }
+
+    /// Apply `changes` to get back the old and the new tokens.
+    fn sides<'t>(changes: &[Change<'t>]) -> (Vec<&'t str>, Vec<&'t str>) {
+        let (mut old, mut new) = (vec![], vec![]);
+
+        for change in changes {
+            match *change {
+                Change::Equal(token) => {
+                    old.push(token);
+                    new.push(token);
+                }
+                Change::Delete(token) => old.push(token),
+                Change::Insert(token) => new.push(token),
+            }
+        }
+
+        (old, new)
+    }
+
+    #[test]
+    fn shortest_diff() {
+        let old = chars("ABCABBA");
+        let new = chars("CBABAC");
+
+        let changes = diff_tokens(&old, &new);
+        let edits = changes
+            .iter()
+            .filter(|change| !matches!(change, Change::Equal(_)))
+            .count();
+
+        assert_eq!(sides(&changes), (old, new));
+        assert_eq!(edits, 5);
+    }
+
+    #[test]
+    fn large_different_inputs() {
+        let old: Vec<String> = (0..6000).map(|i| format!("old {}", i)).collect();
+        let new: Vec<String> = (0..6000).map(|i| format!("new {}", i)).collect();
+        let old: Vec<&str> = old.iter().map(|token| &token[..]).collect();
+        let new: Vec<&str> = new.iter().map(|token| &token[..]).collect();
+
+        let changes = diff_tokens(&old, &new);
+
+        assert_eq!(changes.len(), 12_000);
+        assert_eq!(sides(&changes), (old, new));
+    }
+
+    #[test]
+    fn interleaved_changes() {
+        let old: Vec<String> = (0..3000).map(|i| (i % 7).to_string()).collect();
+        let new: Vec<String> = (0..3000).map(|i| (i % 5).to_string()).collect();
+        let old: Vec<&str> = old.iter().map(|token| &token[..]).collect();
+        let new: Vec<&str> = new.iter().map(|token| &token[..]).collect();
+
+        assert_eq!(sides(&diff_tokens(&old, &new)), (old, new));
+    }
+}
@@ -0,0 +1,55 @@
+use std::{borrow::Cow, fmt::Display, sync::Arc};
+
+use super::intern::StringId;
+
+/// The text of a fragment in a doc.
+///
+/// A `DocString` is either a `StringId` (a `&'static str` or a string that was interned ahead
+/// of time), or a string owned by the doc itself. Owned strings are copied into the
+/// `StyledArena` when the doc is rendered, and are freed along with the arena.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum DocString {
+    Id(StringId),
+    Owned(String),
+    Shared(Arc<str>),
+}
+
+impl DocString {
+    /// The formatted value of `value`.
+    pub fn display(value: impl Display) -> DocString {
+        DocString::Owned(value.to_string())
+    }
+}
+
+impl From<StringId> for DocString {
+    fn from(id: StringId) -> Self {
+        DocString::Id(id)
+    }
+}
+
+impl From<&'static str> for DocString {
+    fn from(string: &'static str) -> Self {
+        DocString::Id(StringId::Literal(string))
+    }
+}
+
+impl From<String> for DocString {
+    fn from(string: String) -> Self {
+        DocString::Owned(string)
+    }
+}
+
+impl From<Cow<'static, str>> for DocString {
+    fn from(string: Cow<'static, str>) -> Self {
+        match string {
+            Cow::Borrowed(string) => string.into(),
+            Cow::Owned(string) => string.into(),
+        }
+    }
+}
+
+impl From<Arc<str>> for DocString {
+    fn from(string: Arc<str>) -> Self {
+        DocString::Shared(string)
+    }
+}
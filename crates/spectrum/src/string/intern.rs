@@ -7,6 +7,8 @@ use format::Display;
 pub enum StringId {
     Id(usize),
     Literal(&'static str),
+    /// A string owned by a `StyledArena`, which is only valid while rendering with that arena
+    Arena(usize),
 }
 
 impl Into<StringId> for &'static str {
@@ -41,7 +43,11 @@ impl Intern {
     }
 
     pub fn get(&self, id: StringId) -> &str {
-        self.intern.map.get_by_left(&id).unwrap()
+        match id {
+            StringId::Literal(string) => string,
+            StringId::Id(_) => self.intern.map.get_by_left(&id).unwrap(),
+            StringId::Arena(_) => panic!("{:?} can only be looked up in its StyledArena", id),
+        }
     }
 
     pub fn intern(&mut self, string: impl Into<String>) -> StringId {
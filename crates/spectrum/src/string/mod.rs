@@ -1 +1,4 @@
+pub(crate) mod diff;
+pub mod doc_string;
 pub mod intern;
+pub mod width;
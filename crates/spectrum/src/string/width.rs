@@ -0,0 +1,76 @@
+//! Measuring strings the way a terminal displays them.
+//!
+//! Layout is computed in terminal columns rather than bytes: CJK characters and most emoji take
+//! up two columns, while combining marks and other zero-width characters don't take up any.
+
+use std::borrow::Cow;
+
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// The number of terminal columns that `string` occupies.
+pub fn display_width(string: &str) -> usize {
+    UnicodeWidthStr::width(string)
+}
+
+/// Split `string` into the longest prefix that fits in `width` columns, and the rest.
+///
+/// Zero-width characters (like combining marks) stay with the character they follow.
+pub fn split_at_width(string: &str, width: usize) -> (&str, &str) {
+    let mut used = 0;
+
+    for (index, c) in string.char_indices() {
+        let char_width = c.width().unwrap_or(0);
+
+        if used + char_width > width {
+            return string.split_at(index);
+        }
+
+        used += char_width;
+    }
+
+    (string, "")
+}
+
+/// A stand-in for `string` whose byte length is the display width of `string`.
+///
+/// `pretty` measures text by its byte length, so layout is given this stand-in instead of the
+/// real text. The `Renderer` never writes the stand-in: it emits the real fragment when it sees
+/// the fragment's annotation.
+pub(crate) fn layout_text(string: &str) -> Cow<'_, str> {
+    let width = display_width(string);
+
+    if width == string.len() {
+        Cow::Borrowed(string)
+    } else {
+        Cow::Owned(" ".repeat(width))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_width_of_wide_chars() {
+        assert_eq!(display_width("hello"), 5);
+        assert_eq!(display_width("日本語"), 6);
+        assert_eq!(display_width("e\u{301}"), 1);
+        assert_eq!(display_width("🦀"), 2);
+    }
+
+    #[test]
+    fn split_wide_chars() {
+        assert_eq!(split_at_width("hello", 3), ("hel", "lo"));
+        assert_eq!(split_at_width("hello", 10), ("hello", ""));
+        assert_eq!(split_at_width("日本語", 3), ("日", "本語"));
+        assert_eq!(split_at_width("日本語", 1), ("", "日本語"));
+        assert_eq!(split_at_width("e\u{301}x", 1), ("e\u{301}", "x"));
+    }
+
+    #[test]
+    fn layout_text_matches_width() {
+        assert_eq!(layout_text("hello"), "hello");
+        assert_eq!(layout_text("日本語"), "      ");
+        assert_eq!(layout_text("e\u{301}"), " ");
+    }
+}
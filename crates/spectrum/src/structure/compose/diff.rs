@@ -0,0 +1,565 @@
+use std::fmt::Debug;
+
+use crate::{
+    render::RenderState,
+    string::diff::{chars, diff_tokens, lines, words, Change},
+    Color, Column, Doc, Style, Table,
+};
+
+use super::{
+    lines::{lines_doc, wrap_line, Line, LineDoc, Span},
+    StyledArena, StyledDoc,
+};
+
+/// The smallest unit that a `Diff` highlights inside a changed line.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Granularity {
+    /// Changed lines are highlighted as a whole
+    Line,
+    /// Words (and runs of whitespace) that changed inside a line are highlighted
+    Word,
+    /// Characters that changed inside a line are highlighted
+    Char,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DiffMode {
+    /// Deleted lines are shown above the lines that were inserted in their place, like a
+    /// unified diff
+    Inline,
+    /// Expected and actual lines are shown next to each other, in two columns
+    SideBySide,
+}
+
+/// A doc that shows the differences between an expected and an actual string.
+///
+/// Lines are matched up first, and lines that were deleted are shown with a `-`, while lines
+/// that were inserted are shown with a `+`. At `Granularity::Word` and `Granularity::Char`, the
+/// parts of a changed line that differ from the line it replaced are highlighted as well.
+///
+/// Long runs of unchanged lines are collapsed, leaving `context` lines around each change.
+///
+/// ```
+/// use spectrum::{Diff, EmitBackendTrait, EmitPlain};
+///
+/// let diff = Diff::new("one\ntwo\nthree", "one\n2\nthree");
+///
+/// assert_eq!(
+///     EmitPlain.render(&diff, 80).unwrap(),
+///     "- expected\n+ actual\n\n  one\n- two\n+ 2\n  three"
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct Diff {
+    expected: String,
+    actual: String,
+    labels: (String, String),
+    granularity: Granularity,
+    mode: DiffMode,
+    context: Option<usize>,
+}
+
+impl Diff {
+    pub fn new(expected: impl Into<String>, actual: impl Into<String>) -> Diff {
+        Diff {
+            expected: expected.into(),
+            actual: actual.into(),
+            labels: ("expected".to_string(), "actual".to_string()),
+            granularity: Granularity::Word,
+            mode: DiffMode::Inline,
+            context: Some(3),
+        }
+    }
+
+    /// Diff the pretty-printed (`{:#?}`) representations of two values.
+    pub fn debug(expected: &impl Debug, actual: &impl Debug) -> Diff {
+        Diff::new(format!("{:#?}", expected), format!("{:#?}", actual))
+    }
+
+    /// The names of the two sides of the diff (`expected` and `actual` by default).
+    pub fn labels(mut self, expected: impl Into<String>, actual: impl Into<String>) -> Diff {
+        self.labels = (expected.into(), actual.into());
+        self
+    }
+
+    pub fn granularity(mut self, granularity: Granularity) -> Diff {
+        self.granularity = granularity;
+        self
+    }
+
+    pub fn mode(mut self, mode: DiffMode) -> Diff {
+        self.mode = mode;
+        self
+    }
+
+    pub fn side_by_side(self) -> Diff {
+        self.mode(DiffMode::SideBySide)
+    }
+
+    /// The number of unchanged lines to keep around each change (3 by default).
+    pub fn context(mut self, lines: usize) -> Diff {
+        self.context = Some(lines);
+        self
+    }
+
+    /// Show every unchanged line.
+    pub fn full_context(mut self) -> Diff {
+        self.context = None;
+        self
+    }
+
+    pub fn has_changes(&self) -> bool {
+        self.expected != self.actual
+    }
+}
+
+/// A line of the diff, after collapsing.
+enum Item<'t> {
+    Equal(&'t str),
+    Collapsed(usize),
+    Change(Vec<&'t str>, Vec<&'t str>),
+}
+
+#[derive(Copy, Clone)]
+enum Side {
+    Delete,
+    Insert,
+}
+
+impl Side {
+    fn prefix(self) -> &'static str {
+        match self {
+            Side::Delete => "-",
+            Side::Insert => "+",
+        }
+    }
+
+    fn style(self) -> Style {
+        match self {
+            Side::Delete => Color::Red.into(),
+            Side::Insert => Color::Green.into(),
+        }
+    }
+
+    fn emphasis(self) -> Style {
+        self.style().attr(console::Attribute::Reverse)
+    }
+}
+
+impl Diff {
+    fn items(&self) -> Vec<Item<'_>> {
+        let old = lines(&self.expected);
+        let new = lines(&self.actual);
+
+        // Group the changes into runs of unchanged lines and runs of changed lines
+        let mut blocks: Vec<Item<'_>> = vec![];
+        let mut equal: Vec<&str> = vec![];
+
+        for change in diff_tokens(&old, &new) {
+            match change {
+                Change::Equal(line) => equal.push(line),
+                Change::Delete(line) | Change::Insert(line) => {
+                    blocks.extend(equal.drain(..).map(Item::Equal));
+
+                    if !matches!(blocks.last(), Some(Item::Change(..))) {
+                        blocks.push(Item::Change(vec![], vec![]));
+                    }
+
+                    if let Some(Item::Change(deleted, inserted)) = blocks.last_mut() {
+                        match change {
+                            Change::Delete(_) => deleted.push(line),
+                            _ => inserted.push(line),
+                        }
+                    }
+                }
+            }
+        }
+
+        blocks.extend(equal.drain(..).map(Item::Equal));
+
+        match self.context {
+            Some(context) if self.has_changes() => collapse(blocks, context),
+            _ => blocks,
+        }
+    }
+
+    /// The spans of a changed line, highlighting the parts that differ from `other` (the line
+    /// it's paired with) if there is one.
+    fn changed_line(&self, line: &str, other: Option<&str>, side: Side) -> Line {
+        let other = match (self.granularity, other) {
+            (Granularity::Line, _) | (_, None) => return vec![Span::new(line, side.style())],
+            (_, Some(other)) => other,
+        };
+
+        let tokenize = match self.granularity {
+            Granularity::Word => words,
+            _ => chars,
+        };
+
+        let (old, new) = match side {
+            Side::Delete => (line, other),
+            Side::Insert => (other, line),
+        };
+
+        let mut spans: Line = vec![];
+
+        for change in diff_tokens(&tokenize(old), &tokenize(new)) {
+            let (text, style) = match (change, side) {
+                (Change::Equal(text), _) => (text, side.style()),
+                (Change::Delete(text), Side::Delete) | (Change::Insert(text), Side::Insert) => {
+                    (text, side.emphasis())
+                }
+                _ => continue,
+            };
+
+            match spans.last_mut() {
+                Some(span) if span.style == style => span.text.push_str(text),
+                _ => spans.push(Span::new(text, style)),
+            }
+        }
+
+        spans
+    }
+
+    /// The changed lines on one side of a change, each paired with the line in the same
+    /// position on the other side.
+    fn changed_lines(&self, lines: &[&str], others: &[&str], side: Side) -> Vec<Line> {
+        lines
+            .iter()
+            .enumerate()
+            .map(|(index, line)| self.changed_line(line, others.get(index).copied(), side))
+            .collect()
+    }
+
+    fn inline_lines(&self) -> Vec<Line> {
+        let prefixed = |prefix: &str, style: Style, mut line: Line| -> Line {
+            let prefix = if line.is_empty() {
+                prefix.to_string()
+            } else {
+                format!("{} ", prefix)
+            };
+
+            line.insert(0, Span::new(prefix, style));
+            line
+        };
+
+        let mut out = vec![
+            vec![Span::new(
+                format!("- {}", self.labels.0),
+                Side::Delete.style(),
+            )],
+            vec![Span::new(
+                format!("+ {}", self.labels.1),
+                Side::Insert.style(),
+            )],
+            vec![],
+        ];
+
+        for item in self.items() {
+            match item {
+                Item::Equal(line) => {
+                    out.push(vec![Span::new(format!("  {}", line), Style::default())])
+                }
+                Item::Collapsed(count) => out.push(vec![collapsed(count)]),
+                Item::Change(deleted, inserted) => {
+                    for (side, lines, others) in &[
+                        (Side::Delete, &deleted, &inserted),
+                        (Side::Insert, &inserted, &deleted),
+                    ] {
+                        for line in self.changed_lines(lines, others, *side) {
+                            out.push(prefixed(side.prefix(), side.style(), line));
+                        }
+                    }
+                }
+            }
+        }
+
+        out
+    }
+
+    fn side_by_side_table(&self, state: RenderState) -> Table {
+        let half = (state.column_size().saturating_sub(3) / 2).max(1);
+
+        let prefixed = |side: Side, mut line: Line| -> Line {
+            line.insert(0, Span::new(format!("{} ", side.prefix()), side.style()));
+            line
+        };
+
+        let mut rows: Vec<(Line, Line)> = vec![(
+            vec![Span::new(self.labels.0.clone(), Side::Delete.style())],
+            vec![Span::new(self.labels.1.clone(), Side::Insert.style())],
+        )];
+
+        for item in self.items() {
+            match item {
+                Item::Equal(line) => {
+                    let line = vec![Span::new(format!("  {}", line), Style::default())];
+                    rows.push((line.clone(), line));
+                }
+                Item::Collapsed(count) => rows.push((vec![collapsed(count)], vec![])),
+                Item::Change(deleted, inserted) => {
+                    let mut left = self
+                        .changed_lines(&deleted, &inserted, Side::Delete)
+                        .into_iter();
+                    let mut right = self
+                        .changed_lines(&inserted, &deleted, Side::Insert)
+                        .into_iter();
+
+                    loop {
+                        match (left.next(), right.next()) {
+                            (None, None) => break,
+                            (left, right) => rows.push((
+                                left.map(|line| prefixed(Side::Delete, line))
+                                    .unwrap_or_default(),
+                                right
+                                    .map(|line| prefixed(Side::Insert, line))
+                                    .unwrap_or_default(),
+                            )),
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut table = Table::new(vec![Column::left(), Column::left(), Column::left()]).gap(1);
+
+        // Lines are wrapped here rather than by the table, so that the separator continues
+        // alongside every line of a wrapped row
+        for (left, right) in rows {
+            let left = wrap_line(left, half);
+            let right = wrap_line(right, half);
+
+            for index in 0..left.len().max(right.len()) {
+                let cell =
+                    |lines: &[Line]| LineDoc(lines.get(index).cloned().unwrap_or_default()).boxed();
+
+                table = table.row(vec![
+                    cell(&left),
+                    LineDoc(vec![Span::new("│", Style::default())]).boxed(),
+                    cell(&right),
+                ]);
+            }
+        }
+
+        table
+    }
+}
+
+fn collapsed(count: usize) -> Span {
+    Span::new(
+        format!("  ... {} unchanged lines", count),
+        Style::default().attr(console::Attribute::Dim),
+    )
+}
+
+/// Collapse runs of unchanged lines, leaving `context` lines next to each change.
+fn collapse(blocks: Vec<Item<'_>>, context: usize) -> Vec<Item<'_>> {
+    let mut items = vec![];
+    let mut equal = vec![];
+    let mut seen_change = false;
+
+    for block in blocks {
+        match block {
+            Item::Equal(line) => equal.push(line),
+            change => {
+                let head = if seen_change { context } else { 0 };
+                collapse_run(&mut items, std::mem::take(&mut equal), head, context);
+
+                seen_change = true;
+                items.push(change);
+            }
+        }
+    }
+
+    let head = if seen_change { context } else { 0 };
+    collapse_run(&mut items, equal, head, 0);
+
+    items
+}
+
+/// Push a run of unchanged lines, keeping `head` lines at its start and `tail` lines at its
+/// end. A single hidden line is shown rather than collapsed, since the marker would take up
+/// as much room.
+fn collapse_run<'t>(items: &mut Vec<Item<'t>>, run: Vec<&'t str>, head: usize, tail: usize) {
+    if run.len() > head + tail + 1 {
+        items.extend(run[..head].iter().map(|line| Item::Equal(line)));
+        items.push(Item::Collapsed(run.len() - head - tail));
+        items.extend(run[run.len() - tail..].iter().map(|line| Item::Equal(line)));
+    } else {
+        items.extend(run.into_iter().map(Item::Equal));
+    }
+}
+
+impl Doc for Diff {
+    fn render<'a>(&self, ctx: &'a StyledArena<'a>, state: RenderState) -> StyledDoc<'a> {
+        match self.mode {
+            DiffMode::Inline => lines_doc(ctx, &self.inline_lines()),
+            DiffMode::SideBySide => self.side_by_side_table(state).render(ctx, state),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{prelude::test::*, EmitBackendTrait, EmitForTest, EmitPlain};
+
+    use super::*;
+
+    #[test]
+    fn diff_inline_lines() -> TestResult {
+        let diff = Diff::new("a\nb\nc\nd", "a\nc\nx\nd");
+
+        assert_eq!(
+            EmitPlain.render(&diff, 80)?,
+            strip(
+                r#"
+                - expected
+                + actual
+
+                  a
+                - b
+                  c
+                + x
+                  d
+                "#
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn diff_highlights_words_and_chars() -> TestResult {
+        let diff = Diff::new("the quick brown fox", "the slow brown fox");
+
+        assert_eq!(
+            EmitForTest.render(&diff.clone().labels("old", "new"), 80)?,
+            "[Red:- old]\n[Green:+ new]\n\n\
+             [Red:- ][Red:the ][Red, reverse:quick][Red: brown fox]\n\
+             [Green:+ ][Green:the ][Green, reverse:slow][Green: brown fox]"
+        );
+
+        assert_eq!(
+            EmitForTest.render(
+                &Diff::new("the color", "the colour").granularity(Granularity::Char),
+                80
+            )?,
+            "[Red:- expected]\n[Green:+ actual]\n\n\
+             [Red:- ][Red:the color]\n\
+             [Green:+ ][Green:the colo][Green, reverse:u][Green:r]"
+        );
+
+        assert_eq!(
+            EmitForTest.render(&diff.granularity(Granularity::Line), 80)?,
+            "[Red:- expected]\n[Green:+ actual]\n\n\
+             [Red:- ][Red:the quick brown fox]\n\
+             [Green:+ ][Green:the slow brown fox]"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn diff_collapses_context() -> TestResult {
+        let expected: Vec<String> = (1..=20).map(|i| i.to_string()).collect();
+        let mut actual = expected.clone();
+        actual[9] = "ten".to_string();
+
+        let diff = Diff::new(expected.join("\n"), actual.join("\n"));
+
+        assert_eq!(
+            EmitPlain.render(&diff.clone().context(2), 80)?,
+            strip(
+                r#"
+                - expected
+                + actual
+
+                  ... 7 unchanged lines
+                  8
+                  9
+                - 10
+                + ten
+                  11
+                  12
+                  ... 8 unchanged lines
+                "#
+            )
+        );
+        assert_eq!(
+            EmitPlain.render(&diff.full_context(), 80)?.lines().count(),
+            24
+        );
+
+        // Nothing is collapsed when there are no changes
+        let same = Diff::new(expected.join("\n"), expected.join("\n"));
+        assert!(!same.has_changes());
+        assert_eq!(EmitPlain.render(&same, 80)?.lines().count(), 23);
+
+        Ok(())
+    }
+
+    #[test]
+    fn diff_side_by_side() -> TestResult {
+        let diff = Diff::new("a\nb\nsame line", "a\nc\nd\nsame line").side_by_side();
+
+        assert_eq!(
+            EmitPlain.render(&diff, 80)?,
+            strip(
+                r#"
+                expected    │ actual
+                  a         │   a
+                - b         │ + c
+                            │ + d
+                  same line │   same line
+                "#
+            )
+        );
+
+        // Each side gets half of the page, and long lines wrap within it
+        let diff = Diff::new("a long line that needs to wrap", "short").side_by_side();
+
+        assert_eq!(
+            EmitPlain.render(&diff, 23)?,
+            strip(
+                r#"
+                expected  │ actual
+                - a long  │ + short
+                line that │
+                needs to  │
+                wrap      │
+                "#
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn diff_debug() -> TestResult {
+        let diff = Diff::debug(&vec![1, 2, 3], &vec![1, 3]);
+
+        assert_eq!(
+            EmitPlain.render(&diff, 80)?,
+            strip(
+                r#"
+                - expected
+                + actual
+
+                  [
+                      1,
+                -     2,
+                      3,
+                  ]
+                "#
+            )
+        );
+
+        Ok(())
+    }
+
+    fn strip(input: &str) -> String {
+        let lines: Vec<&str> = input.split('\n').collect();
+        let string = lines[1..lines.len() - 1].to_vec().join("\n");
+        textwrap::dedent(&string)
+    }
+}
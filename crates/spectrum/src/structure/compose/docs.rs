@@ -1,29 +1,34 @@
-use derive_new::new;
+use std::fmt::Display;
+
 use pretty::DocAllocator;
 
-use crate::{render::RenderState, string::intern::Intern, string::intern::StringId, Style};
+use crate::{render::RenderState, string::doc_string::DocString, string::intern::Intern, Style};
 
 use super::{BoxedDoc, Doc, Fragment};
 use super::{StyledArena, StyledDoc};
 
 #[derive(Debug)]
 pub struct Plain {
-    string: StringId,
+    string: DocString,
 }
 
 impl Doc for Plain {
     fn render<'a>(&self, ctx: &'a StyledArena<'a>, _state: RenderState) -> StyledDoc<'a> {
-        ctx.text(ctx.get_str(self.string))
-            .annotate(Fragment::plain(self.string))
+        ctx.fragment(Fragment::plain(ctx.string_id(&self.string)))
     }
 }
 
-pub fn plain(string: impl Into<StringId>) -> Plain {
+pub fn plain(string: impl Into<DocString>) -> Plain {
     Plain {
         string: string.into(),
     }
 }
 
+/// A plain fragment containing the formatted value of `value`
+pub fn display(value: impl Display) -> Plain {
+    plain(DocString::display(value))
+}
+
 // document! {
 //     plain as Plain<'a> { string: &'a str }
 //     |plain, ctx, _| ctx.text(plain.string).annotate(Styled::plain(plain.string))
@@ -33,24 +38,34 @@ pub trait InternedStyledFragment {
     fn intern(self, intern: &mut Intern) -> StyledFragment;
 }
 
-#[derive(Debug, new)]
+#[derive(Debug)]
 pub struct StyledFragment {
-    string: StringId,
+    string: DocString,
     style: Style,
 }
 
+impl StyledFragment {
+    pub fn new(string: impl Into<DocString>, style: Style) -> StyledFragment {
+        StyledFragment {
+            string: string.into(),
+            style,
+        }
+    }
+}
+
 impl Doc for StyledFragment {
     fn render<'a>(&self, ctx: &'a StyledArena<'a>, _state: RenderState) -> StyledDoc<'a> {
-        ctx.text(ctx.get_str(self.string))
-            .annotate(Fragment::new(self.string, self.style))
+        ctx.fragment(Fragment::new(ctx.string_id(&self.string), self.style))
     }
 }
 
-pub fn styled(string: impl Into<StringId>, style: Style) -> StyledFragment {
-    StyledFragment {
-        string: string.into(),
-        style,
-    }
+pub fn styled(string: impl Into<DocString>, style: Style) -> StyledFragment {
+    StyledFragment::new(string, style)
+}
+
+/// A styled fragment containing the formatted value of `value`
+pub fn styled_display(value: impl Display, style: Style) -> StyledFragment {
+    styled(DocString::display(value), style)
 }
 
 // document! {
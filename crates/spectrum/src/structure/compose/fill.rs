@@ -0,0 +1,215 @@
+use pretty::DocAllocator;
+
+use crate::{
+    render::{Nesting, RenderState},
+    string::doc_string::DocString,
+    Doc, Style,
+};
+
+use super::{Fragment, StyledArena, StyledDoc};
+
+#[derive(Debug)]
+enum Piece {
+    Text(DocString, Style),
+    Break,
+}
+
+/// A paragraph of text that is reflowed to fit the page.
+///
+/// The text is split into words at whitespace, and the words are joined by soft breaks, so
+/// each line holds as many words as fit before the line breaks. Runs of whitespace (including
+/// newlines) collapse into a single break, and each word keeps the style of the text it came
+/// from. Use `line_break` for a break that is always taken.
+///
+/// Continuation lines start at the current nesting level, so a `Fill` inside a `Nested` stays
+/// inside it. `hang` indents the continuation lines further.
+///
+/// ```
+/// use spectrum::{fill, render::Nesting, EmitBackendTrait, EmitPlain};
+///
+/// let paragraph = fill("expected the suite to pass, but two of its specs failed")
+///     .hang(Nesting::Exact(2));
+///
+/// assert_eq!(
+///     EmitPlain.render(&paragraph, 30).unwrap(),
+///     "expected the suite to pass,\n  but two of its specs failed"
+/// );
+/// ```
+#[derive(Debug, Default)]
+pub struct Fill {
+    pieces: Vec<Piece>,
+    hang: Option<Nesting>,
+}
+
+/// A `Fill` containing `text`.
+pub fn fill(text: impl Into<DocString>) -> Fill {
+    Fill::default().plain(text)
+}
+
+impl Fill {
+    pub fn plain(self, text: impl Into<DocString>) -> Fill {
+        self.styled(text, Style::default())
+    }
+
+    pub fn styled(mut self, text: impl Into<DocString>, style: impl Into<Style>) -> Fill {
+        self.pieces.push(Piece::Text(text.into(), style.into()));
+        self
+    }
+
+    pub fn line_break(mut self) -> Fill {
+        self.pieces.push(Piece::Break);
+        self
+    }
+
+    /// Indent every line after the first by `indent`.
+    pub fn hang(mut self, indent: Nesting) -> Fill {
+        self.hang = Some(indent);
+        self
+    }
+}
+
+impl Doc for Fill {
+    fn render<'a>(&self, ctx: &'a StyledArena<'a>, state: RenderState) -> StyledDoc<'a> {
+        let mut doc = ctx.nil();
+
+        // Whitespace only turns into a break once there's a word on both sides of it
+        let mut at_start = true;
+        let mut space = false;
+
+        for piece in &self.pieces {
+            let (text, style) = match piece {
+                Piece::Text(text, style) => (ctx.get_str(ctx.string_id(text)), *style),
+                Piece::Break => {
+                    doc = doc.append(ctx.hardline());
+                    at_start = true;
+                    space = false;
+                    continue;
+                }
+            };
+
+            let mut rest = text;
+
+            while let Some(c) = rest.chars().next() {
+                let is_space = c.is_whitespace();
+                let end = rest
+                    .find(|c: char| c.is_whitespace() != is_space)
+                    .unwrap_or(rest.len());
+
+                if is_space {
+                    space = !at_start;
+                } else {
+                    if space {
+                        doc = doc.append(ctx.softline());
+                        space = false;
+                    }
+
+                    let word = ctx.alloc_str(&rest[..end]);
+                    doc = doc.append(ctx.fragment(Fragment::new(word, style)));
+                    at_start = false;
+                }
+
+                rest = &rest[end..];
+            }
+        }
+
+        match self.hang {
+            Some(indent) => doc.nest(state.size(indent)),
+            None => doc,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        empty, prelude::test::*, Color, EmitBackendTrait, EmitForTest, EmitPlain, Nested, BOUNDARY,
+    };
+
+    use super::*;
+
+    const TEXT: &str = "the quick brown fox jumps over the lazy dog";
+
+    #[test]
+    fn fill_reflows_to_width() -> TestResult {
+        assert_eq!(EmitPlain.render(&fill(TEXT), 80)?, TEXT);
+        assert_eq!(
+            EmitPlain.render(&fill(TEXT), 16)?,
+            "the quick brown\nfox jumps over\nthe lazy dog"
+        );
+
+        // Words that are wider than the page get a line of their own
+        assert_eq!(
+            EmitPlain.render(&fill("a supercalifragilistic word"), 10)?,
+            "a\nsupercalifragilistic\nword"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn fill_collapses_whitespace() -> TestResult {
+        let paragraph = fill("  the quick\n  brown   fox ").plain(" jumps");
+
+        assert_eq!(
+            EmitPlain.render(&paragraph, 80)?,
+            "the quick brown fox jumps"
+        );
+
+        let paragraph = fill("first paragraph").line_break().plain(" second one");
+
+        assert_eq!(
+            EmitPlain.render(&paragraph, 80)?,
+            "first paragraph\nsecond one"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn fill_preserves_styles() -> TestResult {
+        let paragraph = fill("expected ")
+            .styled("two specs", Color::Green)
+            .plain(" to pass, but ")
+            .styled("one", Color::Red)
+            .plain(" failed");
+
+        assert_eq!(
+            EmitForTest.render(&paragraph, 20)?,
+            "[normal:expected][normal: ][Green:two][normal: ][Green:specs]\n\
+             [normal:to][normal: ][normal:pass,][normal: ][normal:but][normal: ][Red:one]\n\
+             [normal:failed]"
+        );
+
+        // Text that isn't separated by whitespace stays together, even across styles
+        let paragraph = fill("see ").styled("Cargo.toml", Color::Blue).plain(",");
+
+        assert_eq!(
+            EmitForTest.render(&paragraph, 10)?,
+            "[normal:see]\n[Blue:Cargo.toml][normal:,]"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn fill_hang_and_nesting() -> TestResult {
+        let paragraph = fill(TEXT).hang(Nesting::Exact(4));
+
+        assert_eq!(
+            EmitPlain.render(&paragraph, 20)?,
+            "the quick brown fox\n    jumps over the\n    lazy dog"
+        );
+
+        let nested = list![
+            "failure:",
+            Nested::once(fill(TEXT).hang(Nesting::Configured(1)), BOUNDARY(), empty())
+        ];
+
+        assert_eq!(
+            EmitPlain.render(&nested, 20)?,
+            "failure:\n  the quick brown\n    fox jumps over\n    the lazy dog"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,397 @@
+//! Laid out lines of styled text.
+//!
+//! Some docs (like `Table`) need to know what their children look like once they're laid out,
+//! so that they can measure, wrap or truncate them. `layout_lines` lays out a doc at a fixed
+//! width and collects the result as a list of `Line`s, which can be turned back into a doc with
+//! `line_doc`.
+
+use pretty::DocAllocator;
+
+use crate::{
+    render::RenderState,
+    string::width::{display_width, split_at_width},
+    Doc, Style,
+};
+
+use super::{Fragment, StyledArena, StyledDoc};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) struct Span {
+    pub(crate) text: String,
+    pub(crate) style: Style,
+}
+
+impl Span {
+    pub(crate) fn new(text: impl Into<String>, style: Style) -> Span {
+        Span {
+            text: text.into(),
+            style,
+        }
+    }
+
+    fn width(&self) -> usize {
+        display_width(&self.text)
+    }
+}
+
+pub(crate) type Line = Vec<Span>;
+
+/// Lay out `doc` at `width` columns, and collect the lines it produced.
+pub(crate) fn layout_lines<'a>(
+    ctx: &'a StyledArena<'a>,
+    doc: StyledDoc<'a>,
+    width: usize,
+) -> Vec<Line> {
+    let mut collector = LineCollector {
+        arena: ctx,
+        annotations: 0,
+        lines: vec![vec![]],
+    };
+
+    // Collecting into memory can't fail
+    doc.into_doc().render_raw(width, &mut collector).unwrap();
+
+    collector.lines
+}
+
+pub(crate) fn line_width(line: &[Span]) -> usize {
+    line.iter().map(Span::width).sum()
+}
+
+/// Turn a line back into a doc.
+pub(crate) fn line_doc<'a>(ctx: &'a StyledArena<'a>, line: &[Span]) -> StyledDoc<'a> {
+    let mut doc = ctx.nil();
+
+    for span in line {
+        doc = doc.append(ctx.fragment(Fragment::new(ctx.alloc_str(&span.text), span.style)));
+    }
+
+    doc
+}
+
+/// A doc made of a line that has already been laid out.
+#[derive(Debug)]
+pub(crate) struct LineDoc(pub(crate) Line);
+
+impl Doc for LineDoc {
+    fn render<'a>(&self, ctx: &'a StyledArena<'a>, _state: RenderState) -> StyledDoc<'a> {
+        line_doc(ctx, &self.0)
+    }
+}
+
+/// Turn a list of lines back into a doc, separating them with hard line breaks.
+pub(crate) fn lines_doc<'a>(ctx: &'a StyledArena<'a>, lines: &[Line]) -> StyledDoc<'a> {
+    let mut doc = ctx.nil();
+
+    for (index, line) in lines.iter().enumerate() {
+        if index > 0 {
+            doc = doc.append(ctx.hardline());
+        }
+
+        doc = doc.append(line_doc(ctx, line));
+    }
+
+    doc
+}
+
+/// Split a line into the longest prefix that fits in `width` columns, and the rest.
+pub(crate) fn split_line(line: Line, width: usize) -> (Line, Line) {
+    let mut head = vec![];
+    let mut remaining = width;
+    let mut spans = line.into_iter();
+
+    for span in &mut spans {
+        let span_width = span.width();
+
+        if span_width <= remaining {
+            remaining -= span_width;
+            head.push(span);
+            continue;
+        }
+
+        let (fits, rest) = split_at_width(&span.text, remaining);
+        let mut tail = vec![];
+
+        if !fits.is_empty() {
+            head.push(Span::new(fits, span.style));
+        }
+
+        if !rest.is_empty() {
+            tail.push(Span::new(rest, span.style));
+        }
+
+        tail.extend(spans);
+        return (head, tail);
+    }
+
+    (head, vec![])
+}
+
+/// Cut a line down to `width` columns, replacing the end of the line with `…` if it was too
+/// long.
+pub(crate) fn truncate_line(line: Line, width: usize) -> Line {
+    if line_width(&line) <= width {
+        return line;
+    }
+
+    if width == 0 {
+        return vec![];
+    }
+
+    let style = line.last().map(|span| span.style).unwrap_or_default();
+    let (mut head, tail) = split_line(line, width - 1);
+    let style = tail.first().map(|span| span.style).unwrap_or(style);
+
+    head.push(Span::new("…", style));
+    head
+}
+
+/// Remove unstyled whitespace from the end of a line.
+pub(crate) fn trim_end(line: &mut Line) {
+    while let Some(span) = line.last_mut() {
+        if !span.style.is_plain() {
+            break;
+        }
+
+        let trimmed = span.text.trim_end().len();
+
+        if trimmed == 0 {
+            line.pop();
+        } else {
+            span.text.truncate(trimmed);
+            break;
+        }
+    }
+}
+
+/// Break a line into lines that fit in `width` columns, breaking at whitespace where possible.
+///
+/// Whitespace at the point where a line is broken is dropped. Words that are wider than `width`
+/// on their own are broken wherever they need to be.
+pub(crate) fn wrap_line(line: Line, width: usize) -> Vec<Line> {
+    let width = width.max(1);
+    let mut lines = vec![];
+    let mut current: Line = vec![];
+    let mut current_width = 0;
+    let mut space: Line = vec![];
+
+    for word in words(line) {
+        if word.is_space {
+            space.extend(word.spans);
+            continue;
+        }
+
+        let space_width = line_width(&space);
+        let word_width = line_width(&word.spans);
+
+        if current_width + space_width + word_width <= width {
+            current.append(&mut space);
+            current.extend(word.spans);
+            current_width += space_width + word_width;
+            continue;
+        }
+
+        space.clear();
+
+        if !current.is_empty() {
+            lines.push(std::mem::take(&mut current));
+        }
+
+        let mut rest = word.spans;
+
+        while line_width(&rest) > width {
+            let (head, tail) = split_line(rest, width);
+
+            if head.is_empty() {
+                // A single character that's wider than the column; give it a line of its own
+                let (head, tail) = split_first_char(tail);
+                lines.push(head);
+                rest = tail;
+            } else {
+                lines.push(head);
+                rest = tail;
+            }
+        }
+
+        current_width = line_width(&rest);
+        current = rest;
+    }
+
+    if !current.is_empty() || lines.is_empty() {
+        lines.push(current);
+    }
+
+    lines
+}
+
+fn split_first_char(mut line: Line) -> (Line, Line) {
+    let first = line.remove(0);
+    let mut chars = first.text.chars();
+    let c = chars.next().unwrap();
+    let rest = chars.as_str();
+
+    if !rest.is_empty() {
+        line.insert(0, Span::new(rest, first.style));
+    }
+
+    (vec![Span::new(c.to_string(), first.style)], line)
+}
+
+struct Word {
+    spans: Line,
+    is_space: bool,
+}
+
+/// Split a line into alternating runs of whitespace and words. A word can span several
+/// differently styled spans.
+fn words(line: Line) -> Vec<Word> {
+    let mut words: Vec<Word> = vec![];
+
+    for span in line {
+        let mut rest = &span.text[..];
+
+        while let Some(c) = rest.chars().next() {
+            let is_space = c.is_whitespace();
+            let end = rest
+                .find(|c: char| c.is_whitespace() != is_space)
+                .unwrap_or(rest.len());
+            let piece = Span::new(&rest[..end], span.style);
+
+            match words.last_mut() {
+                Some(word) if word.is_space == is_space => word.spans.push(piece),
+                _ => words.push(Word {
+                    spans: vec![piece],
+                    is_space,
+                }),
+            }
+
+            rest = &rest[end..];
+        }
+    }
+
+    words
+}
+
+/// Collects the output of `pretty` into lines of spans. Like `Renderer`, it takes the text of
+/// each fragment from its annotation, and ignores the stand-in text inside the annotation.
+struct LineCollector<'a> {
+    arena: &'a StyledArena<'a>,
+    annotations: usize,
+    lines: Vec<Line>,
+}
+
+impl LineCollector<'_> {
+    fn push(&mut self, span: Span) {
+        if !span.text.is_empty() {
+            self.lines.last_mut().unwrap().push(span);
+        }
+    }
+}
+
+impl pretty::Render for LineCollector<'_> {
+    type Error = std::fmt::Error;
+
+    fn write_str(&mut self, s: &str) -> Result<usize, Self::Error> {
+        if self.annotations == 0 {
+            let mut parts = s.split('\n');
+
+            self.push(Span::new(parts.next().unwrap(), Style::default()));
+
+            for part in parts {
+                self.lines.push(vec![]);
+                self.push(Span::new(part, Style::default()));
+            }
+        }
+
+        Ok(s.len())
+    }
+
+    fn fail_doc(&self) -> Self::Error {
+        std::fmt::Error
+    }
+}
+
+impl pretty::RenderAnnotated<'_, Fragment> for LineCollector<'_> {
+    fn push_annotation(&mut self, annotation: &Fragment) -> Result<(), Self::Error> {
+        self.annotations += 1;
+
+        let text = self.arena.get_str(annotation.id());
+        let mut parts = text.split('\n');
+
+        self.push(Span::new(parts.next().unwrap(), annotation.style()));
+
+        for part in parts {
+            self.lines.push(vec![]);
+            self.push(Span::new(part, annotation.style()));
+        }
+
+        Ok(())
+    }
+
+    fn pop_annotation(&mut self) -> Result<(), Self::Error> {
+        self.annotations -= 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Color;
+
+    use super::*;
+
+    fn plain(text: &str) -> Span {
+        Span::new(text, Style::default())
+    }
+
+    fn red(text: &str) -> Span {
+        Span::new(text, Color::Red.into())
+    }
+
+    fn texts(lines: Vec<Line>) -> Vec<String> {
+        lines
+            .into_iter()
+            .map(|line| line.into_iter().map(|span| span.text).collect())
+            .collect()
+    }
+
+    #[test]
+    fn wrap_at_whitespace() {
+        let line = vec![plain("hello world, "), red("goodbye"), plain(" world")];
+
+        assert_eq!(
+            texts(wrap_line(line.clone(), 12)),
+            vec!["hello world,", "goodbye", "world"]
+        );
+        assert_eq!(
+            texts(wrap_line(line, 13)),
+            vec!["hello world,", "goodbye world"]
+        );
+    }
+
+    #[test]
+    fn wrap_long_words() {
+        let line = vec![plain("a "), plain("abc"), red("defgh"), plain(" b")];
+
+        assert_eq!(
+            texts(wrap_line(line, 3)),
+            vec!["a", "abc", "def", "gh", "b"]
+        );
+        assert_eq!(
+            texts(wrap_line(vec![plain("日本語")], 1)),
+            vec!["日", "本", "語"]
+        );
+    }
+
+    #[test]
+    fn truncate() {
+        let line = vec![plain("hello "), red("world")];
+
+        assert_eq!(truncate_line(line.clone(), 11), line);
+        assert_eq!(
+            truncate_line(line.clone(), 8),
+            vec![plain("hello "), red("w"), red("…")]
+        );
+        assert_eq!(truncate_line(line, 6), vec![plain("hello"), plain("…")]);
+    }
+}
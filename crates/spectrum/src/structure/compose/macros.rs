@@ -19,12 +19,7 @@ macro_rules! list_impl {
         #[allow(unused)]
         use $crate::Doc;
 
-        #[allow(unused_mut)]
-        let mut vec: Vec<$crate::BoxedDoc> = vec![];
-
-        $(
-            vec.push($expr.boxed());
-        )*
+        let vec: Vec<$crate::BoxedDoc> = vec![$($expr.boxed()),*];
 
         vec
     }}
@@ -17,14 +17,23 @@ pub mod list;
 #[macro_use]
 mod join;
 
+pub mod diff;
+pub mod fill;
+mod lines;
 pub mod render_context;
 mod renderer;
+pub mod snippet;
 mod subdoc;
+pub mod table;
+pub mod tree;
 
 use derive_new::new;
-use std::fmt::Debug;
+use elsa::FrozenVec;
+use std::{borrow::Cow, fmt::Debug, sync::Arc};
 
+use crate::string::doc_string::DocString;
 use crate::string::intern::{DerefInternedString, Intern, StringId};
+use crate::string::width::layout_text;
 use crate::{render::RenderState, Style};
 
 pub use self::list::{DocList, Group};
@@ -59,18 +68,14 @@ impl Fragment {
 
 impl Doc for Fragment {
     fn render<'a>(&self, ctx: &'a StyledArena<'a>, _state: RenderState) -> StyledDoc<'a> {
-        let str = ctx.get_str(self.fragment);
-        // let doc = str.annotate(*self);
-
-        let text = ctx.text(str);
-        text.annotate(*self)
-        // ctx.clone().text(ctx.get_str(self.fragment)).annotate(*self)
+        ctx.fragment(*self)
     }
 }
 
 pub struct StyledArenaInternal<'arena> {
     arena: pretty::Arena<'arena, Fragment>,
     intern: &'arena Intern,
+    strings: FrozenVec<String>,
 }
 
 pub struct StyledArena<'arena> {
@@ -83,12 +88,43 @@ impl<'arena> StyledArena<'arena> {
             arena: StyledArenaInternal {
                 arena: pretty::Arena::new(),
                 intern,
+                strings: FrozenVec::new(),
             },
         }
     }
 
-    pub fn get_str(&self, id: StringId) -> &str {
-        self.arena.intern.get(id)
+    /// The `Intern` that this arena's fragments were interned into.
+    pub fn intern(&self) -> &'arena Intern {
+        self.arena.intern
+    }
+
+    pub fn get_str(&'arena self, id: StringId) -> &'arena str {
+        match id {
+            StringId::Arena(index) => self.arena.strings.get(index).unwrap(),
+            _ => self.arena.intern.get(id),
+        }
+    }
+
+    /// Copy `string` into the arena, so that it lives as long as the docs allocated in it.
+    pub fn alloc_str(&self, string: &str) -> StringId {
+        let id = StringId::Arena(self.arena.strings.len());
+        self.arena.strings.push(string.to_string());
+        id
+    }
+
+    /// The `StringId` of a `DocString`, copying it into the arena if the doc owns it.
+    pub fn string_id(&self, string: &DocString) -> StringId {
+        match string {
+            DocString::Id(id) => *id,
+            DocString::Owned(string) => self.alloc_str(string),
+            DocString::Shared(string) => self.alloc_str(string),
+        }
+    }
+
+    /// Lay out a fragment, measuring it by its display width rather than its length in bytes.
+    pub fn fragment(&'arena self, fragment: Fragment) -> StyledDoc<'arena> {
+        self.text(layout_text(self.get_str(fragment.id())))
+            .annotate(fragment)
     }
 }
 
@@ -144,20 +180,43 @@ impl Doc for BoxedDoc {
     }
 }
 
-impl Doc for &'static str {
+impl Doc for String {
+    fn render<'a>(&self, ctx: &'a StyledArena<'a>, _state: RenderState) -> StyledDoc<'a> {
+        ctx.fragment(Fragment::plain(ctx.alloc_str(self)))
+    }
+}
+
+impl Doc for Cow<'static, str> {
     fn render<'a>(&self, ctx: &'a StyledArena<'a>, _state: RenderState) -> StyledDoc<'a> {
-        let text = StringId::Literal(self);
+        match self {
+            Cow::Borrowed(string) => ctx.fragment(Fragment::plain(StringId::Literal(string))),
+            Cow::Owned(string) => ctx.fragment(Fragment::plain(ctx.alloc_str(string))),
+        }
+    }
+}
 
-        ctx.text(*self).annotate(Fragment::plain(text))
+impl Doc for Arc<str> {
+    fn render<'a>(&self, ctx: &'a StyledArena<'a>, _state: RenderState) -> StyledDoc<'a> {
+        ctx.fragment(Fragment::plain(ctx.alloc_str(self)))
+    }
+}
+
+impl Doc for &'static str {
+    fn render<'a>(&self, ctx: &'a StyledArena<'a>, _state: RenderState) -> StyledDoc<'a> {
+        ctx.fragment(Fragment::plain(StringId::Literal(self)))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
-        emit::buf::Buf, prelude::test::*, render::RenderConfig, string::intern::Intern, EmitPlain,
+        display, emit::buf::Buf, plain, prelude::test::*, render::RenderConfig,
+        string::intern::Intern, styled, styled_display, Color, EmitBackendTrait, EmitForTest,
+        EmitPlain,
     };
 
+    use std::{borrow::Cow, sync::Arc, time::Duration};
+
     use super::{docs::empty, StyledArena};
     use super::{render_context::RenderContext, Doc, GAP};
     use textwrap::dedent;
@@ -213,6 +272,122 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn layout_measures_display_width() -> TestResult {
+        // 6 + 1 + 8 columns, but 22 bytes
+        let doc = group!["日本語", GAP(), "テキスト"];
+
+        assert_eq!(render(&doc, 15)?, "日本語 テキスト");
+        assert_eq!(render(&doc, 14)?, "日本語\nテキスト");
+
+        let doc = group!["🦀🦀", GAP(), "crab"];
+
+        assert_eq!(render(&doc, 9)?, "🦀🦀 crab");
+        assert_eq!(render(&doc, 8)?, "🦀🦀\ncrab");
+
+        Ok(())
+    }
+
+    #[test]
+    fn layout_ignores_zero_width_chars() -> TestResult {
+        // "café" spelled with a combining accent is 4 columns wide, but 6 bytes long
+        let doc = group!["cafe\u{301}", GAP(), "ok"];
+
+        assert_eq!(render(&doc, 7)?, "cafe\u{301} ok");
+        assert_eq!(render(&doc, 6)?, "cafe\u{301}\nok");
+
+        Ok(())
+    }
+
+    #[test]
+    fn render_wide_styled_fragments() -> TestResult {
+        let doc = group![
+            styled("日本語", Color::Red.into()),
+            GAP(),
+            styled("🦀", Color::Blue.into())
+        ];
+
+        assert_eq!(
+            render_with(&doc, EmitForTest, 9)?,
+            "[Red:日本語][normal: ][Blue:🦀]"
+        );
+        assert_eq!(
+            render_with(&doc, EmitForTest, 8)?,
+            "[Red:日本語]\n[Blue:🦀]"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn render_interned_fragments() -> TestResult {
+        let mut intern = Intern::new();
+        let names: Vec<String> = (1..=3).map(|i| format!("arg{}", i)).collect();
+
+        let doc = group![
+            "f(",
+            plain(intern.intern(names[0].clone())),
+            ",",
+            GAP(),
+            styled(intern.intern(names[1].clone()), Color::Red.into()),
+            ",",
+            GAP(),
+            plain(intern.intern(&names[2][..])),
+            ")"
+        ];
+
+        assert_eq!(
+            EmitPlain.render_with_intern(&doc, &intern, 80)?,
+            "f(arg1, arg2, arg3)"
+        );
+        assert_eq!(
+            EmitForTest.render_with_intern(&doc, &intern, 10)?,
+            "[normal:f(][normal:arg1][normal:,]\n[Red:arg2][normal:,]\n[normal:arg3][normal:)]"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn render_owned_strings() -> TestResult {
+        let owned: String = format!("{}-{}", "owned", 1);
+        let cow: Cow<'static, str> = Cow::Owned("cow".to_string());
+        let borrowed: Cow<'static, str> = Cow::Borrowed("borrowed");
+        let shared: Arc<str> = Arc::from("shared");
+
+        let doc = group![
+            owned,
+            GAP(),
+            cow,
+            GAP(),
+            borrowed,
+            GAP(),
+            shared.clone(),
+            GAP(),
+            display(3.5),
+            GAP(),
+            styled_display(Duration::from_millis(20).as_millis(), Color::Red.into()),
+            GAP(),
+            styled(format!("{}", 'x'), Color::Blue.into())
+        ];
+
+        assert_eq!(
+            render_with(&doc, EmitForTest, 80)?,
+            "[normal:owned-1][normal: ][normal:cow][normal: ][normal:borrowed][normal: ]\
+             [normal:shared][normal: ][normal:3.5][normal: ][Red:20][normal: ][Blue:x]"
+        );
+        assert_eq!(
+            render(&doc, 10)?,
+            "owned-1\ncow\nborrowed\nshared\n3.5\n20\nx"
+        );
+
+        // Rendering copies the string into the arena, which is dropped once rendering is done
+        drop(doc);
+        assert_eq!(Arc::strong_count(&shared), 1);
+
+        Ok(())
+    }
+
     fn render(text: &impl Doc, page_size: usize) -> Result<String, std::fmt::Error> {
         Buf::collect_string(|writer| {
             let intern = Intern::new();
@@ -224,6 +399,21 @@ mod tests {
         })
     }
 
+    fn render_with(
+        text: &impl Doc,
+        backend: impl EmitBackendTrait,
+        page_size: usize,
+    ) -> Result<String, std::fmt::Error> {
+        Buf::collect_string(|writer| {
+            let intern = Intern::new();
+            let arena = StyledArena::new(&intern);
+            let mut context = RenderContext::new(arena);
+            context.render(text, backend, writer, RenderConfig::width(page_size))?;
+
+            Ok(())
+        })
+    }
+
     fn strip(input: &str) -> String {
         let lines: Vec<&str> = input.split('\n').collect();
         let string = lines[1..lines.len() - 1].to_vec().join("\n");
@@ -4,13 +4,15 @@ use pretty::{DocAllocator, DocPtr};
 
 use crate::{
     render::{RenderConfig, RenderState},
-    string::intern::Intern,
     EmitBackendTrait,
 };
 
 use super::{renderer::Renderer, Doc, Fragment, StyledArena};
 
-#[allow(dead_code)]
+/// Renders docs into a writer.
+///
+/// Fragments are looked up in the `StyledArena`, so any `StringId::Id` in the doc must come
+/// from the `Intern` that the arena was built with.
 #[derive(new)]
 pub struct RenderContext<'arena> {
     arena: StyledArena<'arena>,
@@ -25,9 +27,7 @@ impl<'arena> RenderContext<'arena> {
         writer: &mut dyn std::io::Write,
         config: RenderConfig,
     ) -> Result<(), std::fmt::Error> {
-        // let string = Buf::collect_string(|writer| {
-        let intern = Intern::default();
-        let mut renderer = Renderer::new(writer, &intern, backend);
+        let mut renderer = Renderer::new(writer, &self.arena, backend);
         let doc = doc.render(&self.arena, RenderState::top(config));
         doc.into_doc()
             .render_raw(config.column_size, &mut renderer)?;
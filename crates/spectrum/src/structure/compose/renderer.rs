@@ -1,48 +1,89 @@
 use format::Display;
 
-use crate::{string::intern::Intern, EmitBackendTrait, Fragment, Style};
+use crate::{EmitBackendTrait, Fragment, Style};
 
-pub struct Renderer<'write, 'intern> {
-    ann: bool,
+use super::StyledArena;
+
+/// Writes a laid out document to a backend.
+///
+/// Fragments are emitted in full when their annotation is pushed. The text that `pretty` writes
+/// inside an annotation is only a stand-in that gives the fragment its display width (see
+/// `string::width::layout_text`), so it's never written.
+///
+/// The indentation that `pretty` writes after a newline is held back until something is written
+/// after it, so that blank lines inside nested docs don't end in whitespace.
+pub struct Renderer<'write, 'arena> {
+    annotations: usize,
+    at_line_start: bool,
+    pending_indent: usize,
     write: &'write mut dyn std::io::Write,
-    intern: &'intern Intern,
+    arena: &'arena StyledArena<'arena>,
     backend: Box<dyn EmitBackendTrait + 'static>,
 }
 
-impl<'write, 'intern> Renderer<'write, 'intern> {
+impl<'write, 'arena> Renderer<'write, 'arena> {
     #[allow(unused)]
     pub fn new(
         write: &'write mut dyn std::io::Write,
-        intern: &'intern Intern,
+        arena: &'arena StyledArena<'arena>,
         backend: impl EmitBackendTrait,
-    ) -> Renderer<'write, 'intern> {
+    ) -> Renderer<'write, 'arena> {
         Renderer {
-            ann: false,
-            intern,
+            annotations: 0,
+            at_line_start: true,
+            pending_indent: 0,
+            arena,
             write,
             backend: Box::new(backend),
         }
     }
 }
 
+impl Renderer<'_, '_> {
+    fn emit_plain(&mut self, s: &str) -> Result<(), std::fmt::Error> {
+        let Self { write, backend, .. } = self;
+        write!(
+            write,
+            "{}",
+            format::lazy_format!(|f| backend
+                .emit(f, s, Style::default())
+                .map_err(|_| std::fmt::Error))
+        )
+        .map_err(|_| std::fmt::Error)
+    }
+
+    fn flush_indent(&mut self) -> Result<(), std::fmt::Error> {
+        if self.pending_indent > 0 {
+            let indent = " ".repeat(self.pending_indent);
+            self.pending_indent = 0;
+            self.emit_plain(&indent)?;
+        }
+
+        self.at_line_start = false;
+        Ok(())
+    }
+}
+
 impl pretty::Render for Renderer<'_, '_> {
     type Error = std::fmt::Error;
 
     fn write_str(&mut self, s: &str) -> Result<usize, Self::Error> {
-        if self.ann {
-            self.ann = false;
-        } else {
-            let Self { write, backend, .. } = self;
-            write!(
-                write,
-                "{}",
-                format::lazy_format!(|f| backend
-                    .emit(f, s, Style::default())
-                    .map_err(|_| std::fmt::Error))
-            )
-            .map_err(|_| std::fmt::Error)?;
+        if self.annotations == 0 && !s.is_empty() {
+            if self.at_line_start && s.bytes().all(|b| b == b' ') {
+                self.pending_indent += s.len();
+            } else {
+                if !s.starts_with('\n') {
+                    self.flush_indent()?;
+                }
+
+                self.pending_indent = 0;
+                self.emit_plain(s)?;
+                self.at_line_start = s.ends_with('\n');
+            }
         }
 
+        // This is the number of bytes consumed, not the width of the text: `pretty` uses it to
+        // advance through `s`, and measures layout on its own.
         Ok(s.len())
     }
 
@@ -53,12 +94,13 @@ impl pretty::Render for Renderer<'_, '_> {
 
 impl<'a> pretty::RenderAnnotated<'_, Fragment> for Renderer<'_, '_> {
     fn push_annotation(&mut self, annotation: &Fragment) -> Result<(), Self::Error> {
-        self.ann = true;
+        self.flush_indent()?;
+        self.annotations += 1;
 
         let Self {
             write,
             backend,
-            intern,
+            arena,
             ..
         } = self;
 
@@ -66,7 +108,7 @@ impl<'a> pretty::RenderAnnotated<'_, Fragment> for Renderer<'_, '_> {
             write,
             "{}",
             Display(move |f| backend
-                .emit(f, intern.get(annotation.id()), annotation.style())
+                .emit(f, arena.get_str(annotation.id()), annotation.style())
                 .map_err(|_| std::fmt::Error))
         )
         .map_err(|_| std::fmt::Error)?;
@@ -75,6 +117,57 @@ impl<'a> pretty::RenderAnnotated<'_, Fragment> for Renderer<'_, '_> {
     }
 
     fn pop_annotation(&mut self) -> Result<(), Self::Error> {
+        self.annotations -= 1;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        emit::buf::Buf, prelude::test::*, render::RenderConfig, string::intern::Intern,
+        structure::compose::render_context::RenderContext, Doc, EmitPlain, GAP,
+    };
+
+    use super::StyledArena;
+
+    #[test]
+    fn blank_lines_inside_nested_docs() -> TestResult {
+        let doc = group![
+            "{",
+            nest![
+                {
+                    "first",
+                    GAP(),
+                    GAP(),
+                    nest![
+                        { "second", GAP(), GAP(), "third" }
+                        before = GAP();
+                        after = GAP();
+                    ]
+                }
+                before = GAP();
+                after = GAP();
+            ],
+            "}"
+        ];
+
+        assert_eq!(
+            render(&doc, 10)?,
+            "{\n  first\n\n\n    second\n\n    third\n\n}"
+        );
+
+        Ok(())
+    }
+
+    fn render(text: &impl Doc, page_size: usize) -> Result<String, std::fmt::Error> {
+        Buf::collect_string(|writer| {
+            let intern = Intern::new();
+            let arena = StyledArena::new(&intern);
+            let mut context = RenderContext::new(arena);
+            context.render(text, EmitPlain, writer, RenderConfig::width(page_size))?;
+
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,733 @@
+use std::{collections::BTreeSet, ops::Range};
+
+use crate::{render::RenderState, string::width::display_width, Color, Doc, Style};
+
+use super::{
+    lines::{lines_doc, trim_end, Line, Span},
+    StyledArena, StyledDoc,
+};
+
+/// Whether a label points at the cause of a problem, or at context that helps to explain it.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum LabelKind {
+    /// Underlined with `^`
+    Primary,
+    /// Underlined with `-`
+    Secondary,
+}
+
+impl LabelKind {
+    fn mark(self) -> char {
+        match self {
+            LabelKind::Primary => '^',
+            LabelKind::Secondary => '-',
+        }
+    }
+
+    fn style(self) -> Style {
+        match self {
+            LabelKind::Primary => Style::from(Color::Red).bold(),
+            LabelKind::Secondary => Color::Blue.into(),
+        }
+    }
+}
+
+/// A message attached to a byte range of a `Snippet`'s source.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SnippetLabel {
+    range: Range<usize>,
+    message: String,
+    kind: LabelKind,
+}
+
+impl SnippetLabel {
+    pub fn new(kind: LabelKind, range: Range<usize>, message: impl Into<String>) -> SnippetLabel {
+        SnippetLabel {
+            range,
+            message: message.into(),
+            kind,
+        }
+    }
+
+    pub fn primary(range: Range<usize>, message: impl Into<String>) -> SnippetLabel {
+        SnippetLabel::new(LabelKind::Primary, range, message)
+    }
+
+    pub fn secondary(range: Range<usize>, message: impl Into<String>) -> SnippetLabel {
+        SnippetLabel::new(LabelKind::Secondary, range, message)
+    }
+}
+
+/// A doc that shows the lines of some source text that labels point at, in the style of rustc
+/// and codespan:
+///
+/// ```text
+///   ┌─ src/lib.rs:2:17
+///   │
+/// 2 │     let x = 1 + "a";
+///   │             -   ^^^ expected an integer
+///   │             │
+///   │             this is an integer
+///   │
+///   = note: integers can't be added to strings
+/// ```
+///
+/// Labels point at byte ranges of the source. A label that covers several lines is drawn with
+/// a line in the gutter that connects its start to its end. Lines that no label points at are
+/// left out, and gaps between the lines that are shown are marked with `·`.
+#[derive(Debug, Clone)]
+pub struct Snippet {
+    source: String,
+    origin: Option<String>,
+    labels: Vec<SnippetLabel>,
+    notes: Vec<String>,
+}
+
+impl Snippet {
+    pub fn new(source: impl Into<String>) -> Snippet {
+        Snippet {
+            source: source.into(),
+            origin: None,
+            labels: vec![],
+            notes: vec![],
+        }
+    }
+
+    /// Where the source came from (usually a file name). It's shown above the snippet, along
+    /// with the position of the first primary label.
+    pub fn origin(mut self, origin: impl Into<String>) -> Snippet {
+        self.origin = Some(origin.into());
+        self
+    }
+
+    pub fn label(mut self, label: SnippetLabel) -> Snippet {
+        self.labels.push(label);
+        self
+    }
+
+    pub fn labels(mut self, labels: impl IntoIterator<Item = SnippetLabel>) -> Snippet {
+        self.labels.extend(labels);
+        self
+    }
+
+    pub fn note(mut self, note: impl Into<String>) -> Snippet {
+        self.notes.push(note.into());
+        self
+    }
+}
+
+/// A line of the source, without its line ending.
+struct SourceLine<'s> {
+    start: usize,
+    text: &'s str,
+}
+
+/// A position in the source, as a line index and a display column.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+struct Position {
+    line: usize,
+    column: usize,
+}
+
+struct Located<'l> {
+    label: &'l SnippetLabel,
+    start: Position,
+    /// The column just past the end of the label. For multi-line labels, this is a column in
+    /// the last line of the label.
+    end: Position,
+}
+
+impl Located<'_> {
+    fn is_multiline(&self) -> bool {
+        self.start.line != self.end.line
+    }
+
+    /// Is the gutter line of a multi-line label drawn next to `line`?
+    fn spans(&self, line: usize) -> bool {
+        self.start.line < line && line <= self.end.line
+    }
+
+    fn style(&self) -> Style {
+        self.label.kind.style()
+    }
+
+    fn mark(&self) -> char {
+        self.label.kind.mark()
+    }
+}
+
+fn source_lines(source: &str) -> Vec<SourceLine<'_>> {
+    let mut start = 0;
+
+    source
+        .split('\n')
+        .map(|text| {
+            let line = SourceLine {
+                start,
+                text: text.strip_suffix('\r').unwrap_or(text),
+            };
+
+            start += text.len() + 1;
+            line
+        })
+        .collect()
+}
+
+/// The last char boundary in `text` at or before `index`, so that a byte offset that lands inside
+/// a character still slices it.
+fn char_boundary(text: &str, index: usize) -> usize {
+    let mut end = index.min(text.len());
+
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    end
+}
+
+fn locate(lines: &[SourceLine<'_>], offset: usize) -> Position {
+    let line = lines
+        .iter()
+        .rposition(|line| line.start <= offset)
+        .unwrap_or(0);
+
+    let text = lines[line].text;
+    let end = char_boundary(text, offset - lines[line].start);
+
+    Position {
+        line,
+        column: display_width(&text[..end]),
+    }
+}
+
+/// A row of annotations, drawn one column at a time.
+struct Row {
+    cells: Vec<(char, Style)>,
+    message: Option<(String, Style)>,
+}
+
+impl Row {
+    fn new() -> Row {
+        Row {
+            cells: vec![],
+            message: None,
+        }
+    }
+
+    fn set(&mut self, column: usize, c: char, style: Style) {
+        if self.cells.len() <= column {
+            self.cells.resize(column + 1, (' ', Style::default()));
+        }
+
+        self.cells[column] = (c, style);
+    }
+
+    fn fill(&mut self, columns: Range<usize>, c: char, style: Style) {
+        for column in columns {
+            self.set(column, c, style);
+        }
+    }
+
+    fn into_spans(self) -> Line {
+        let mut line: Line = vec![];
+
+        for (c, style) in self.cells {
+            match line.last_mut() {
+                Some(span) if span.style == style => span.text.push(c),
+                _ => line.push(Span::new(c.to_string(), style)),
+            }
+        }
+
+        if let Some((message, style)) = self.message {
+            line.push(Span::new(message, style));
+        }
+
+        line
+    }
+}
+
+struct Layout<'s> {
+    lines: Vec<SourceLine<'s>>,
+    labels: Vec<Located<'s>>,
+    /// The multi-line labels, in the order of their gutter columns
+    multiline: Vec<usize>,
+    number_width: usize,
+    gutter_style: Style,
+}
+
+impl<'s> Layout<'s> {
+    fn new(snippet: &'s Snippet) -> Layout<'s> {
+        let lines = source_lines(&snippet.source);
+        let len = snippet.source.len();
+
+        let mut labels: Vec<Located<'_>> = snippet
+            .labels
+            .iter()
+            .map(|label| {
+                let start = label.range.start.min(len);
+                let end = label.range.end.min(len).max(start);
+                let start_position = locate(&lines, start);
+
+                let end_position = if end > start {
+                    let last = locate(&lines, end - 1);
+
+                    Position {
+                        line: last.line,
+                        column: locate(&lines, end).column.max(last.column + 1),
+                    }
+                } else {
+                    start_position
+                };
+
+                Located {
+                    label,
+                    start: start_position,
+                    end: end_position,
+                }
+            })
+            .collect();
+
+        labels.sort_by_key(|label| (label.start, label.end));
+
+        let multiline = (0..labels.len())
+            .filter(|index| labels[*index].is_multiline())
+            .collect();
+
+        let last_line = labels.iter().map(|label| label.end.line).max().unwrap_or(0);
+
+        Layout {
+            lines,
+            labels,
+            multiline,
+            number_width: (last_line + 1).to_string().len(),
+            gutter_style: Color::Blue.into(),
+        }
+    }
+
+    fn shown_lines(&self) -> BTreeSet<usize> {
+        let mut shown = BTreeSet::new();
+
+        for label in &self.labels {
+            shown.extend(label.start.line..=label.end.line);
+        }
+
+        shown
+    }
+
+    /// The gutter in front of a line: a line number (or blank space), and a separator.
+    fn gutter(&self, number: Option<usize>, separator: char) -> Line {
+        let number = match number {
+            Some(number) => format!("{:>width$}", number, width = self.number_width),
+            None => " ".repeat(self.number_width),
+        };
+
+        vec![
+            Span::new(format!("{} {}", number, separator), self.gutter_style),
+            Span::new(" ", Style::default()),
+        ]
+    }
+
+    /// Draw the gutter lines of the multi-line labels whose gutter line is drawn next to `line`
+    /// (according to `is_drawn`).
+    fn multiline_gutter(&self, row: &mut Row, is_drawn: impl Fn(usize, &Located<'_>) -> bool) {
+        for (slot, index) in self.multiline.iter().enumerate() {
+            let label = &self.labels[*index];
+
+            if is_drawn(slot, label) {
+                row.set(slot * 2, '│', label.style());
+            }
+        }
+    }
+
+    /// The width of the space for the gutter lines of multi-line labels.
+    fn multiline_width(&self) -> usize {
+        self.multiline.len() * 2
+    }
+
+    fn annotated_line(&self, line: usize, out: &mut Vec<Line>) {
+        let offset = self.multiline_width();
+
+        let mut source = Row::new();
+        self.multiline_gutter(&mut source, |_, label| label.spans(line));
+        source.fill(source.cells.len()..offset, ' ', Style::default());
+        source.message = Some((self.lines[line].text.to_string(), Style::default()));
+        self.push(Some(line + 1), source.into_spans(), out);
+
+        for row in self.single_line_rows(line) {
+            self.push(None, row.into_spans(), out);
+        }
+
+        // The start of multi-line labels, which point at their first column
+        for (slot, index) in self.multiline.iter().enumerate() {
+            let label = &self.labels[*index];
+
+            if label.start.line != line {
+                continue;
+            }
+
+            let mut row = Row::new();
+            self.multiline_gutter(&mut row, |other, label| {
+                other < slot && (label.spans(line) || label.start.line == line)
+            });
+            row.set(slot * 2, '╭', label.style());
+            row.fill(
+                slot * 2 + 1..offset + label.start.column,
+                '─',
+                label.style(),
+            );
+            row.set(offset + label.start.column, label.mark(), label.style());
+            self.push(None, row.into_spans(), out);
+        }
+
+        // The end of multi-line labels, which point at their last column. Inner labels end
+        // before outer ones, so that the outer labels' gutter lines continue past them.
+        for (slot, index) in self.multiline.iter().enumerate().rev() {
+            let label = &self.labels[*index];
+
+            if label.end.line != line {
+                continue;
+            }
+
+            let mut row = Row::new();
+            self.multiline_gutter(&mut row, |other, label| {
+                other < slot && label.start.line <= line && line <= label.end.line
+            });
+            let last = offset + label.end.column - 1;
+            row.set(slot * 2, '╰', label.style());
+            row.fill(slot * 2 + 1..last, '─', label.style());
+            row.set(last, label.mark(), label.style());
+            row.message = message(label);
+            self.push(None, row.into_spans(), out);
+        }
+    }
+
+    /// Underline the labels that start and end on `line`, and then write their messages. The
+    /// message of the rightmost label goes right after its underline, and the others go below
+    /// it, connected to their labels by vertical lines.
+    fn single_line_rows(&self, line: usize) -> Vec<Row> {
+        let offset = self.multiline_width();
+        let gutter = |row: &mut Row| self.multiline_gutter(row, |_, label| label.spans(line));
+
+        let labels: Vec<&Located<'_>> = self
+            .labels
+            .iter()
+            .filter(|label| !label.is_multiline() && label.start.line == line)
+            .collect();
+
+        let (last, rest) = match labels.split_last() {
+            Some(split) => split,
+            None => return vec![],
+        };
+
+        let mut underline = Row::new();
+        gutter(&mut underline);
+
+        // Primary labels are drawn last, so that they win where labels overlap
+        for kind in &[LabelKind::Secondary, LabelKind::Primary] {
+            for label in labels.iter().filter(|label| label.label.kind == *kind) {
+                underline.fill(
+                    offset + label.start.column
+                        ..offset + label.end.column.max(label.start.column + 1),
+                    label.mark(),
+                    label.style(),
+                );
+            }
+        }
+
+        underline.message = message(last);
+
+        let pending: Vec<&&Located<'_>> = rest
+            .iter()
+            .filter(|label| !label.label.message.is_empty())
+            .collect();
+
+        if pending.is_empty() {
+            return vec![underline];
+        }
+
+        let connectors = |row: &mut Row, labels: &[&&Located<'_>]| {
+            for label in labels {
+                row.set(offset + label.start.column, '│', label.style());
+            }
+        };
+
+        let mut rows = vec![underline];
+
+        let mut row = Row::new();
+        gutter(&mut row);
+        connectors(&mut row, &pending);
+        rows.push(row);
+
+        for (index, label) in pending.iter().enumerate().rev() {
+            let mut row = Row::new();
+            gutter(&mut row);
+            connectors(&mut row, &pending[..index]);
+            row.fill(
+                row.cells.len()..offset + label.start.column,
+                ' ',
+                Style::default(),
+            );
+            row.message = Some((label.label.message.clone(), label.style()));
+            rows.push(row);
+        }
+
+        rows
+    }
+
+    fn push(&self, number: Option<usize>, line: Line, out: &mut Vec<Line>) {
+        let mut full = self.gutter(number, '│');
+        full.extend(line);
+        trim_end(&mut full);
+        out.push(full);
+    }
+
+    fn render(&self, snippet: &Snippet) -> Vec<Line> {
+        let mut out = vec![];
+        let blank = " ".repeat(self.number_width);
+
+        if let Some(origin) = &snippet.origin {
+            let location = self
+                .labels
+                .iter()
+                .find(|label| label.label.kind == LabelKind::Primary)
+                .or_else(|| self.labels.first())
+                .map(|label| {
+                    let line = &self.lines[label.start.line];
+                    let byte = label.label.range.start.min(snippet.source.len()) - line.start;
+                    let column = line.text[..char_boundary(line.text, byte)].chars().count();
+
+                    format!(":{}:{}", label.start.line + 1, column + 1)
+                })
+                .unwrap_or_default();
+
+            out.push(vec![
+                Span::new(format!("{} ┌─ ", blank), self.gutter_style),
+                Span::new(format!("{}{}", origin, location), Style::default()),
+            ]);
+        }
+
+        if !self.labels.is_empty() {
+            self.push(None, vec![], &mut out);
+
+            let mut previous: Option<usize> = None;
+
+            for line in self.shown_lines() {
+                if let Some(previous) = previous {
+                    if line > previous + 1 {
+                        let mut gap = self.gutter(None, '·');
+                        trim_end(&mut gap);
+                        out.push(gap);
+                    }
+                }
+
+                self.annotated_line(line, &mut out);
+                previous = Some(line);
+            }
+        }
+
+        if !snippet.notes.is_empty() {
+            if !self.labels.is_empty() {
+                self.push(None, vec![], &mut out);
+            }
+
+            for note in &snippet.notes {
+                out.push(vec![
+                    Span::new(format!("{} = ", blank), self.gutter_style),
+                    Span::new("note", Style::default().bold()),
+                    Span::new(format!(": {}", note), Style::default()),
+                ]);
+            }
+        }
+
+        out
+    }
+}
+
+fn message(label: &Located<'_>) -> Option<(String, Style)> {
+    if label.label.message.is_empty() {
+        None
+    } else {
+        Some((format!(" {}", label.label.message), label.style()))
+    }
+}
+
+impl Doc for Snippet {
+    fn render<'a>(&self, ctx: &'a StyledArena<'a>, _state: RenderState) -> StyledDoc<'a> {
+        let lines = Layout::new(self).render(self);
+
+        lines_doc(ctx, &lines)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{prelude::test::*, EmitBackendTrait, EmitForTest, EmitPlain};
+
+    use super::*;
+
+    const SOURCE: &str = "fn main() {\n    let x = 1 + \"a\";\n    let y = x;\n}\n";
+
+    fn range(needle: &str) -> Range<usize> {
+        let start = SOURCE.find(needle).unwrap();
+        start..start + needle.len()
+    }
+
+    #[test]
+    fn snippet_single_line() -> TestResult {
+        let snippet = Snippet::new(SOURCE)
+            .origin("src/main.rs")
+            .label(SnippetLabel::primary(range("\"a\""), "expected an integer"))
+            .label(SnippetLabel::secondary(range("1"), "this is an integer"))
+            .note("integers can't be added to strings");
+
+        assert_eq!(
+            EmitPlain.render(&snippet, 80)?,
+            strip(
+                r#"
+                  ┌─ src/main.rs:2:17
+                  │
+                2 │     let x = 1 + "a";
+                  │             -   ^^^ expected an integer
+                  │             │
+                  │             this is an integer
+                  │
+                  = note: integers can't be added to strings
+                "#
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn snippet_styles() -> TestResult {
+        let snippet = Snippet::new(SOURCE)
+            .label(SnippetLabel::primary(range("x;"), "used here"))
+            .label(SnippetLabel::secondary(range("y"), ""));
+
+        assert_eq!(
+            EmitForTest.render(&snippet, 80)?,
+            strip(
+                r#"
+                [Blue:  │]
+                [Blue:3 │][normal: ][normal:    let y = x;]
+                [Blue:  │][normal: ][normal:        ][Blue:-][normal:   ][Red, bold:^^][Red, bold: used here]
+                "#
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn snippet_overlapping_labels() -> TestResult {
+        let snippet = Snippet::new(SOURCE)
+            .label(SnippetLabel::primary(range("1 + \"a\""), "can't add these"))
+            .label(SnippetLabel::secondary(range("+"), "addition"))
+            .label(SnippetLabel::secondary(range("let x"), "binding"));
+
+        assert_eq!(
+            EmitPlain.render(&snippet, 80)?,
+            strip(
+                r#"
+                  │
+                2 │     let x = 1 + "a";
+                  │     -----   ^^^^^^^ addition
+                  │     │       │
+                  │     │       can't add these
+                  │     binding
+                "#
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn snippet_multiline_labels() -> TestResult {
+        let snippet = Snippet::new(SOURCE)
+            .label(SnippetLabel::primary(range("{\n    let x"), "starts here"))
+            .label(SnippetLabel::secondary(
+                range("1 + \"a\";\n    let y = x;\n}"),
+                "the body",
+            ))
+            .label(SnippetLabel::primary(range("x;"), "used here"));
+
+        assert_eq!(
+            EmitPlain.render(&snippet, 80)?,
+            strip(
+                r#"
+                  │
+                1 │     fn main() {
+                  │ ╭─────────────^
+                2 │ │       let x = 1 + "a";
+                  │ │ ╭─────────────-
+                  │ ╰───────────^ starts here
+                3 │   │     let y = x;
+                  │   │             ^^ used here
+                4 │   │ }
+                  │   ╰─- the body
+                "#
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn snippet_gaps_and_wide_chars() -> TestResult {
+        let source = "let s = \"日本\" + 1;\n\n\n\n\n\n\n\n\nlet t = s;\n";
+        let one = source.find('1').unwrap();
+        let t = source.rfind("t =").unwrap();
+
+        let snippet = Snippet::new(source)
+            .origin("wide.rs")
+            .label(SnippetLabel::primary(one..one + 1, "expected a string"))
+            .label(SnippetLabel::secondary(t..t + 1, "assigned here"));
+
+        assert_eq!(
+            EmitPlain.render(&snippet, 80)?,
+            strip(
+                r#"
+                   ┌─ wide.rs:1:16
+                   │
+                 1 │ let s = "日本" + 1;
+                   │                  ^ expected a string
+                   ·
+                10 │ let t = s;
+                   │     - assigned here
+                "#
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn snippet_label_inside_a_char() -> TestResult {
+        // The label starts in the middle of `é`, which takes two bytes
+        let snippet = Snippet::new("let é = 1;")
+            .origin("x.rs")
+            .label(SnippetLabel::primary(5..6, "a name"));
+
+        assert_eq!(
+            EmitPlain.render(&snippet, 80)?,
+            strip(
+                r#"
+                  ┌─ x.rs:1:5
+                  │
+                1 │ let é = 1;
+                  │     ^ a name
+                "#
+            )
+        );
+
+        Ok(())
+    }
+
+    fn strip(input: &str) -> String {
+        let lines: Vec<&str> = input.split('\n').collect();
+        let string = lines[1..lines.len() - 1].to_vec().join("\n");
+        textwrap::dedent(&string)
+    }
+}
@@ -0,0 +1,610 @@
+use pretty::DocAllocator;
+
+use crate::{render::RenderState, BoxedDoc, Doc, Style};
+
+use super::{
+    lines::{layout_lines, line_width, lines_doc, trim_end, truncate_line, wrap_line, Line, Span},
+    StyledArena, StyledDoc,
+};
+
+/// How a cell is positioned inside its column when it's narrower than the column.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Align {
+    Left,
+    Right,
+    Center,
+}
+
+/// What to do with a cell that's wider than its column's `max_width`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Overflow {
+    /// Lay the cell out again at the column's width, and then wrap any lines that are still too
+    /// wide at whitespace.
+    Wrap,
+    /// Cut each line of the cell off at the column's width, ending it with `…`.
+    Truncate,
+}
+
+/// The definition of a column in a `Table`.
+///
+/// A column is as wide as its widest cell, but no narrower than `min_width` and no wider than
+/// `max_width`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Column {
+    align: Align,
+    min_width: usize,
+    max_width: Option<usize>,
+    overflow: Overflow,
+}
+
+impl Default for Column {
+    fn default() -> Self {
+        Column {
+            align: Align::Left,
+            min_width: 0,
+            max_width: None,
+            overflow: Overflow::Wrap,
+        }
+    }
+}
+
+impl Column {
+    pub fn left() -> Column {
+        Column::default()
+    }
+
+    pub fn right() -> Column {
+        Column::default().align(Align::Right)
+    }
+
+    pub fn center() -> Column {
+        Column::default().align(Align::Center)
+    }
+
+    pub fn align(mut self, align: Align) -> Column {
+        self.align = align;
+        self
+    }
+
+    pub fn min_width(mut self, width: usize) -> Column {
+        self.min_width = width;
+        self
+    }
+
+    pub fn max_width(mut self, width: usize) -> Column {
+        self.max_width = Some(width);
+        self
+    }
+
+    pub fn wrap(mut self) -> Column {
+        self.overflow = Overflow::Wrap;
+        self
+    }
+
+    pub fn truncate(mut self) -> Column {
+        self.overflow = Overflow::Truncate;
+        self
+    }
+
+    fn width(&self, natural: usize) -> usize {
+        let width = match self.max_width {
+            Some(max) => natural.min(max),
+            None => natural,
+        };
+
+        width.max(self.min_width)
+    }
+}
+
+/// The horizontal rules drawn above the table, below the header and below the table.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Border {
+    rule: char,
+    style: Style,
+}
+
+impl Border {
+    pub fn unicode() -> Border {
+        Border {
+            rule: '─',
+            style: Style::default(),
+        }
+    }
+
+    pub fn ascii() -> Border {
+        Border {
+            rule: '-',
+            style: Style::default(),
+        }
+    }
+
+    pub fn style(mut self, style: impl Into<Style>) -> Border {
+        self.style = style.into();
+        self
+    }
+}
+
+/// A doc that lays out rows of cells in aligned columns.
+///
+/// Cells are arbitrary docs. Each cell is laid out on its own to find its width, and cells that
+/// don't fit in their column are wrapped or truncated (see `Overflow`). Cells that take up
+/// several lines make the whole row taller.
+///
+/// ```
+/// use spectrum::{Column, Doc, EmitBackendTrait, EmitPlain, Table};
+///
+/// let table = Table::new(vec![Column::left(), Column::right()])
+///     .row(vec!["passed".boxed(), "12".boxed()])
+///     .row(vec!["failed".boxed(), "3".boxed()]);
+///
+/// assert_eq!(EmitPlain.render(&table, 80).unwrap(), "passed  12\nfailed   3");
+/// ```
+#[derive(Debug)]
+pub struct Table {
+    columns: Vec<Column>,
+    header: Option<Vec<BoxedDoc>>,
+    header_style: Style,
+    rows: Vec<Vec<BoxedDoc>>,
+    border: Option<Border>,
+    gap: usize,
+}
+
+impl Default for Table {
+    fn default() -> Self {
+        Table::new(vec![])
+    }
+}
+
+impl Table {
+    /// Create a table with the given column definitions. Cells in columns past the end of
+    /// `columns` use `Column::default()`.
+    pub fn new(columns: Vec<Column>) -> Table {
+        Table {
+            columns,
+            header: None,
+            header_style: Style::default(),
+            rows: vec![],
+            border: None,
+            gap: 2,
+        }
+    }
+
+    pub fn header(mut self, cells: Vec<BoxedDoc>) -> Table {
+        self.header = Some(cells);
+        self
+    }
+
+    /// The style of header fragments that don't have a style of their own.
+    pub fn header_style(mut self, style: impl Into<Style>) -> Table {
+        self.header_style = style.into();
+        self
+    }
+
+    pub fn row(mut self, cells: Vec<BoxedDoc>) -> Table {
+        self.rows.push(cells);
+        self
+    }
+
+    pub fn border(mut self, border: Border) -> Table {
+        self.border = Some(border);
+        self
+    }
+
+    /// The number of spaces between columns (2 by default).
+    pub fn gap(mut self, gap: usize) -> Table {
+        self.gap = gap;
+        self
+    }
+
+    fn column(&self, index: usize) -> Column {
+        self.columns.get(index).copied().unwrap_or_default()
+    }
+}
+
+/// A cell that has been laid out at its natural width.
+struct Cell<'t> {
+    doc: Option<&'t BoxedDoc>,
+    lines: Vec<Line>,
+}
+
+impl Cell<'_> {
+    fn width(&self) -> usize {
+        self.lines
+            .iter()
+            .map(|line| line_width(line))
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+impl Table {
+    fn layout_row<'t, 'a>(
+        &self,
+        ctx: &'a StyledArena<'a>,
+        state: RenderState,
+        row: &'t [BoxedDoc],
+        column_count: usize,
+    ) -> Vec<Cell<'t>> {
+        (0..column_count)
+            .map(|index| match row.get(index) {
+                Some(doc) => Cell {
+                    doc: Some(doc),
+                    lines: layout_lines(ctx, doc.render(ctx, state), usize::MAX),
+                },
+                None => Cell {
+                    doc: None,
+                    lines: vec![],
+                },
+            })
+            .collect()
+    }
+
+    /// Fit each cell of a row into its column, and join the cells into lines.
+    fn row_lines<'a>(
+        &self,
+        ctx: &'a StyledArena<'a>,
+        state: RenderState,
+        row: Vec<Cell<'_>>,
+        widths: &[usize],
+    ) -> Vec<Line> {
+        let last = widths.len() - 1;
+
+        let cells: Vec<Vec<Line>> = row
+            .into_iter()
+            .enumerate()
+            .map(|(index, cell)| {
+                let width = widths[index];
+
+                if cell.width() <= width {
+                    return cell.lines;
+                }
+
+                match (self.column(index).overflow, cell.doc) {
+                    (Overflow::Wrap, Some(doc)) => layout_lines(ctx, doc.render(ctx, state), width)
+                        .into_iter()
+                        .flat_map(|line| wrap_line(line, width))
+                        .collect(),
+                    _ => cell
+                        .lines
+                        .into_iter()
+                        .map(|line| truncate_line(line, width))
+                        .collect(),
+                }
+            })
+            .collect();
+
+        let height = cells
+            .iter()
+            .map(|cell| cell.len())
+            .max()
+            .unwrap_or(0)
+            .max(1);
+
+        (0..height)
+            .map(|line_index| {
+                let mut line: Line = vec![];
+
+                for (index, cell) in cells.iter().enumerate() {
+                    let content = cell.get(line_index).cloned().unwrap_or_default();
+                    let space = widths[index].saturating_sub(line_width(&content));
+
+                    let (before, after) = match self.column(index).align {
+                        Align::Left => (0, space),
+                        Align::Right => (space, 0),
+                        Align::Center => (space / 2, space - space / 2),
+                    };
+
+                    if index > 0 {
+                        push_space(&mut line, self.gap);
+                    }
+
+                    push_space(&mut line, before);
+                    line.extend(content);
+
+                    // Don't leave trailing whitespace after the last column
+                    if index != last {
+                        push_space(&mut line, after);
+                    }
+                }
+
+                trim_end(&mut line);
+                line
+            })
+            .collect()
+    }
+}
+
+fn push_space(line: &mut Line, width: usize) {
+    if width == 0 {
+        return;
+    }
+
+    match line.last_mut() {
+        Some(span) if span.style.is_plain() && span.text.trim().is_empty() => {
+            span.text.push_str(&" ".repeat(width))
+        }
+        _ => line.push(Span::new(" ".repeat(width), Style::default())),
+    }
+}
+
+impl Doc for Table {
+    fn render<'a>(&self, ctx: &'a StyledArena<'a>, state: RenderState) -> StyledDoc<'a> {
+        if self.header.is_none() && self.rows.is_empty() {
+            return ctx.nil();
+        }
+
+        let column_count = self
+            .header
+            .iter()
+            .chain(self.rows.iter())
+            .map(|row| row.len())
+            .max()
+            .unwrap_or(0)
+            .max(self.columns.len())
+            .max(1);
+
+        let header = self
+            .header
+            .as_ref()
+            .map(|row| self.layout_row(ctx, state, row, column_count));
+
+        let rows: Vec<Vec<Cell<'_>>> = self
+            .rows
+            .iter()
+            .map(|row| self.layout_row(ctx, state, row, column_count))
+            .collect();
+
+        let widths: Vec<usize> = (0..column_count)
+            .map(|index| {
+                let natural = header
+                    .iter()
+                    .chain(rows.iter())
+                    .map(|row| row[index].width())
+                    .max()
+                    .unwrap_or(0);
+
+                self.column(index).width(natural)
+            })
+            .collect();
+
+        let rule = |border: Border| -> Line {
+            let width = widths.iter().sum::<usize>() + self.gap * (column_count - 1);
+            vec![Span::new(
+                border.rule.to_string().repeat(width),
+                border.style,
+            )]
+        };
+
+        let mut lines: Vec<Line> = vec![];
+
+        if let Some(border) = self.border {
+            lines.push(rule(border));
+        }
+
+        if let Some(header) = header {
+            for line in self.row_lines(ctx, state, header, &widths) {
+                lines.push(
+                    line.into_iter()
+                        .map(|span| {
+                            if span.style.is_plain() && !span.text.trim().is_empty() {
+                                Span::new(span.text, self.header_style)
+                            } else {
+                                span
+                            }
+                        })
+                        .collect(),
+                );
+            }
+
+            if let Some(border) = self.border {
+                lines.push(rule(border));
+            }
+        }
+
+        for row in rows {
+            lines.extend(self.row_lines(ctx, state, row, &widths));
+        }
+
+        if let Some(border) = self.border {
+            lines.push(rule(border));
+        }
+
+        lines_doc(ctx, &lines)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        empty, plain, prelude::test::*, styled, Color, EmitBackendTrait, EmitForTest, EmitPlain,
+        Nested, BOUNDARY, GAP,
+    };
+
+    use super::*;
+
+    fn cells(cells: Vec<&'static str>) -> Vec<BoxedDoc> {
+        cells.into_iter().map(|cell| plain(cell).boxed()).collect()
+    }
+
+    fn summary() -> Table {
+        Table::new(vec![Column::left(), Column::center(), Column::right()])
+            .header(cells(vec!["name", "status", "duration"]))
+            .row(cells(vec!["parses input", "ok", "2ms"]))
+            .row(cells(vec!["emits", "failed", "120ms"]))
+    }
+
+    #[test]
+    fn table_alignment() -> TestResult {
+        assert_eq!(
+            EmitPlain.render(&summary(), 80)?,
+            strip(
+                r#"
+                name          status  duration
+                parses input    ok         2ms
+                emits         failed     120ms
+                "#
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn table_border() -> TestResult {
+        let table = summary().border(Border::unicode()).gap(1);
+
+        assert_eq!(
+            EmitPlain.render(&table, 80)?,
+            strip(
+                r#"
+                ────────────────────────────
+                name         status duration
+                ────────────────────────────
+                parses input   ok        2ms
+                emits        failed    120ms
+                ────────────────────────────
+                "#
+            )
+        );
+
+        let table = Table::default()
+            .border(Border::ascii().style(Color::Blue))
+            .row(cells(vec!["a", "b"]));
+
+        assert_eq!(
+            EmitForTest.render(&table, 80)?,
+            "[Blue:----]\n[normal:a][normal:  ][normal:b]\n[Blue:----]"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn table_header_style() -> TestResult {
+        let table = Table::default()
+            .header(vec![
+                plain("name").boxed(),
+                styled("status", Color::Red.into()).boxed(),
+            ])
+            .header_style(Style::default().bold())
+            .row(cells(vec!["a", "ok"]));
+
+        assert_eq!(
+            EmitForTest.render(&table, 80)?,
+            "[normal, bold:name][normal:  ][Red:status]\n[normal:a][normal:     ][normal:ok]"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn table_wrap() -> TestResult {
+        let table = Table::new(vec![Column::left(), Column::left().max_width(12)])
+            .row(cells(vec!["first", "a message that is too long"]))
+            .row(vec![
+                plain("second").boxed(),
+                group!["hello", GAP(), "world"].boxed(),
+            ]);
+
+        assert_eq!(
+            EmitPlain.render(&table, 80)?,
+            strip(
+                r#"
+                first   a message
+                        that is too
+                        long
+                second  hello world
+                "#
+            )
+        );
+
+        let table = Table::new(vec![Column::left(), Column::left().max_width(8)]).row(vec![
+            plain("second").boxed(),
+            group!["hello", GAP(), "world"].boxed(),
+        ]);
+
+        assert_eq!(
+            EmitPlain.render(&table, 80)?,
+            "second  hello\n        world"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn table_truncate() -> TestResult {
+        let table = Table::new(vec![Column::left().max_width(8).truncate(), Column::left()])
+            .row(cells(vec!["a very long name", "ok"]))
+            .row(cells(vec!["short", "failed"]));
+
+        assert_eq!(
+            EmitPlain.render(&table, 80)?,
+            "a very …  ok\nshort     failed"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn table_min_width_and_missing_cells() -> TestResult {
+        let table = Table::new(vec![Column::right().min_width(4)])
+            .row(cells(vec!["1", "one", "uno"]))
+            .row(cells(vec!["2", "two"]));
+
+        assert_eq!(EmitPlain.render(&table, 80)?, "   1  one  uno\n   2  two");
+
+        Ok(())
+    }
+
+    #[test]
+    fn table_wide_chars() -> TestResult {
+        let table = Table::new(vec![Column::left(), Column::right()])
+            .row(cells(vec!["日本語", "1"]))
+            .row(cells(vec!["abc", "22"]));
+
+        assert_eq!(EmitPlain.render(&table, 80)?, "日本語   1\nabc     22");
+
+        Ok(())
+    }
+
+    #[test]
+    fn table_multiline_cells() -> TestResult {
+        let table = Table::default()
+            .row(vec![
+                plain("a").boxed(),
+                list!["one", BOUNDARY(), "two"].boxed(),
+            ])
+            .row(cells(vec!["b", "three"]));
+
+        assert_eq!(EmitPlain.render(&table, 80)?, "a  one\n   two\nb  three");
+
+        Ok(())
+    }
+
+    #[test]
+    fn nested_table() -> TestResult {
+        let doc = list![
+            "results:",
+            Nested::once(
+                Table::default()
+                    .row(cells(vec!["a", "ok"]))
+                    .row(cells(vec!["bb", "failed"])),
+                BOUNDARY(),
+                empty()
+            )
+        ];
+
+        assert_eq!(
+            EmitPlain.render(&doc, 80)?,
+            "results:\n  a   ok\n  bb  failed"
+        );
+
+        Ok(())
+    }
+
+    fn strip(input: &str) -> String {
+        let lines: Vec<&str> = input.split('\n').collect();
+        let string = lines[1..lines.len() - 1].to_vec().join("\n");
+        textwrap::dedent(&string)
+    }
+}
@@ -0,0 +1,385 @@
+use crate::{
+    render::{Nesting, RenderState},
+    BoxedDoc, Doc, NonemptyList, Style,
+};
+
+use super::{
+    lines::{layout_lines, lines_doc, trim_end, Line, Span},
+    StyledArena, StyledDoc,
+};
+
+/// The characters used to draw the guides of a `Tree`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct TreeGuides {
+    branch: char,
+    last_branch: char,
+    horizontal: char,
+    vertical: char,
+    style: Style,
+}
+
+impl Default for TreeGuides {
+    fn default() -> Self {
+        TreeGuides::unicode()
+    }
+}
+
+impl TreeGuides {
+    /// `├──`, `└──` and `│`, like `cargo tree`.
+    pub fn unicode() -> TreeGuides {
+        TreeGuides {
+            branch: '├',
+            last_branch: '└',
+            horizontal: '─',
+            vertical: '│',
+            style: Style::default(),
+        }
+    }
+
+    /// `|--`, `` `-- `` and `|`, like `cargo tree --charset ascii`.
+    pub fn ascii() -> TreeGuides {
+        TreeGuides {
+            branch: '|',
+            last_branch: '`',
+            horizontal: '-',
+            vertical: '|',
+            style: Style::default(),
+        }
+    }
+
+    pub fn style(mut self, style: impl Into<Style>) -> TreeGuides {
+        self.style = style.into();
+        self
+    }
+
+    /// The guide in front of a line of a child. `first` is true for the first line of the
+    /// child, and `last` is true if the child is the last of its siblings.
+    fn guide(&self, width: usize, first: bool, last: bool) -> Span {
+        let text = match (first, last) {
+            (true, _) => {
+                let branch = if last { self.last_branch } else { self.branch };
+
+                format!(
+                    "{}{} ",
+                    branch,
+                    self.horizontal.to_string().repeat(width - 2)
+                )
+            }
+            (false, false) => format!("{}{}", self.vertical, " ".repeat(width - 1)),
+            (false, true) => return Span::new(" ".repeat(width), Style::default()),
+        };
+
+        Span::new(text, self.style)
+    }
+}
+
+/// A doc with a label and a list of children, which are drawn below the label with guides that
+/// connect them to it:
+///
+/// ```text
+/// spectrum
+/// ├── pretty
+/// │   └── typed-arena
+/// └── unicode-width
+/// ```
+///
+/// Children are arbitrary docs, and children that take up several lines are continued with a
+/// vertical guide. Nested trees are added with `subtree`, and use the guides and indentation of
+/// the tree they're in unless they set their own.
+///
+/// Each level of the tree is indented by `indent` (`Nesting::Configured(2)` by default, so the
+/// width of the guides follows `RenderConfig::indent_size`), and children are laid out in the
+/// columns that remain.
+///
+/// ```
+/// use spectrum::{EmitBackendTrait, EmitPlain, Tree, TreeGuides};
+///
+/// let tree = Tree::new("laboratory")
+///     .subtree(Tree::new("spectrum").child("pretty"))
+///     .child("veritas")
+///     .guides(TreeGuides::ascii());
+///
+/// assert_eq!(
+///     EmitPlain.render(&tree, 80).unwrap(),
+///     "laboratory\n|-- spectrum\n|   `-- pretty\n`-- veritas"
+/// );
+/// ```
+#[derive(Debug)]
+pub struct Tree {
+    label: BoxedDoc,
+    children: Option<NonemptyList<Node>>,
+    guides: Option<TreeGuides>,
+    indent: Option<Nesting>,
+}
+
+#[derive(Debug)]
+enum Node {
+    Leaf(BoxedDoc),
+    Tree(Tree),
+}
+
+impl Tree {
+    pub fn new(label: impl Doc) -> Tree {
+        Tree {
+            label: label.boxed(),
+            children: None,
+            guides: None,
+            indent: None,
+        }
+    }
+
+    pub fn child(self, child: impl Doc) -> Tree {
+        self.push(Node::Leaf(child.boxed()))
+    }
+
+    pub fn children(self, children: Vec<BoxedDoc>) -> Tree {
+        children
+            .into_iter()
+            .fold(self, |tree, child| tree.child(child))
+    }
+
+    pub fn subtree(self, tree: Tree) -> Tree {
+        self.push(Node::Tree(tree))
+    }
+
+    pub fn guides(mut self, guides: TreeGuides) -> Tree {
+        self.guides = Some(guides);
+        self
+    }
+
+    /// How far each level of the tree is indented. Guides need at least two columns, so
+    /// anything narrower is widened to two columns.
+    pub fn indent(mut self, indent: Nesting) -> Tree {
+        self.indent = Some(indent);
+        self
+    }
+
+    fn push(mut self, node: Node) -> Tree {
+        match &mut self.children {
+            Some(children) => children.push(node),
+            None => self.children = Some(NonemptyList::new(vec![node])),
+        }
+
+        self
+    }
+
+    fn render_lines<'a>(
+        &self,
+        ctx: &'a StyledArena<'a>,
+        state: RenderState,
+        guides: TreeGuides,
+        indent: Nesting,
+    ) -> Vec<Line> {
+        let guides = self.guides.unwrap_or(guides);
+        let indent = self.indent.unwrap_or(indent);
+
+        let mut lines = layout_lines(ctx, self.label.render(ctx, state), state.column_size());
+
+        if let Some(children) = &self.children {
+            let width = state.size(indent).max(2) as usize;
+            let child_state = state.narrow(width);
+
+            for child in children.iter() {
+                let last = child.is_last();
+
+                let child_lines = match child.value() {
+                    Node::Leaf(doc) => {
+                        layout_lines(ctx, doc.render(ctx, child_state), child_state.column_size())
+                    }
+                    Node::Tree(tree) => tree.render_lines(ctx, child_state, guides, indent),
+                };
+
+                for (index, child_line) in child_lines.into_iter().enumerate() {
+                    let mut line: Line = vec![guides.guide(width, index == 0, last)];
+                    line.extend(child_line);
+                    trim_end(&mut line);
+                    lines.push(line);
+                }
+            }
+        }
+
+        lines
+    }
+}
+
+impl Doc for Tree {
+    fn render<'a>(&self, ctx: &'a StyledArena<'a>, state: RenderState) -> StyledDoc<'a> {
+        let lines = self.render_lines(ctx, state, TreeGuides::default(), Nesting::Configured(2));
+
+        lines_doc(ctx, &lines)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        emit::buf::Buf, plain, prelude::test::*, render::RenderConfig, string::intern::Intern,
+        Color, EmitBackendTrait, EmitForTest, EmitPlain, Group, RenderContext, BOUNDARY, GAP,
+    };
+
+    use super::*;
+
+    fn deps() -> Tree {
+        Tree::new("spectrum")
+            .subtree(Tree::new("pretty").child("arrayvec").child("typed-arena"))
+            .subtree(Tree::new("unicode-width"))
+            .subtree(Tree::new("elsa").child("stable_deref_trait"))
+    }
+
+    #[test]
+    fn tree_unicode() -> TestResult {
+        assert_eq!(
+            EmitPlain.render(&deps(), 80)?,
+            strip(
+                r#"
+                spectrum
+                ├── pretty
+                │   ├── arrayvec
+                │   └── typed-arena
+                ├── unicode-width
+                └── elsa
+                    └── stable_deref_trait
+                "#
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn tree_ascii() -> TestResult {
+        assert_eq!(
+            EmitPlain.render(&deps().guides(TreeGuides::ascii()), 80)?,
+            strip(
+                r#"
+                spectrum
+                |-- pretty
+                |   |-- arrayvec
+                |   `-- typed-arena
+                |-- unicode-width
+                `-- elsa
+                    `-- stable_deref_trait
+                "#
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn tree_indent_size() -> TestResult {
+        let tree = Tree::new("a").subtree(Tree::new("b").child("c")).child("d");
+
+        assert_eq!(
+            render(&tree, RenderConfig::width(80).indent_size(3))?,
+            strip(
+                r#"
+                a
+                ├──── b
+                │     └──── c
+                └──── d
+                "#
+            )
+        );
+
+        assert_eq!(
+            render(&tree, RenderConfig::width(80).indent_size(1))?,
+            "a\n├ b\n│ └ c\n└ d"
+        );
+
+        let tree = tree.indent(Nesting::Exact(3));
+
+        assert_eq!(
+            render(&tree, RenderConfig::width(80).indent_size(1))?,
+            "a\n├─ b\n│  └─ c\n└─ d"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn tree_multiline_children() -> TestResult {
+        let words = || {
+            Group::new(vec![
+                plain("hello").boxed(),
+                GAP().boxed(),
+                plain("goodbye").boxed(),
+            ])
+        };
+
+        let tree = Tree::new("root")
+            .child(words())
+            .subtree(Tree::new(words()).child(list!["one", BOUNDARY(), "two"]));
+
+        assert_eq!(
+            EmitPlain.render(&tree, 80)?,
+            strip(
+                r#"
+                root
+                ├── hello goodbye
+                └── hello goodbye
+                    └── one
+                        two
+                "#
+            )
+        );
+
+        // Children are laid out in the columns that are left over after the guides
+        assert_eq!(
+            EmitPlain.render(&tree, 16)?,
+            strip(
+                r#"
+                root
+                ├── hello
+                │   goodbye
+                └── hello
+                    goodbye
+                    └── one
+                        two
+                "#
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn tree_guide_style() -> TestResult {
+        let tree = Tree::new("a")
+            .subtree(Tree::new("b").child("c"))
+            .child("d")
+            .guides(TreeGuides::ascii().style(Color::Blue));
+
+        assert_eq!(
+            EmitForTest.render(&tree, 80)?,
+            "[normal:a]\n[Blue:|-- ][normal:b]\n[Blue:|   ][Blue:`-- ][normal:c]\n[Blue:`-- ][normal:d]"
+        );
+
+        // Subtrees can override the guides they would otherwise inherit
+        let tree = Tree::new("a")
+            .subtree(Tree::new("b").child("c").guides(TreeGuides::unicode()))
+            .child("d")
+            .guides(TreeGuides::ascii());
+
+        assert_eq!(EmitPlain.render(&tree, 80)?, "a\n|-- b\n|   └── c\n`-- d");
+
+        Ok(())
+    }
+
+    fn render(doc: &impl Doc, config: RenderConfig) -> Result<String, std::fmt::Error> {
+        Buf::collect_string(|writer| {
+            let intern = Intern::new();
+            let arena = StyledArena::new(&intern);
+            let mut context = RenderContext::new(arena);
+            context.render(doc, EmitPlain, writer, config)?;
+
+            Ok(())
+        })
+    }
+
+    fn strip(input: &str) -> String {
+        let lines: Vec<&str> = input.split('\n').collect();
+        let string = lines[1..lines.len() - 1].to_vec().join("\n");
+        textwrap::dedent(&string)
+    }
+}
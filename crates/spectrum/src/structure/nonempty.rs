@@ -12,6 +12,10 @@ impl<T> NonemptyList<T> {
         NonemptyList { vec: list }
     }
 
+    pub fn push(&mut self, item: T) {
+        self.vec.push(item);
+    }
+
     pub fn drain(self) -> DrainList<T> {
         self.vec.into()
     }
@@ -1,3 +1,4 @@
+pub use crate::emit::auto::EmitAuto;
 pub use crate::emit::backend::{EmitColored, EmitPlain};
 
 pub mod test {
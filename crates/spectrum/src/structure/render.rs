@@ -70,6 +70,21 @@ impl RenderState {
         self.config.size(nesting)
     }
 
+    pub fn column_size(&self) -> usize {
+        self.config.column_size
+    }
+
+    /// The state for a doc that will be laid out in `columns` fewer columns than this one.
+    pub fn narrow(&self, columns: usize) -> RenderState {
+        RenderState {
+            config: RenderConfig {
+                column_size: self.config.column_size.saturating_sub(columns),
+                ..self.config
+            },
+            nesting: self.nesting,
+        }
+    }
+
     pub fn indent(&self, indent: Nesting) -> RenderState {
         RenderState {
             config: self.config,
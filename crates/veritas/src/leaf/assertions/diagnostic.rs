@@ -1,12 +1,47 @@
 #![allow(unused)]
 
-use std::ops::Range;
+use std::{fmt::Debug, ops::Range};
 
+use spectrum::{
+    compose::{StyledArena, StyledDoc},
+    render::RenderState,
+    styled, Diff, Doc, DocList, LabelKind, Snippet, SnippetLabel, Style, BOUNDARY,
+};
+
+#[derive(Debug, Clone)]
 pub struct Label {
     range: Range<usize>,
     message: String,
+    kind: LabelKind,
+}
+
+impl Label {
+    /// A label that points at the offending part of a value.
+    pub fn new(range: Range<usize>, message: impl Into<String>) -> Label {
+        Label {
+            range,
+            message: message.into(),
+            kind: LabelKind::Primary,
+        }
+    }
+
+    /// A label that points at context that helps explain a primary label.
+    pub fn secondary(range: Range<usize>, message: impl Into<String>) -> Label {
+        Label {
+            range,
+            message: message.into(),
+            kind: LabelKind::Secondary,
+        }
+    }
 }
 
+impl From<Label> for SnippetLabel {
+    fn from(label: Label) -> SnippetLabel {
+        SnippetLabel::new(label.kind, label.range, label.message)
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Described<T> {
     description: String,
     value: T,
@@ -14,11 +49,234 @@ pub struct Described<T> {
     notes: Vec<String>,
 }
 
+impl<T> Described<T> {
+    pub fn new(description: impl Into<String>, value: T) -> Described<T> {
+        Described {
+            description: description.into(),
+            value,
+            labels: vec![],
+            notes: vec![],
+        }
+    }
+
+    pub fn label(mut self, label: Label) -> Described<T> {
+        self.labels.push(label);
+        self
+    }
+
+    pub fn note(mut self, note: impl Into<String>) -> Described<T> {
+        self.notes.push(note.into());
+        self
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    pub fn notes(&self) -> &[String] {
+        &self.notes
+    }
+
+    pub fn into_value(self) -> T {
+        self.value
+    }
+
+    /// Describe a value by its pretty-printed (`{:#?}`) representation, keeping the description.
+    pub fn debug(&self) -> Described<String>
+    where
+        T: Debug,
+    {
+        Described::new(self.description.clone(), format!("{:#?}", self.value))
+    }
+}
+
+impl<T> Described<T>
+where
+    T: AsRef<str>,
+{
+    /// The value's text, with the labels pointing into it.
+    pub fn snippet(&self) -> Snippet {
+        self.notes.iter().fold(
+            Snippet::new(self.value.as_ref())
+                .labels(self.labels.iter().cloned().map(SnippetLabel::from)),
+            |snippet, note| snippet.note(note.clone()),
+        )
+    }
+}
+
+impl<T> Doc for Described<T>
+where
+    T: AsRef<str> + Debug + 'static,
+{
+    fn render<'a>(&self, ctx: &'a StyledArena<'a>, state: RenderState) -> StyledDoc<'a> {
+        let description = styled(self.description.clone(), Style::default().bold());
+
+        if !self.labels.is_empty() {
+            return list![description, BOUNDARY(), self.snippet()].render(ctx, state);
+        }
+
+        // There's nothing to point at, so the value is shown as it is
+        let mut docs = vec![description.boxed()];
+
+        for line in self.value.as_ref().split('\n') {
+            docs.push(BOUNDARY().boxed());
+            docs.push(styled(line.to_string(), Style::default()).boxed());
+        }
+
+        for note in &self.notes {
+            docs.push(BOUNDARY().boxed());
+            docs.push(styled(format!("= note: {}", note), Style::default()).boxed());
+        }
+
+        DocList::new(docs).render(ctx, state)
+    }
+}
+
+#[derive(Debug, Clone)]
 pub enum Diagnostic {
+    /// A failure that is explained by showing the actual value
+    Value(Described<String>),
+    /// A failure that is explained by comparing the actual value with the expected value
     Diffable(DiffableDiagnostic),
 }
 
+impl Diagnostic {
+    pub fn actual(&self) -> &Described<String> {
+        match self {
+            Diagnostic::Value(actual) => actual,
+            Diagnostic::Diffable(diagnostic) => &diagnostic.actual,
+        }
+    }
+
+    pub fn expected(&self) -> Option<&Described<String>> {
+        match self {
+            Diagnostic::Value(_) => None,
+            Diagnostic::Diffable(diagnostic) => Some(&diagnostic.expected),
+        }
+    }
+}
+
+impl From<Described<String>> for Diagnostic {
+    fn from(actual: Described<String>) -> Diagnostic {
+        Diagnostic::Value(actual)
+    }
+}
+
+impl From<DiffableDiagnostic> for Diagnostic {
+    fn from(diagnostic: DiffableDiagnostic) -> Diagnostic {
+        Diagnostic::Diffable(diagnostic)
+    }
+}
+
+impl Doc for Diagnostic {
+    fn render<'a>(&self, ctx: &'a StyledArena<'a>, state: RenderState) -> StyledDoc<'a> {
+        match self {
+            Diagnostic::Value(actual) => actual.render(ctx, state),
+            Diagnostic::Diffable(diagnostic) => diagnostic.render(ctx, state),
+        }
+    }
+}
+
+/// A failure that compares an expected value with the actual value, and is shown as a diff
+/// between the two.
+#[derive(Debug, Clone)]
 pub struct DiffableDiagnostic {
     actual: Described<String>,
     expected: Described<String>,
 }
+
+impl DiffableDiagnostic {
+    pub fn new(expected: Described<String>, actual: Described<String>) -> DiffableDiagnostic {
+        DiffableDiagnostic { actual, expected }
+    }
+
+    /// A diagnostic comparing the pretty-printed (`{:#?}`) representations of two values.
+    pub fn debug(expected: &impl Debug, actual: &impl Debug) -> DiffableDiagnostic {
+        DiffableDiagnostic::new(
+            Described::new("expected", format!("{:#?}", expected)),
+            Described::new("actual", format!("{:#?}", actual)),
+        )
+    }
+
+    /// The diff between the two values, labeled with their descriptions.
+    pub fn diff(&self) -> Diff {
+        Diff::new(self.expected.value.clone(), self.actual.value.clone())
+            .labels(&self.expected.description[..], &self.actual.description[..])
+    }
+}
+
+impl Doc for DiffableDiagnostic {
+    fn render<'a>(&self, ctx: &'a StyledArena<'a>, state: RenderState) -> StyledDoc<'a> {
+        let mut docs = vec![self.diff().boxed()];
+
+        for note in self.expected.notes.iter().chain(&self.actual.notes) {
+            docs.push(BOUNDARY().boxed());
+            docs.push(styled(format!("= note: {}", note), Style::default()).boxed());
+        }
+
+        DocList::new(docs).render(ctx, state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use spectrum::{EmitBackendTrait, EmitPlain};
+
+    use super::*;
+
+    #[test]
+    fn render_described() -> Result<(), Box<dyn std::error::Error>> {
+        let actual = "{ \"name\": \"veritas\", \"version\": 1 }".to_string();
+        let start = actual.find('1').unwrap();
+
+        let described = Described::new("the manifest", actual)
+            .label(Label::new(start..start + 1, "expected a string"))
+            .label(Label::secondary(2..8, "in this field"))
+            .note("versions are written as strings, like \"1.0.0\"");
+
+        assert_eq!(
+            EmitPlain.render(&described, 80)?,
+            [
+                "the manifest",
+                "  │",
+                "1 │ { \"name\": \"veritas\", \"version\": 1 }",
+                "  │   ------                        ^ expected a string",
+                "  │   │",
+                "  │   in this field",
+                "  │",
+                "  = note: versions are written as strings, like \"1.0.0\"",
+            ]
+            .join("\n")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn render_diffable() -> Result<(), Box<dyn std::error::Error>> {
+        let diagnostic = DiffableDiagnostic::new(
+            Described::new("the expected version", "1.0.0".to_string()),
+            Described::new("the published version", "1.0.1".to_string())
+                .note("the version was bumped by the release script"),
+        );
+
+        assert_eq!(
+            EmitPlain.render(&Diagnostic::Diffable(diagnostic), 80)?,
+            [
+                "- the expected version",
+                "+ the published version",
+                "",
+                "- 1.0.0",
+                "+ 1.0.1",
+                "= note: the version was bumped by the release script",
+            ]
+            .join("\n")
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,45 @@
+use std::fmt::Debug;
+
+use super::{diagnostic::Described, failure::Failure, matchers::Matcher};
+
+/// A value that is about to be checked, created by `expect`.
+#[derive(Debug)]
+pub struct Expectation<T> {
+    actual: Described<T>,
+}
+
+/// Start an assertion about `actual`:
+///
+/// ```
+/// use veritas::{contains, eq, expect, lt, MatcherExt};
+///
+/// # fn main() -> Result<(), veritas::Failure> {
+/// expect(2 + 2).to(eq(4))?;
+/// expect(vec![1, 2, 3]).to(contains(2).and(contains(4).not()))?;
+///
+/// let failure = expect(10).described_as("the answer").to(lt(5)).unwrap_err();
+/// assert_eq!(failure.message(), "expected the answer to be less than 5");
+/// # Ok(())
+/// # }
+/// ```
+pub fn expect<T>(actual: T) -> Expectation<T> {
+    Expectation {
+        actual: Described::new("the value", actual),
+    }
+}
+
+impl<T: Debug> Expectation<T> {
+    /// How the actual value is referred to in failures ("the value" by default).
+    pub fn described_as(self, description: impl Into<String>) -> Expectation<T> {
+        Expectation {
+            actual: Described::new(description, self.actual.into_value()),
+        }
+    }
+
+    pub fn to(self, matcher: impl Matcher<T>) -> Result<(), Failure> {
+        matcher.check(&Described::new(
+            self.actual.description(),
+            self.actual.value(),
+        ))
+    }
+}
@@ -0,0 +1,83 @@
+use std::fmt::{self, Display};
+
+use spectrum::{
+    compose::{StyledArena, StyledDoc},
+    render::{Nesting, RenderState},
+    styled, Color, Doc, EmitBackendTrait, EmitPlain, Style, BOUNDARY,
+};
+
+use super::diagnostic::{Described, Diagnostic};
+
+/// An assertion that didn't hold.
+///
+/// A failure has a one-line message, a diagnostic that shows the actual value (and the expected
+/// value, if there is one), and the failures that caused it, if it was made of other assertions.
+#[derive(Debug, Clone)]
+pub struct Failure {
+    message: String,
+    // Boxed to keep `Result<(), Failure>` small
+    diagnostic: Box<Diagnostic>,
+    causes: Vec<Failure>,
+}
+
+impl Failure {
+    pub fn new(message: impl Into<String>, diagnostic: impl Into<Diagnostic>) -> Failure {
+        Failure {
+            message: message.into(),
+            diagnostic: Box::new(diagnostic.into()),
+            causes: vec![],
+        }
+    }
+
+    pub fn cause(mut self, cause: Failure) -> Failure {
+        self.causes.push(cause);
+        self
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn diagnostic(&self) -> &Diagnostic {
+        &self.diagnostic
+    }
+
+    pub fn actual(&self) -> &Described<String> {
+        self.diagnostic.actual()
+    }
+
+    pub fn expected(&self) -> Option<&Described<String>> {
+        self.diagnostic.expected()
+    }
+
+    pub fn causes(&self) -> &[Failure] {
+        &self.causes
+    }
+}
+
+impl Doc for Failure {
+    fn render<'a>(&self, ctx: &'a StyledArena<'a>, state: RenderState) -> StyledDoc<'a> {
+        // The diagnostic and the causes are nested below the message
+        let mut nested = BOUNDARY()
+            .render(ctx, state)
+            .append(self.diagnostic.render(ctx, state));
+
+        for cause in &self.causes {
+            nested = nested
+                .append(BOUNDARY().render(ctx, state))
+                .append(cause.render(ctx, state));
+        }
+
+        styled(self.message.clone(), Style::from(Color::Red).bold())
+            .render(ctx, state)
+            .append(nested.nest(state.size(Nesting::Configured(1))))
+    }
+}
+
+impl Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", EmitPlain.render(self, 80)?)
+    }
+}
+
+impl std::error::Error for Failure {}
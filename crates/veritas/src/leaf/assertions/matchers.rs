@@ -0,0 +1,533 @@
+use std::{
+    cmp::Ordering,
+    collections::{BTreeSet, HashSet, VecDeque},
+    fmt::Debug,
+    hash::Hash,
+};
+
+use super::{
+    diagnostic::{Described, DiffableDiagnostic},
+    failure::Failure,
+};
+
+/// A check on a value, which is passed to `Expectation::to`.
+///
+/// A matcher describes the values it accepts (`"equal to 5"`), which is used to build failure
+/// messages, and checks the actual value, returning a `Failure` that explains why it didn't
+/// match. Matchers compose with `and`, `or` and `not`, from `MatcherExt`.
+pub trait Matcher<T: ?Sized> {
+    /// The values that this matcher accepts, phrased so that it reads well after "to be", like
+    /// "equal to 5" or "less than 10".
+    fn description(&self) -> String;
+
+    fn check(&self, actual: &Described<&T>) -> Result<(), Failure>;
+}
+
+/// Combinators for matchers. These live outside of `Matcher` so that they can be called before
+/// the type of the value being matched is known (`lt(3).or(gt(7))`).
+pub trait MatcherExt: Sized {
+    /// Match values that match both `self` and `other`.
+    fn and<M>(self, other: M) -> And<Self, M> {
+        And(self, other)
+    }
+
+    /// Match values that match either `self` or `other`.
+    fn or<M>(self, other: M) -> Or<Self, M> {
+        Or(self, other)
+    }
+
+    /// Match values that don't match `self`.
+    fn not(self) -> Not<Self> {
+        Not(self)
+    }
+}
+
+impl<M> MatcherExt for M {}
+
+/// The message of a failure, like "expected the answer to be equal to 42".
+fn expected_to_be<T: ?Sized>(actual: &Described<&T>, matcher: &impl Matcher<T>) -> String {
+    format!(
+        "expected {} to be {}",
+        actual.description(),
+        matcher.description()
+    )
+}
+
+fn mismatch<T: ?Sized + Debug>(actual: &Described<&T>, matcher: &impl Matcher<T>) -> Failure {
+    Failure::new(expected_to_be(actual, matcher), actual.debug())
+}
+
+#[derive(Debug, Clone)]
+pub struct And<A, B>(A, B);
+
+impl<T: ?Sized, A: Matcher<T>, B: Matcher<T>> Matcher<T> for And<A, B> {
+    fn description(&self) -> String {
+        format!("{} and {}", self.0.description(), self.1.description())
+    }
+
+    fn check(&self, actual: &Described<&T>) -> Result<(), Failure> {
+        self.0.check(actual)?;
+        self.1.check(actual)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Or<A, B>(A, B);
+
+impl<T: ?Sized + Debug, A: Matcher<T>, B: Matcher<T>> Matcher<T> for Or<A, B> {
+    fn description(&self) -> String {
+        format!("{} or {}", self.0.description(), self.1.description())
+    }
+
+    fn check(&self, actual: &Described<&T>) -> Result<(), Failure> {
+        match (self.0.check(actual), self.1.check(actual)) {
+            (Err(first), Err(second)) => Err(mismatch(actual, self).cause(first).cause(second)),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Not<M>(M);
+
+impl<T: ?Sized + Debug, M: Matcher<T>> Matcher<T> for Not<M> {
+    fn description(&self) -> String {
+        format!("not {}", self.0.description())
+    }
+
+    fn check(&self, actual: &Described<&T>) -> Result<(), Failure> {
+        match self.0.check(actual) {
+            Ok(()) => Err(mismatch(actual, self)),
+            Err(_) => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Eq<E>(E);
+
+/// Match values that are equal to `expected`. Failures show a diff between the two values.
+pub fn eq<E>(expected: E) -> Eq<E> {
+    Eq(expected)
+}
+
+impl<T, E> Matcher<T> for Eq<E>
+where
+    T: ?Sized + PartialEq<E> + Debug,
+    E: Debug,
+{
+    fn description(&self) -> String {
+        format!("equal to {:?}", self.0)
+    }
+
+    fn check(&self, actual: &Described<&T>) -> Result<(), Failure> {
+        if *actual.value() == &self.0 {
+            return Ok(());
+        }
+
+        let diagnostic = DiffableDiagnostic::new(
+            Described::new("expected", format!("{:#?}", self.0)),
+            actual.debug(),
+        );
+
+        Err(Failure::new(expected_to_be(actual, self), diagnostic))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Ne<E>(E);
+
+/// Match values that are not equal to `unexpected`.
+pub fn ne<E>(unexpected: E) -> Ne<E> {
+    Ne(unexpected)
+}
+
+impl<T, E> Matcher<T> for Ne<E>
+where
+    T: ?Sized + PartialEq<E> + Debug,
+    E: Debug,
+{
+    fn description(&self) -> String {
+        format!("not equal to {:?}", self.0)
+    }
+
+    fn check(&self, actual: &Described<&T>) -> Result<(), Failure> {
+        if *actual.value() != &self.0 {
+            Ok(())
+        } else {
+            Err(mismatch(actual, self))
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Compare<B> {
+    bound: B,
+    accepts: fn(Ordering) -> bool,
+    relation: &'static str,
+}
+
+/// Match values that are less than `bound`.
+pub fn lt<B>(bound: B) -> Compare<B> {
+    Compare {
+        bound,
+        accepts: |ordering| ordering == Ordering::Less,
+        relation: "less than",
+    }
+}
+
+/// Match values that are less than or equal to `bound`.
+pub fn le<B>(bound: B) -> Compare<B> {
+    Compare {
+        bound,
+        accepts: |ordering| ordering != Ordering::Greater,
+        relation: "less than or equal to",
+    }
+}
+
+/// Match values that are greater than `bound`.
+pub fn gt<B>(bound: B) -> Compare<B> {
+    Compare {
+        bound,
+        accepts: |ordering| ordering == Ordering::Greater,
+        relation: "greater than",
+    }
+}
+
+/// Match values that are greater than or equal to `bound`.
+pub fn ge<B>(bound: B) -> Compare<B> {
+    Compare {
+        bound,
+        accepts: |ordering| ordering != Ordering::Less,
+        relation: "greater than or equal to",
+    }
+}
+
+impl<T, B> Matcher<T> for Compare<B>
+where
+    T: ?Sized + PartialOrd<B> + Debug,
+    B: Debug,
+{
+    fn description(&self) -> String {
+        format!("{} {:?}", self.relation, self.bound)
+    }
+
+    fn check(&self, actual: &Described<&T>) -> Result<(), Failure> {
+        match (*actual.value()).partial_cmp(&self.bound) {
+            Some(ordering) if (self.accepts)(ordering) => Ok(()),
+            _ => Err(mismatch(actual, self)),
+        }
+    }
+}
+
+/// Values that can be searched with `contains`.
+pub trait Container<Item: ?Sized> {
+    fn contains_item(&self, item: &Item) -> bool;
+}
+
+impl<T: PartialEq> Container<T> for [T] {
+    fn contains_item(&self, item: &T) -> bool {
+        self.contains(item)
+    }
+}
+
+impl<T: PartialEq, const N: usize> Container<T> for [T; N] {
+    fn contains_item(&self, item: &T) -> bool {
+        self[..].contains(item)
+    }
+}
+
+impl<T: PartialEq> Container<T> for Vec<T> {
+    fn contains_item(&self, item: &T) -> bool {
+        self[..].contains(item)
+    }
+}
+
+impl<T: PartialEq> Container<T> for VecDeque<T> {
+    fn contains_item(&self, item: &T) -> bool {
+        self.contains(item)
+    }
+}
+
+impl<T: std::cmp::Eq + Hash> Container<T> for HashSet<T> {
+    fn contains_item(&self, item: &T) -> bool {
+        self.contains(item)
+    }
+}
+
+impl<T: Ord> Container<T> for BTreeSet<T> {
+    fn contains_item(&self, item: &T) -> bool {
+        self.contains(item)
+    }
+}
+
+impl<'s> Container<&'s str> for str {
+    fn contains_item(&self, item: &&'s str) -> bool {
+        self.contains(*item)
+    }
+}
+
+impl<'s> Container<&'s str> for String {
+    fn contains_item(&self, item: &&'s str) -> bool {
+        self.contains(*item)
+    }
+}
+
+impl<'s> Container<&'s str> for &str {
+    fn contains_item(&self, item: &&'s str) -> bool {
+        (*self).contains(*item)
+    }
+}
+
+impl<T: PartialEq> Container<T> for Option<T> {
+    fn contains_item(&self, item: &T) -> bool {
+        self.as_ref() == Some(item)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Contains<I>(I);
+
+/// Match collections that contain `item`, and strings that contain the substring `item`.
+pub fn contains<I>(item: I) -> Contains<I> {
+    Contains(item)
+}
+
+impl<T, I> Matcher<T> for Contains<I>
+where
+    T: ?Sized + Container<I> + Debug,
+    I: Debug,
+{
+    fn description(&self) -> String {
+        format!("something that contains {:?}", self.0)
+    }
+
+    fn check(&self, actual: &Described<&T>) -> Result<(), Failure> {
+        if (*actual.value()).contains_item(&self.0) {
+            Ok(())
+        } else {
+            Err(mismatch(actual, self))
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct IsSome;
+
+/// Match `Some` values.
+pub fn is_some() -> IsSome {
+    IsSome
+}
+
+impl<T: Debug> Matcher<Option<T>> for IsSome {
+    fn description(&self) -> String {
+        "Some".to_string()
+    }
+
+    fn check(&self, actual: &Described<&Option<T>>) -> Result<(), Failure> {
+        match actual.value() {
+            Some(_) => Ok(()),
+            None => Err(mismatch(actual, self)),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct IsNone;
+
+/// Match `None`.
+pub fn is_none() -> IsNone {
+    IsNone
+}
+
+impl<T: Debug> Matcher<Option<T>> for IsNone {
+    fn description(&self) -> String {
+        "None".to_string()
+    }
+
+    fn check(&self, actual: &Described<&Option<T>>) -> Result<(), Failure> {
+        match actual.value() {
+            None => Ok(()),
+            Some(_) => Err(mismatch(actual, self)),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct IsOk;
+
+/// Match `Ok` values.
+pub fn is_ok() -> IsOk {
+    IsOk
+}
+
+impl<T: Debug, E: Debug> Matcher<Result<T, E>> for IsOk {
+    fn description(&self) -> String {
+        "Ok".to_string()
+    }
+
+    fn check(&self, actual: &Described<&Result<T, E>>) -> Result<(), Failure> {
+        match actual.value() {
+            Ok(_) => Ok(()),
+            Err(_) => Err(mismatch(actual, self)),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct IsErr;
+
+/// Match `Err` values.
+pub fn is_err() -> IsErr {
+    IsErr
+}
+
+impl<T: Debug, E: Debug> Matcher<Result<T, E>> for IsErr {
+    fn description(&self) -> String {
+        "Err".to_string()
+    }
+
+    fn check(&self, actual: &Described<&Result<T, E>>) -> Result<(), Failure> {
+        match actual.value() {
+            Err(_) => Ok(()),
+            Ok(_) => Err(mismatch(actual, self)),
+        }
+    }
+}
+
+pub struct Matches<F> {
+    description: String,
+    predicate: F,
+}
+
+/// Match values that satisfy `predicate`. `description` describes the values that it accepts,
+/// like "an even number".
+pub fn matches<F>(description: impl Into<String>, predicate: F) -> Matches<F> {
+    Matches {
+        description: description.into(),
+        predicate,
+    }
+}
+
+impl<T, F> Matcher<T> for Matches<F>
+where
+    T: ?Sized + Debug,
+    F: Fn(&T) -> bool,
+{
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn check(&self, actual: &Described<&T>) -> Result<(), Failure> {
+        if (self.predicate)(*actual.value()) {
+            Ok(())
+        } else {
+            Err(mismatch(actual, self))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use spectrum::{EmitBackendTrait, EmitPlain};
+
+    use crate::expect;
+
+    use super::*;
+
+    type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+    #[test]
+    fn passing_matchers() -> TestResult {
+        expect(5).to(eq(5))?;
+        expect(5).to(ne(6))?;
+        expect(5).to(lt(6).and(le(5)).and(gt(4)).and(ge(5)))?;
+        expect(vec![1, 2, 3]).to(contains(2))?;
+        expect("hello world").to(contains("o w"))?;
+        expect(Some(1)).to(is_some())?;
+        expect(None::<i32>).to(is_none())?;
+        expect(Ok::<_, ()>(1)).to(is_ok())?;
+        expect(Err::<(), _>("nope")).to(is_err())?;
+        expect(4).to(matches("an even number", |n: &i32| n % 2 == 0))?;
+        expect(4).to(eq(3).or(eq(4)))?;
+        expect(4).to(eq(3).not())?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn failure_messages() -> TestResult {
+        let failure = expect(10).described_as("the count").to(lt(5)).unwrap_err();
+        assert_eq!(failure.message(), "expected the count to be less than 5");
+        assert_eq!(failure.actual().value(), "10");
+        assert!(failure.expected().is_none());
+
+        let failure = expect(None::<i32>).to(is_some()).unwrap_err();
+        assert_eq!(failure.message(), "expected the value to be Some");
+
+        let failure = expect(4).to(ne(3).and(eq(4).not())).unwrap_err();
+        assert_eq!(failure.message(), "expected the value to be not equal to 4");
+
+        let failure = expect(3)
+            .to(matches("an even number", |n: &i32| n % 2 == 0))
+            .unwrap_err();
+        assert_eq!(failure.message(), "expected the value to be an even number");
+
+        Ok(())
+    }
+
+    #[test]
+    fn eq_failures_are_diffable() -> TestResult {
+        let failure = expect(vec![1, 3]).to(eq(vec![1, 2, 3])).unwrap_err();
+
+        assert_eq!(
+            failure.expected().map(|e| &e.value()[..]),
+            Some("[\n    1,\n    2,\n    3,\n]")
+        );
+        assert_eq!(
+            EmitPlain.render(&failure, 80)?,
+            [
+                "expected the value to be equal to [1, 2, 3]",
+                "  - expected",
+                "  + the value",
+                "",
+                "    [",
+                "        1,",
+                "  -     2,",
+                "        3,",
+                "    ]",
+            ]
+            .join("\n")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn or_failures_have_causes() -> TestResult {
+        let failure = expect(5).to(lt(3).or(gt(7))).unwrap_err();
+
+        assert_eq!(
+            failure.message(),
+            "expected the value to be less than 3 or greater than 7"
+        );
+        assert_eq!(failure.causes().len(), 2);
+        assert_eq!(
+            EmitPlain.render(&failure, 80)?,
+            [
+                "expected the value to be less than 3 or greater than 7",
+                "  the value",
+                "  5",
+                "  expected the value to be less than 3",
+                "    the value",
+                "    5",
+                "  expected the value to be greater than 7",
+                "    the value",
+                "    5",
+            ]
+            .join("\n")
+        );
+
+        Ok(())
+    }
+}
@@ -1,2 +1,5 @@
-mod diagnostic;
+pub mod diagnostic;
+pub mod expect;
+pub mod failure;
+pub mod matchers;
 mod traits;
@@ -1,2 +1,2 @@
-mod assertions;
+pub mod assertions;
 mod traits;
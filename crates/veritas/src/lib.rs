@@ -1 +1,12 @@
+#[macro_use]
+extern crate spectrum;
+
 mod leaf;
+
+pub use leaf::assertions::diagnostic::{Described, Diagnostic, DiffableDiagnostic, Label};
+pub use leaf::assertions::expect::{expect, Expectation};
+pub use leaf::assertions::failure::Failure;
+pub use leaf::assertions::matchers::{
+    contains, eq, ge, gt, is_err, is_none, is_ok, is_some, le, lt, matches, ne, Container, Matcher,
+    MatcherExt,
+};
@@ -4,21 +4,24 @@ pub struct TypedFunction<In, Out>
 where
     Out: 'static,
 {
-    function: Box<dyn FnMut(&mut In) -> Out + 'static>,
+    function: Box<dyn FnMut(&mut In) -> Out + Send + 'static>,
 }
 
 impl<In, Out> TypedFunction<In, Out>
 where
     Out: 'static,
 {
-    pub fn new(function: impl FnMut(&mut In) -> Out + 'static) -> TypedFunction<In, Out> {
+    pub fn new(function: impl FnMut(&mut In) -> Out + Send + 'static) -> TypedFunction<In, Out> {
         TypedFunction {
             function: Box::new(function),
         }
     }
 
     #[cfg(test)]
-    pub fn curry_mut(self, arg: &'_ mut In) -> TypedCurriedMutFunction<'_, In, Out> {
+    pub fn curry_mut(self, arg: &'_ mut In) -> TypedCurriedMutFunction<'_, In, Out>
+    where
+        In: Send,
+    {
         TypedCurriedMutFunction {
             function: self.function,
             argument: arg,
@@ -28,7 +31,7 @@ where
     #[cfg(test)]
     pub fn curry(self, arg: In) -> TypedCurriedFunction<'static, In, Out>
     where
-        In: Clone + 'static,
+        In: Clone + Send + 'static,
     {
         TypedCurriedFunction {
             function: self.function,
@@ -36,7 +39,7 @@ where
         }
     }
 
-    pub fn hide_fn<'a>(self, arg: impl FnMut() -> In + 'a) -> Function<'a, Out>
+    pub fn hide_fn<'a>(self, arg: impl FnMut() -> In + Send + 'a) -> Function<'a, Out>
     where
         In: 'a,
     {
@@ -50,7 +53,7 @@ where
     #[cfg(test)]
     pub fn hide(self, arg: In) -> Function<'static, Out>
     where
-        In: Clone + 'static,
+        In: Clone + Send + 'static,
     {
         self.curry(arg).hide()
     }
@@ -59,17 +62,17 @@ where
 #[cfg(test)]
 pub struct TypedCurriedMutFunction<'a, In, Out>
 where
-    In: 'static,
+    In: Send + 'static,
     Out: 'static,
 {
-    function: Box<dyn FnMut(&mut In) -> Out + 'static>,
+    function: Box<dyn FnMut(&mut In) -> Out + Send + 'static>,
     argument: &'a mut In,
 }
 
 #[cfg(test)]
 impl<'a, In, Out> TypedCurriedMutFunction<'a, In, Out>
 where
-    In: 'static,
+    In: Send + 'static,
     Out: 'static,
 {
     pub fn hide(self) -> Function<'a, Out> {
@@ -90,8 +93,8 @@ where
     In: 'a,
     Out: 'static,
 {
-    function: Box<dyn FnMut(&mut In) -> Out + 'static>,
-    argument: Box<dyn FnMut() -> In + 'a>,
+    function: Box<dyn FnMut(&mut In) -> Out + Send + 'static>,
+    argument: Box<dyn FnMut() -> In + Send + 'a>,
 }
 
 impl<'a, In, Out> TypedCurriedFunction<'a, In, Out>
@@ -116,7 +119,7 @@ where
 }
 
 pub struct Function<'a, Out> {
-    function: Box<dyn FnMut() -> Out + 'a>,
+    function: Box<dyn FnMut() -> Out + Send + 'a>,
     lt: PhantomData<&'a mut ()>,
 }
 
@@ -144,7 +147,7 @@ mod tests {
         function.hide()
     }
 
-    fn hide_mut<T>(function: TypedCurriedMutFunction<'_, T, usize>) -> Function<usize> {
+    fn hide_mut<T: Send>(function: TypedCurriedMutFunction<'_, T, usize>) -> Function<'_, usize> {
         function.hide()
     }
 
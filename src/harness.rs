@@ -0,0 +1,502 @@
+use std::{fmt::Display, time::Instant};
+
+use spectrum::{EmitColored, EmitPlain};
+
+use crate::{
+    reporter::StartedReporter,
+    suite::{
+        filter::Filter,
+        finalized::{run_parallel, FinalizedSuite},
+        DurationWithPrecision, FullSuiteInfo, StartInfo,
+    },
+    DurationPrecision, LibtestJsonReporter, MinimalReporter, ReportResult, Reporter,
+    ReporterOutput, SpecReporter, SuiteOutcome,
+};
+
+const USAGE: &str = "Usage: [OPTIONS] [FILTERS...]
+
+Options:
+        --include-ignored
+                        Run ignored and not ignored specs
+        --ignored       Run only ignored specs
+        --exact         Exactly match filters rather than by substring
+        --skip FILTER   Skip specs whose names contain FILTER (this flag can
+                        be used multiple times)
+        --list          List all specs
+        --nocapture     Accepted for libtest compatibility; output is never
+                        captured
+        --show-output   Accepted for libtest compatibility; output is never
+                        captured
+        --test-threads n_threads
+                        Number of threads used for running suites (default
+                        1)
+        --color auto|always|never
+                        Configure coloring of output
+        --format pretty|terse|json
+                        Configure formatting of output
+    -q, --quiet         Only display the summary, like --format terse
+    -h, --help          Display this message
+";
+
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub enum ColorChoice {
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub enum OutputFormat {
+    #[default]
+    Pretty,
+    Terse,
+    /// libtest's JSON event stream, which needs `-Z unstable-options` with libtest
+    Json,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ArgumentError(String);
+
+impl Display for ArgumentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for ArgumentError {}
+
+/// The command line arguments that libtest accepts, as `cargo test -- <args>` passes them.
+///
+/// Output isn't captured, so `--nocapture` and `--show-output` are accepted but don't change how
+/// specs run. Unlike libtest, suites run one at a time unless `--test-threads` asks for more than
+/// one thread.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Arguments {
+    pub filters: Vec<String>,
+    pub skip: Vec<String>,
+    pub exact: bool,
+    pub ignored: bool,
+    pub include_ignored: bool,
+    pub list: bool,
+    pub nocapture: bool,
+    pub show_output: bool,
+    pub test_threads: Option<usize>,
+    pub color: ColorChoice,
+    pub format: OutputFormat,
+    pub bench: bool,
+    pub help: bool,
+}
+
+impl Arguments {
+    /// The arguments that the current process was started with.
+    pub fn from_env() -> Result<Arguments, ArgumentError> {
+        Arguments::parse(std::env::args().skip(1))
+    }
+
+    pub fn parse(
+        args: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Result<Arguments, ArgumentError> {
+        let mut arguments = Arguments::default();
+        let mut args = args.into_iter().map(Into::into);
+
+        while let Some(arg) = args.next() {
+            // Options that take a value accept it as `--option=value` as well as `--option value`
+            let (name, inline) = match arg.find('=') {
+                Some(index) if arg.starts_with("--") => {
+                    (arg[..index].to_string(), Some(arg[index + 1..].to_string()))
+                }
+                _ => (arg.clone(), None),
+            };
+
+            let mut value = || {
+                inline
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| ArgumentError(format!("Argument to option '{}' missing", name)))
+            };
+
+            match name.as_str() {
+                "--exact" => arguments.exact = true,
+                "--ignored" => arguments.ignored = true,
+                "--include-ignored" => arguments.include_ignored = true,
+                "--list" => arguments.list = true,
+                "--nocapture" => arguments.nocapture = true,
+                "--show-output" => arguments.show_output = true,
+                "-q" | "--quiet" => arguments.format = OutputFormat::Terse,
+                "--bench" => arguments.bench = true,
+                "--test" => {}
+                "-h" | "--help" => arguments.help = true,
+                "--skip" => arguments.skip.push(value()?),
+                "-Z" => {
+                    value()?;
+                }
+                "--test-threads" => {
+                    let threads = value()?;
+
+                    arguments.test_threads = match threads.parse::<usize>() {
+                        Ok(threads) if threads > 0 => Some(threads),
+                        _ => {
+                            return Err(ArgumentError(format!(
+                                "argument for --test-threads must be a number > 0 (got {})",
+                                threads
+                            )))
+                        }
+                    };
+                }
+                "--color" => {
+                    arguments.color = match value()?.as_str() {
+                        "auto" => ColorChoice::Auto,
+                        "always" => ColorChoice::Always,
+                        "never" => ColorChoice::Never,
+                        other => {
+                            return Err(ArgumentError(format!(
+                                "argument for --color must be auto, always, or never (was {})",
+                                other
+                            )))
+                        }
+                    };
+                }
+                "--format" => {
+                    arguments.format = match value()?.as_str() {
+                        "pretty" => OutputFormat::Pretty,
+                        "terse" => OutputFormat::Terse,
+                        "json" => OutputFormat::Json,
+                        other => {
+                            return Err(ArgumentError(format!(
+                                "argument for --format must be pretty, terse, or json (was {})",
+                                other
+                            )))
+                        }
+                    };
+                }
+                _ if arg.starts_with('-') => {
+                    return Err(ArgumentError(format!("Unrecognized option: '{}'", arg)));
+                }
+                _ => arguments.filters.push(arg),
+            }
+        }
+
+        Ok(arguments)
+    }
+
+    fn matches_filter(&self, name: &str, filter: &str) -> bool {
+        if self.exact {
+            name == filter
+        } else {
+            name.contains(filter)
+        }
+    }
+
+    /// Whether the spec with the full name `name` is selected by these arguments.
+    pub fn selects(&self, name: &str, skipped: bool) -> bool {
+        if self.ignored && !skipped {
+            return false;
+        }
+
+        if self
+            .skip
+            .iter()
+            .any(|filter| self.matches_filter(name, filter))
+        {
+            return false;
+        }
+
+        self.filters.is_empty()
+            || self
+                .filters
+                .iter()
+                .any(|filter| self.matches_filter(name, filter))
+    }
+}
+
+/// The outcome of every suite that a harness ran.
+#[derive(Debug, Clone, Default)]
+pub struct Conclusion {
+    outcomes: Vec<SuiteOutcome>,
+}
+
+impl Conclusion {
+    pub fn outcomes(&self) -> &[SuiteOutcome] {
+        &self.outcomes
+    }
+
+    pub fn failures(&self) -> usize {
+        self.outcomes.iter().map(|outcome| outcome.failures()).sum()
+    }
+
+    /// The exit code that libtest would use: 101 if any spec failed.
+    pub fn exit_code(&self) -> i32 {
+        if self.failures() == 0 {
+            0
+        } else {
+            101
+        }
+    }
+}
+
+/// Runs registered suites as the main function of a `harness = false` test target.
+///
+/// ```no_run
+/// use laboratory::{test::*, Harness};
+///
+/// Harness::from_args()
+///     .suite(describe("add_one()").specs(|it| {
+///         it.should("return 1", || expect(0 + 1).to(eq(1)));
+///     }))
+///     .exit();
+/// ```
+///
+/// The `laboratory::main!` macro writes this main function for you.
+#[derive(Debug)]
+pub struct Harness {
+    arguments: Arguments,
+    reporter: Option<Box<dyn StartedReporter>>,
+    filter: Option<Filter>,
+    suites: Vec<FinalizedSuite>,
+}
+
+impl Harness {
+    pub fn new(arguments: Arguments) -> Harness {
+        Harness {
+            arguments,
+            reporter: None,
+            filter: None,
+            suites: vec![],
+        }
+    }
+
+    /// A harness for the arguments that the current process was started with. If they can't be
+    /// parsed, or if `--help` was passed, this prints a message and exits.
+    pub fn from_args() -> Harness {
+        match Arguments::from_env() {
+            Ok(arguments) if arguments.help => {
+                print!("{}", USAGE);
+                std::process::exit(0);
+            }
+            Ok(arguments) => Harness::new(arguments),
+            Err(err) => {
+                eprintln!("error: {}", err);
+                std::process::exit(101);
+            }
+        }
+    }
+
+    pub fn arguments(&self) -> &Arguments {
+        &self.arguments
+    }
+
+    /// Report with `reporter` instead of the one that `--format` picks: the `SpecReporter`,
+    /// the `MinimalReporter` or the `LibtestJsonReporter`.
+    pub fn reporter(mut self, reporter: impl Reporter + 'static) -> Harness {
+        self.reporter = Some(Box::new(reporter.start()));
+        self
+    }
+
+    /// Only run the specs that `filter` selects, as well as the arguments and the suites' own
+    /// filters. Unlike the ones that the arguments filter out, the specs that `filter` leaves out
+    /// are reported as `SpecStatus::Filtered`.
+    pub fn filter(mut self, filter: Filter) -> Harness {
+        self.filter = Some(filter);
+        self
+    }
+
+    pub fn suite(mut self, suite: impl Into<FinalizedSuite>) -> Harness {
+        self.suites.push(suite.into());
+        self
+    }
+
+    pub fn run(self) -> ReportResult<Conclusion> {
+        let output = ReporterOutput::stdout();
+
+        match self.arguments.color {
+            ColorChoice::Auto => self.run_with(output),
+            ColorChoice::Always => self.run_with(output.backend(EmitColored)),
+            ColorChoice::Never => self.run_with(output.backend(EmitPlain)),
+        }
+    }
+
+    pub fn run_with(self, mut output: ReporterOutput) -> ReportResult<Conclusion> {
+        let Harness {
+            arguments,
+            reporter,
+            filter,
+            suites,
+        } = self;
+
+        // Benchmarks aren't supported, so there's nothing to run for `cargo bench`
+        if arguments.bench {
+            return Ok(Conclusion::default());
+        }
+
+        let total: usize = suites.iter().map(|suite| suite.spec_count()).sum();
+
+        let suites: Vec<FinalizedSuite> = suites
+            .into_iter()
+            .filter_map(|suite| {
+                suite.retain("", false, &mut |name, skipped| {
+                    arguments.selects(name, skipped)
+                })
+            })
+            .map(|suite| match &filter {
+                Some(filter) => suite.select_own().select(filter, "", &[]),
+                None => suite.select_own(),
+            })
+            .collect();
+
+        if arguments.list {
+            let mut out = output.for_suite(0);
+            let names: Vec<_> = suites
+                .iter()
+                .flat_map(|suite| suite.spec_names(""))
+                .collect();
+
+            for (name, _) in &names {
+                out.raw(format!("{}: test\n", name))?;
+            }
+
+            if arguments.format == OutputFormat::Pretty {
+                out.raw(format!("\n{} tests, 0 benchmarks\n", names.len()))?;
+            }
+
+            return Ok(Conclusion::default());
+        }
+
+        let suites: Vec<FinalizedSuite> = if arguments.ignored || arguments.include_ignored {
+            suites.into_iter().map(|suite| suite.unskip()).collect()
+        } else {
+            suites
+        };
+
+        // A focused spec or suite in any of the suites leaves out the others in all of them
+        let focused = suites.iter().any(|suite| suite.has_focus());
+        let suites: Vec<FinalizedSuite> = if focused {
+            suites.into_iter().map(|suite| suite.focus(false)).collect()
+        } else {
+            suites
+        };
+
+        let mut reporter = reporter.unwrap_or_else(|| match arguments.format {
+            OutputFormat::Pretty => Box::new(SpecReporter.start()),
+            OutputFormat::Terse => Box::new(MinimalReporter.start()),
+            OutputFormat::Json => Box::new(LibtestJsonReporter.start()),
+        });
+
+        let test_count: usize = suites.iter().map(|suite| suite.spec_count()).sum();
+
+        reporter.start_run(
+            &mut output.for_suite(0),
+            &StartInfo {
+                test_count,
+                filtered_out: total - test_count,
+            },
+        )?;
+
+        let precision = suites
+            .first()
+            .map(|suite| suite.precision())
+            .unwrap_or(DurationPrecision::Millis);
+        let start = Instant::now();
+        // `--test-threads` wins over the threads that the suites ask for with `parallel`
+        let threads = arguments.test_threads.unwrap_or_else(|| {
+            suites
+                .iter()
+                .map(|suite| suite.threads())
+                .max()
+                .unwrap_or(1)
+        });
+        let suites = suites.into_iter().map(|suite| suite.nest(0));
+
+        let outcomes = if threads > 1 {
+            run_parallel(suites.collect(), threads, &mut output, &mut *reporter)?
+        } else {
+            let mut outcomes = vec![];
+
+            for suite in suites {
+                outcomes.push(suite.run_with_reporter(&mut output, &mut *reporter)?);
+            }
+
+            outcomes
+        };
+
+        reporter.finish(
+            &mut output.for_suite(0),
+            FullSuiteInfo {
+                duration: DurationWithPrecision::new(start.elapsed(), precision),
+                focused,
+            },
+        )?;
+
+        Ok(Conclusion { outcomes })
+    }
+
+    /// Run the suites and exit the process, with a failing status if any spec failed.
+    pub fn exit(self) -> ! {
+        match self.run() {
+            Ok(conclusion) => std::process::exit(conclusion.exit_code()),
+            Err(err) => {
+                eprintln!("error: {:?}", err);
+                std::process::exit(101);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_libtest_arguments() {
+        let arguments = Arguments::parse(vec![
+            "add_one",
+            "--exact",
+            "--skip",
+            "slow",
+            "--test-threads=4",
+            "--color",
+            "never",
+            "-q",
+            "-Z",
+            "unstable-options",
+        ])
+        .unwrap();
+
+        assert_eq!(
+            arguments,
+            Arguments {
+                filters: vec!["add_one".to_string()],
+                skip: vec!["slow".to_string()],
+                exact: true,
+                test_threads: Some(4),
+                color: ColorChoice::Never,
+                format: OutputFormat::Terse,
+                ..Arguments::default()
+            }
+        );
+    }
+
+    #[test]
+    fn reject_bad_arguments() {
+        assert!(Arguments::parse(vec!["--frobnicate"]).is_err());
+        assert!(Arguments::parse(vec!["--test-threads", "0"]).is_err());
+        assert!(Arguments::parse(vec!["--skip"]).is_err());
+        assert!(Arguments::parse(vec!["--format=xml"]).is_err());
+    }
+
+    #[test]
+    fn select_specs() {
+        let substring = Arguments::parse(vec!["add_one"]).unwrap();
+        assert!(substring.selects("math::add_one()::return 1", false));
+        assert!(!substring.selects("math::sub_one()::return 1", false));
+
+        let exact = Arguments::parse(vec!["--exact", "math::add_one()"]).unwrap();
+        assert!(!exact.selects("math::add_one()::return 1", false));
+        assert!(exact.selects("math::add_one()", false));
+
+        let ignored = Arguments::parse(vec!["--ignored", "--skip", "slow"]).unwrap();
+        assert!(ignored.selects("math::return 1", true));
+        assert!(!ignored.selects("math::return 1", false));
+        assert!(!ignored.selects("math::slow return 1", true));
+    }
+}
@@ -1,79 +1,81 @@
-// mod curry;
-// mod reporter;
-// mod spec;
-// mod spec_result;
-// mod suite;
-// mod suite_result;
-// pub mod test;
-
-// pub use laboratory_expectations::Expect;
-// pub use reporter::{
-//     json::JsonReporter,
-//     output::ReporterOutput,
-//     spec::{MinimalReporter, SpecReporter, StartedSpecReporter},
-//     ReportResult, Reporter,
-// };
-// pub use serde::{Deserialize, Serialize};
-// pub use spec::Spec;
-// use suite::described::{DescribedSuite, SuiteDetails};
-// pub use suite::{traits::SuiteExt, DurationPrecision, Suite};
-// pub use suite_result::SuiteOutcome;
-
-// #[macro_export]
-// macro_rules! should_panic {
-//     ($name:expr, $handle: expr) => {{
-//         use std::panic::{catch_unwind, set_hook, take_hook};
-
-//         set_hook(Box::new(|_| {
-//             // println!("");
-//         }));
-//         let tmp_result = catch_unwind(|| {
-//             ($handle)();
-//         })
-//         .is_ok();
-//         let _ = take_hook();
-//         if tmp_result == false {
-//             Ok(())
-//         } else {
-//             Err(format!(
-//                 "Expected {} to panic but it didn't",
-//                 stringify!($name)
-//             ))
-//         }
-//     }};
-// }
-
-// #[macro_export]
-// macro_rules! should_not_panic {
-//     ($name:expr, $handle: expr) => {{
-//         use std::panic::{catch_unwind, set_hook, take_hook};
-
-//         set_hook(Box::new(|_| {
-//             // println!("");
-//         }));
-//         let tmp_result = catch_unwind(|| {
-//             ($handle)();
-//         })
-//         .is_ok();
-//         let _ = take_hook();
-//         if tmp_result == true {
-//             Ok(())
-//         } else {
-//             Err(format!(
-//                 "Expected {} to panic but it didn't",
-//                 stringify!($name)
-//             ))
-//         }
-//     }};
-// }
-
-// pub fn describe(name: &'static str) -> DescribedSuite {
-//     DescribedSuite::new(name.to_string())
-// }
-
-// pub fn describe_skip(name: &'static str) -> DescribedSuite {
-//     DescribedSuite::new(name.to_string()).skip()
-// }
+mod curry;
+mod harness;
+mod reporter;
+mod spec;
+mod spec_result;
+mod suite;
+mod suite_result;
+pub mod test;
+mod unwind;
+
+pub use harness::{ArgumentError, Arguments, ColorChoice, Conclusion, Harness, OutputFormat};
+pub use reporter::{
+    json::{
+        JsonAttempt, JsonFailure, JsonPanic, JsonReport, JsonReporter, JsonStatus, JsonSuite,
+        JsonTest, JsonValue, JSON_SCHEMA_VERSION,
+    },
+    junit::JunitReporter,
+    libtest::LibtestJsonReporter,
+    output::{ReporterOutput, SuiteOutput},
+    spec::{MinimalReporter, SpecReporter, StartedSpecReporter},
+    tap::TapReporter,
+    ReportError, ReportResult, Reporter, StartedReporter,
+};
+pub use serde::{Deserialize, Serialize};
+pub use spec::{It, MatchResult, Spec, SpecHandle, TypedIt, TypedMutableIt, TypedSpec};
+pub use spec_result::{HookFailure, HookKind, SpecAttempt, SpecInfo, SpecPanic, SpecStatus};
+pub use suite::described::{DescribedSuite, SuiteDetails};
+pub use suite::{
+    filter::Filter,
+    finalized::FinalizedSuite,
+    hooks::HookResult,
+    mutable::{StateAbandoned, SuiteWithMutableState},
+    state::SuiteWithState,
+    traits::{RunnableSuite, SuiteExt},
+    DurationPrecision, DurationWithPrecision, FullSuiteInfo, StartInfo, Suite,
+};
+pub use suite_result::SuiteOutcome;
+
+/// Write a `main` function that runs `suites` as a test harness, for test targets with
+/// `harness = false`. It accepts the arguments that `cargo test -- <args>` passes to libtest.
+///
+/// ```ignore
+/// laboratory::main!(math_suite(), string_suite());
+///
+/// // Or, with another reporter
+/// laboratory::main!(reporter = laboratory::MinimalReporter; math_suite(), string_suite());
+/// ```
+#[macro_export]
+macro_rules! main {
+    (reporter = $reporter:expr; $($suite:expr),+ $(,)?) => {
+        fn main() {
+            $crate::Harness::from_args()
+                .reporter($reporter)
+                $(.suite($suite))+
+                .exit()
+        }
+    };
+    ($($suite:expr),+ $(,)?) => {
+        fn main() {
+            $crate::Harness::from_args()
+                $(.suite($suite))+
+                .exit()
+        }
+    };
+}
+
+pub fn describe(name: impl Into<String>) -> DescribedSuite {
+    DescribedSuite::new(name)
+}
+
+pub fn describe_skip(name: impl Into<String>) -> DescribedSuite {
+    DescribedSuite::new(name).skip()
+}
+
+/// A focused suite: while anything is focused, only the focused suites and specs run.
+pub fn describe_only(name: impl Into<String>) -> DescribedSuite {
+    DescribedSuite::new(name).only()
+}
 
 // trait SpecCallback {
 //     type State: Clone + 'static;
@@ -1,52 +1,266 @@
 use derive_new::new;
 use serde::{Deserialize, Serialize};
-use std::{fmt::Write, time::Duration};
+use std::time::Duration;
+use veritas::{Described, Failure};
 
 use crate::{
-    spec_result::{SpecInfo, SpecStatus},
-    suite::described::SuiteDetails,
-    suite::DurationWithPrecision,
+    spec_result::{HookFailure, HookKind, SpecAttempt, SpecInfo, SpecStatus},
+    suite::{described::SuiteDetails, finalized::spec_path},
     suite_result::SuiteFinished,
 };
 
 use super::{output::SuiteOutput, ReportResult, Reporter, StartedReporter};
 
-#[derive(Debug, Serialize, Deserialize)]
-struct JsonOutput {
-    name: String,
-    passing: usize,
-    failing: usize,
-    ignored: usize,
-    child_suites: Vec<()>,
-    child_tests: Vec<JsonTest>,
-    duration: Duration,
+/// The version of the JSON that `JsonReporter` emits. It changes whenever a field is removed or
+/// changes meaning, or a tool could be handed a value it doesn't know, like a new `JsonStatus`,
+/// so that tools reading the output can tell which layout they're looking at.
+///
+/// Version 2 added the `flaky` and `filtered` statuses, and the `hook_failures`, `panic`,
+/// `timed_out`, `attempts` and `filtered` fields.
+pub const JSON_SCHEMA_VERSION: u32 = 2;
+
+/// The document that `JsonReporter` emits for each top-level suite.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonReport {
+    pub version: u32,
+    pub suite: JsonSuite,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct JsonTest {
-    name: String,
-    full_name: String,
-    pass: bool,
-    error_message: Option<String>,
-    duration: Duration,
+/// A suite, with the results of its own specs and of the suites nested in it. The counts include
+/// the nested suites.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonSuite {
+    pub name: String,
+    pub skip: bool,
+    pub passing: usize,
+    pub failing: usize,
+    pub ignored: usize,
+    /// The specs that the run's filter left out.
+    #[serde(default)]
+    pub filtered: usize,
+    pub duration: Duration,
+    pub child_suites: Vec<JsonSuite>,
+    pub child_tests: Vec<JsonTest>,
+    /// The suite's `after_all` hooks that failed. They count as failing.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hook_failures: Vec<JsonFailure>,
+}
+
+impl JsonSuite {
+    fn new(name: impl Into<String>, skip: bool) -> JsonSuite {
+        JsonSuite {
+            name: name.into(),
+            skip,
+            passing: 0,
+            failing: 0,
+            ignored: 0,
+            filtered: 0,
+            duration: Duration::default(),
+            child_suites: vec![],
+            child_tests: vec![],
+            hook_failures: vec![],
+        }
+    }
+
+    fn count(&mut self) {
+        self.failing += self.hook_failures.len();
+
+        for test in &self.child_tests {
+            match test.status {
+                JsonStatus::Passed | JsonStatus::Flaky => self.passing += 1,
+                JsonStatus::Failed => self.failing += 1,
+                JsonStatus::Skipped => self.ignored += 1,
+                JsonStatus::Filtered => self.filtered += 1,
+            }
+        }
+
+        for suite in &self.child_suites {
+            self.passing += suite.passing;
+            self.failing += suite.failing;
+            self.ignored += suite.ignored;
+            self.filtered += suite.filtered;
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JsonStatus {
+    Passed,
+    Failed,
+    /// The spec passed when it was retried, after it failed. It counts as passing.
+    Flaky,
+    Skipped,
+    /// The spec wasn't selected by the run's filter. It's counted in `filtered`.
+    Filtered,
+}
+
+/// The status of a spec or an attempt, and the details of why it didn't pass.
+fn outcome(
+    status: &SpecStatus,
+) -> (
+    JsonStatus,
+    Option<JsonFailure>,
+    Option<JsonPanic>,
+    Option<Duration>,
+) {
+    match status {
+        SpecStatus::Success => (JsonStatus::Passed, None, None, None),
+        SpecStatus::Failure(failure) => (
+            JsonStatus::Failed,
+            Some(JsonFailure::from(failure)),
+            None,
+            None,
+        ),
+        SpecStatus::HookFailure(failure) => (
+            JsonStatus::Failed,
+            Some(JsonFailure::from(failure)),
+            None,
+            None,
+        ),
+        SpecStatus::Panicked(panic) => (
+            JsonStatus::Failed,
+            None,
+            Some(JsonPanic {
+                message: panic.message.clone(),
+                location: panic.location.clone(),
+            }),
+            None,
+        ),
+        SpecStatus::TimedOut(elapsed) => (JsonStatus::Failed, None, None, Some(**elapsed)),
+        SpecStatus::Flaky => (JsonStatus::Flaky, None, None, None),
+        SpecStatus::Skipped => (JsonStatus::Skipped, None, None, None),
+        SpecStatus::Filtered => (JsonStatus::Filtered, None, None, None),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonTest {
+    pub name: String,
+    /// The names of the suites that the spec is nested in and its own name, separated by `::`.
+    pub full_name: String,
+    pub status: JsonStatus,
+    pub only: bool,
+    pub duration: Duration,
+    pub failure: Option<JsonFailure>,
+    /// Set when the spec panicked. Its status is `failed`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub panic: Option<JsonPanic>,
+    /// Set when the spec timed out: how long it ran before it was given up on. Its status is
+    /// `failed`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timed_out: Option<Duration>,
+    /// Every run of the spec, in order, if it was retried.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attempts: Vec<JsonAttempt>,
 }
 
 impl JsonTest {
-    fn from_test(info: SpecInfo) -> JsonTest {
+    fn from_test(path: &str, info: SpecInfo) -> JsonTest {
+        let (status, failure, panic, timed_out) = outcome(&info.status);
+
+        let attempts = if info.attempts.len() > 1 {
+            info.attempts.iter().map(JsonAttempt::from).collect()
+        } else {
+            vec![]
+        };
+
         JsonTest {
-            name: info.name.clone(),
-            full_name: format!("{} {}", info.suite_name, info.name),
-            pass: match info.status {
-                SpecStatus::Success => true,
-                SpecStatus::Failure(_) => false,
-                SpecStatus::Skipped => false,
-            },
-            error_message: match info.status {
-                SpecStatus::Success => None,
-                SpecStatus::Failure(err) => Some(format!("{:?}", err)),
-                SpecStatus::Skipped => None,
-            },
+            full_name: spec_path(path, &info.name),
+            name: info.name,
+            status,
+            only: info.only,
             duration: *info.duration,
+            failure,
+            panic,
+            timed_out,
+            attempts,
+        }
+    }
+}
+
+/// One run of a spec that was retried. The fields mean the same as they do in `JsonTest`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonAttempt {
+    pub status: JsonStatus,
+    pub duration: Duration,
+    pub failure: Option<JsonFailure>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub panic: Option<JsonPanic>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timed_out: Option<Duration>,
+}
+
+impl From<&SpecAttempt> for JsonAttempt {
+    fn from(attempt: &SpecAttempt) -> JsonAttempt {
+        let (status, failure, panic, timed_out) = outcome(attempt.status());
+
+        JsonAttempt {
+            status,
+            duration: **attempt.duration(),
+            failure,
+            panic,
+            timed_out,
+        }
+    }
+}
+
+/// A panic in a spec, and where it happened (`file:line:column`) if that's known.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonPanic {
+    pub message: String,
+    pub location: Option<String>,
+}
+
+/// A value in a failure, as the assertion described it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonValue {
+    pub description: String,
+    pub value: String,
+    pub notes: Vec<String>,
+}
+
+impl From<&Described<String>> for JsonValue {
+    fn from(described: &Described<String>) -> JsonValue {
+        JsonValue {
+            description: described.description().to_string(),
+            value: described.value().clone(),
+            notes: described.notes().to_vec(),
+        }
+    }
+}
+
+/// An assertion that failed. `rendered` is the failure as the spec reporter prints it, without
+/// colors. `hook` is set when the assertion failed in a hook rather than in the spec itself.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonFailure {
+    pub message: String,
+    pub actual: JsonValue,
+    pub expected: Option<JsonValue>,
+    pub causes: Vec<JsonFailure>,
+    pub rendered: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hook: Option<HookKind>,
+}
+
+impl From<&Failure> for JsonFailure {
+    fn from(failure: &Failure) -> JsonFailure {
+        JsonFailure {
+            message: failure.message().to_string(),
+            actual: failure.actual().into(),
+            expected: failure.expected().map(JsonValue::from),
+            causes: failure.causes().iter().map(JsonFailure::from).collect(),
+            rendered: failure.to_string(),
+            hook: None,
+        }
+    }
+}
+
+impl From<&HookFailure> for JsonFailure {
+    fn from(failure: &HookFailure) -> JsonFailure {
+        JsonFailure {
+            hook: Some(failure.hook),
+            ..JsonFailure::from(&failure.failure)
         }
     }
 }
@@ -63,41 +277,19 @@ impl JsonReporter {
     }
 }
 
+/// Builds the suite tree as suites start and end. The last suite in `suites` is the one that's
+/// running, and the ones before it are the suites that it's nested in.
 #[derive(Debug, Clone)]
 pub struct StartedJsonReporter {
     pretty: bool,
-    suite_name: Option<String>,
-    tests: Vec<SpecInfo>,
+    suites: Vec<JsonSuite>,
 }
 
 impl StartedJsonReporter {
-    fn into_json(self, duration: DurationWithPrecision) -> JsonOutput {
-        let StartedJsonReporter { tests, .. } = self;
-
-        let mut passing = 0;
-        let mut failing = 0;
-        let mut ignored = 0;
-        let mut json_tests: Vec<JsonTest> = vec![];
-
-        for test in tests.into_iter() {
-            match test.status {
-                SpecStatus::Success => passing += 1,
-                SpecStatus::Failure(_) => failing += 1,
-                SpecStatus::Skipped => ignored += 1,
-            }
-
-            json_tests.push(JsonTest::from_test(test));
-        }
-
-        JsonOutput {
-            name: self.suite_name.unwrap(),
-            passing,
-            failing,
-            ignored,
-            child_suites: vec![],
-            child_tests: json_tests,
-            duration: *duration,
-        }
+    fn path(&self) -> String {
+        self.suites
+            .iter()
+            .fold(String::new(), |path, suite| spec_path(&path, &suite.name))
     }
 }
 
@@ -107,35 +299,62 @@ impl Reporter for JsonReporter {
     fn start(&self) -> Self::Started {
         StartedJsonReporter {
             pretty: self.pretty,
-            suite_name: None,
-            tests: vec![],
+            suites: vec![],
         }
     }
 }
 
 impl StartedReporter for StartedJsonReporter {
     fn start_suite(&mut self, _out: &mut SuiteOutput, desc: &SuiteDetails) -> ReportResult {
-        self.suite_name = Some(desc.name().clone());
+        self.suites
+            .push(JsonSuite::new(desc.name().clone(), desc.skip));
         Ok(())
     }
 
     fn end_suite(&mut self, out: &mut SuiteOutput, result: &SuiteFinished) -> ReportResult {
-        let pretty = self.pretty;
+        let mut suite = self
+            .suites
+            .pop()
+            .expect("a suite ended that the JSON reporter didn't see start");
+
+        suite.duration = *result.duration();
+        suite.hook_failures = result
+            .hook_failures()
+            .iter()
+            .map(JsonFailure::from)
+            .collect();
+        suite.count();
+
+        if let Some(parent) = self.suites.last_mut() {
+            parent.child_suites.push(suite);
+            return Ok(());
+        }
+
+        let report = JsonReport {
+            version: JSON_SCHEMA_VERSION,
+            suite,
+        };
 
-        let json = self.clone().into_json(result.duration());
-        let string = if pretty {
-            serde_json::to_string_pretty(&json)?
+        let string = if self.pretty {
+            serde_json::to_string_pretty(&report)?
         } else {
-            serde_json::to_string(&json)?
+            serde_json::to_string(&report)?
         };
 
-        write!(out, "{}", string)?;
+        out.raw(string)?;
+        out.raw("\n")?;
 
         Ok(())
     }
 
     fn end_test(&mut self, _out: &mut SuiteOutput, desc: SpecInfo) -> ReportResult {
-        self.tests.push(desc);
+        let test = JsonTest::from_test(&self.path(), desc);
+
+        self.suites
+            .last_mut()
+            .expect("a spec ended outside of a suite")
+            .child_tests
+            .push(test);
 
         Ok(())
     }
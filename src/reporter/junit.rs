@@ -0,0 +1,420 @@
+use std::{fmt::Write, fs, path::PathBuf, time::Duration};
+
+use veritas::Failure;
+
+use crate::{
+    spec_result::{HookKind, SpecInfo, SpecPanic, SpecStatus},
+    suite::{
+        described::SuiteDetails, finalized::spec_path, DurationPrecision, DurationWithPrecision,
+    },
+    suite_result::SuiteFinished,
+};
+
+use super::{output::SuiteOutput, ReportResult, Reporter, StartedReporter};
+
+/// Reports suites as JUnit XML, which most CI systems can read.
+///
+/// Nested suites become nested `<testsuite>` elements. A spec that panicked or timed out has an
+/// `<error>` rather than a `<failure>`, as an unexpected exception would in JUnit. If the top-level
+/// suite was exported with `SuiteExt::export_to`, the XML is written to that file instead of the
+/// reporter's output. A flaky spec passes, with a `<flakyFailure>` or `<flakyError>` for each
+/// attempt that failed, as Maven Surefire reports them. Specs that a `Filter` left out aren't
+/// listed.
+#[derive(Debug, Clone)]
+pub struct JunitReporter;
+
+#[derive(Debug, Clone)]
+struct JunitCase {
+    name: String,
+    classname: String,
+    time: String,
+    failure: Option<Failure>,
+    /// The hook that failed, if it wasn't the spec itself.
+    hook: Option<HookKind>,
+    panic: Option<SpecPanic>,
+    /// How long the spec ran before it timed out.
+    timed_out: Option<DurationWithPrecision>,
+    /// The attempts that failed before a flaky spec passed.
+    flaky: Vec<SpecStatus>,
+    skipped: bool,
+}
+
+#[derive(Debug, Clone)]
+struct JunitSuite {
+    name: String,
+    time: String,
+    tests: usize,
+    failures: usize,
+    errors: usize,
+    skipped: usize,
+    cases: Vec<JunitCase>,
+    suites: Vec<JunitSuite>,
+}
+
+impl JunitSuite {
+    fn new(name: impl Into<String>) -> JunitSuite {
+        JunitSuite {
+            name: name.into(),
+            time: String::new(),
+            tests: 0,
+            failures: 0,
+            errors: 0,
+            skipped: 0,
+            cases: vec![],
+            suites: vec![],
+        }
+    }
+
+    /// Count the specs in this suite and the suites nested in it.
+    fn count(&mut self) {
+        self.tests = self.cases.len();
+        self.failures = self.cases.iter().filter(|c| c.failure.is_some()).count();
+        self.errors = self
+            .cases
+            .iter()
+            .filter(|c| c.panic.is_some() || c.timed_out.is_some())
+            .count();
+        self.skipped = self.cases.iter().filter(|c| c.skipped).count();
+
+        for suite in &self.suites {
+            self.tests += suite.tests;
+            self.failures += suite.failures;
+            self.errors += suite.errors;
+            self.skipped += suite.skipped;
+        }
+    }
+
+    fn write_xml(&self, xml: &mut String, depth: usize) -> std::fmt::Result {
+        let indent = "  ".repeat(depth);
+
+        writeln!(
+            xml,
+            r#"{}<testsuite name="{}" tests="{}" failures="{}" errors="{}" skipped="{}" time="{}">"#,
+            indent,
+            escape(&self.name),
+            self.tests,
+            self.failures,
+            self.errors,
+            self.skipped,
+            self.time
+        )?;
+
+        for case in &self.cases {
+            write!(
+                xml,
+                r#"{}  <testcase name="{}" classname="{}" time="{}""#,
+                indent,
+                escape(&case.name),
+                escape(&case.classname),
+                case.time
+            )?;
+
+            if let Some(failure) = &case.failure {
+                let (message, kind) = match case.hook {
+                    Some(hook) => (format!("\"{}\" hook: {}", hook, failure.message()), "hook"),
+                    None => (failure.message().to_string(), "assertion"),
+                };
+
+                writeln!(xml, ">")?;
+                writeln!(
+                    xml,
+                    r#"{}    <failure message="{}" type="{}">{}</failure>"#,
+                    indent,
+                    escape(&message),
+                    kind,
+                    escape(&failure.to_string())
+                )?;
+                writeln!(xml, "{}  </testcase>", indent)?;
+            } else if let Some(panic) = &case.panic {
+                writeln!(xml, ">")?;
+                writeln!(
+                    xml,
+                    r#"{}    <error message="{}" type="panic">{}</error>"#,
+                    indent,
+                    escape(&panic.message),
+                    escape(&panic.to_string())
+                )?;
+                writeln!(xml, "{}  </testcase>", indent)?;
+            } else if let Some(elapsed) = &case.timed_out {
+                writeln!(xml, ">")?;
+                writeln!(
+                    xml,
+                    r#"{}    <error message="timed out after {}" type="timeout"/>"#,
+                    indent, elapsed
+                )?;
+                writeln!(xml, "{}  </testcase>", indent)?;
+            } else if !case.flaky.is_empty() {
+                writeln!(xml, ">")?;
+
+                // Only failures and panics are retried
+                for status in &case.flaky {
+                    match status {
+                        SpecStatus::Failure(failure) => writeln!(
+                            xml,
+                            r#"{}    <flakyFailure message="{}" type="assertion">{}</flakyFailure>"#,
+                            indent,
+                            escape(failure.message()),
+                            escape(&failure.to_string())
+                        )?,
+                        SpecStatus::Panicked(panic) => writeln!(
+                            xml,
+                            r#"{}    <flakyError message="{}" type="panic">{}</flakyError>"#,
+                            indent,
+                            escape(&panic.message),
+                            escape(&panic.to_string())
+                        )?,
+                        _ => {}
+                    }
+                }
+
+                writeln!(xml, "{}  </testcase>", indent)?;
+            } else if case.skipped {
+                writeln!(xml, ">")?;
+                writeln!(xml, "{}    <skipped/>", indent)?;
+                writeln!(xml, "{}  </testcase>", indent)?;
+            } else {
+                writeln!(xml, "/>")?;
+            }
+        }
+
+        for suite in &self.suites {
+            suite.write_xml(xml, depth + 1)?;
+        }
+
+        writeln!(xml, "{}</testsuite>", indent)
+    }
+
+    fn to_xml(&self) -> Result<String, std::fmt::Error> {
+        let mut xml = String::new();
+
+        writeln!(xml, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            xml,
+            r#"<testsuites name="{}" tests="{}" failures="{}" errors="{}" skipped="{}" time="{}">"#,
+            escape(&self.name),
+            self.tests,
+            self.failures,
+            self.errors,
+            self.skipped,
+            self.time
+        )?;
+        self.write_xml(&mut xml, 1)?;
+        writeln!(xml, "</testsuites>")?;
+
+        Ok(xml)
+    }
+}
+
+/// JUnit times are in seconds. They keep as many decimal places as the suite's precision
+/// measures.
+fn seconds(duration: DurationWithPrecision) -> String {
+    let decimals = match duration.precision() {
+        DurationPrecision::Seconds => 0,
+        DurationPrecision::Millis => 3,
+        DurationPrecision::Micros => 6,
+        DurationPrecision::Nanos => 9,
+    };
+
+    format!("{:.*}", decimals, duration.as_secs_f64())
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters other than whitespace aren't allowed in XML 1.0
+            c if c.is_control() && !matches!(c, '\n' | '\r' | '\t') => {}
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// Builds the suite tree as suites start and end, like the JSON reporter.
+#[derive(Debug, Clone)]
+pub struct StartedJunitReporter {
+    suites: Vec<JunitSuite>,
+    export: Option<PathBuf>,
+}
+
+impl StartedJunitReporter {
+    fn path(&self) -> String {
+        self.suites
+            .iter()
+            .fold(String::new(), |path, suite| spec_path(&path, &suite.name))
+    }
+}
+
+impl Reporter for JunitReporter {
+    type Started = StartedJunitReporter;
+
+    fn start(&self) -> Self::Started {
+        StartedJunitReporter {
+            suites: vec![],
+            export: None,
+        }
+    }
+}
+
+impl StartedReporter for StartedJunitReporter {
+    fn start_suite(&mut self, _out: &mut SuiteOutput, desc: &SuiteDetails) -> ReportResult {
+        if self.suites.is_empty() {
+            self.export = desc.export().clone();
+        }
+
+        self.suites.push(JunitSuite::new(desc.name().clone()));
+        Ok(())
+    }
+
+    fn end_suite(&mut self, out: &mut SuiteOutput, result: &SuiteFinished) -> ReportResult {
+        let mut suite = self
+            .suites
+            .pop()
+            .expect("a suite ended that the JUnit reporter didn't see start");
+
+        suite.time = seconds(result.duration());
+
+        // A failed `after_all` hook doesn't belong to any spec, so it gets a case of its own
+        for failure in result.hook_failures() {
+            suite.cases.push(JunitCase {
+                name: format!("\"{}\" hook", failure.hook),
+                classname: spec_path(&self.path(), &suite.name),
+                time: seconds(DurationWithPrecision::new(
+                    Duration::ZERO,
+                    result.duration().precision(),
+                )),
+                failure: Some(failure.failure.clone()),
+                hook: Some(failure.hook),
+                panic: None,
+                timed_out: None,
+                flaky: vec![],
+                skipped: false,
+            });
+        }
+
+        suite.count();
+
+        if let Some(parent) = self.suites.last_mut() {
+            parent.suites.push(suite);
+            return Ok(());
+        }
+
+        let xml = suite.to_xml()?;
+
+        match self.export.take() {
+            Some(path) => {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+
+                fs::write(path, xml)?;
+            }
+            None => out.raw(xml)?,
+        }
+
+        Ok(())
+    }
+
+    fn end_test(&mut self, _out: &mut SuiteOutput, info: SpecInfo) -> ReportResult {
+        // JUnit has nothing for specs that weren't selected, so they're left out, as if they
+        // were filtered out before the run
+        if let SpecStatus::Filtered = info.status {
+            return Ok(());
+        }
+
+        let (mut failure, mut hook, mut panic, mut skipped) = (None, None, None, false);
+        let mut timed_out = None;
+        let mut flaky = vec![];
+
+        match &info.status {
+            SpecStatus::Success => {}
+            SpecStatus::Failure(spec_failure) => failure = Some(spec_failure.clone()),
+            SpecStatus::HookFailure(hook_failure) => {
+                failure = Some(hook_failure.failure.clone());
+                hook = Some(hook_failure.hook);
+            }
+            SpecStatus::Panicked(spec_panic) => panic = Some(spec_panic.clone()),
+            SpecStatus::TimedOut(elapsed) => timed_out = Some(*elapsed),
+            SpecStatus::Flaky => {
+                flaky = info
+                    .attempts
+                    .iter()
+                    .map(|attempt| attempt.status().clone())
+                    .filter(SpecStatus::is_failure)
+                    .collect();
+            }
+            SpecStatus::Skipped => skipped = true,
+            SpecStatus::Filtered => {}
+        }
+
+        let case = JunitCase {
+            classname: self.path(),
+            time: seconds(info.duration),
+            name: info.name,
+            failure,
+            hook,
+            panic,
+            timed_out,
+            flaky,
+            skipped,
+        };
+
+        self.suites
+            .last_mut()
+            .expect("a spec ended outside of a suite")
+            .cases
+            .push(case);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn escape_xml() {
+        assert_eq!(
+            escape(r#"<a href="x">Tom & 'Jerry'</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; &apos;Jerry&apos;&lt;/a&gt;"
+        );
+        assert_eq!(escape("bell\u{7}\nline"), "bell\nline");
+    }
+
+    #[test]
+    fn seconds_by_precision() {
+        let duration = Duration::from_micros(1_234_567);
+
+        assert_eq!(
+            seconds(DurationWithPrecision::new(
+                duration,
+                DurationPrecision::Millis
+            )),
+            "1.235"
+        );
+        assert_eq!(
+            seconds(DurationWithPrecision::new(
+                duration,
+                DurationPrecision::Micros
+            )),
+            "1.234567"
+        );
+        assert_eq!(
+            seconds(DurationWithPrecision::new(
+                duration,
+                DurationPrecision::Seconds
+            )),
+            "1"
+        );
+    }
+}
@@ -0,0 +1,252 @@
+use serde::Serialize;
+
+use crate::{
+    spec_result::{ReporterSpecInfo, SpecInfo, SpecStatus},
+    suite::{described::SuiteDetails, finalized::spec_path, FullSuiteInfo, StartInfo},
+    suite_result::SuiteFinished,
+};
+
+use super::{output::SuiteOutput, ReportResult, Reporter, StartedReporter};
+
+/// Reports the newline-delimited JSON events that libtest emits for
+/// `cargo test -- -Z unstable-options --format json`, so that tools which read them can show
+/// laboratory's results.
+///
+/// Specs are named by their full path, like `Library::add_one()::return 1`. A failure's rendered
+/// message goes in the event's `stdout`, where libtest puts a failing test's output.
+#[derive(Debug, Clone)]
+pub struct LibtestJsonReporter;
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Event<'a> {
+    Suite(SuiteEvent),
+    Test(TestEvent<'a>),
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "lowercase")]
+enum SuiteEvent {
+    Started {
+        test_count: usize,
+    },
+    Ok {
+        passed: usize,
+        failed: usize,
+        ignored: usize,
+        measured: usize,
+        filtered_out: usize,
+        exec_time: f64,
+    },
+    Failed {
+        passed: usize,
+        failed: usize,
+        ignored: usize,
+        measured: usize,
+        filtered_out: usize,
+        exec_time: f64,
+    },
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "lowercase")]
+enum TestEvent<'a> {
+    Started {
+        name: &'a str,
+    },
+    Ok {
+        name: &'a str,
+        exec_time: f64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        stdout: Option<String>,
+    },
+    Failed {
+        name: &'a str,
+        exec_time: f64,
+        stdout: String,
+    },
+    Ignored {
+        name: &'a str,
+    },
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct StartedLibtestJsonReporter {
+    suites: Vec<String>,
+    passed: usize,
+    failed: usize,
+    ignored: usize,
+    filtered_out: usize,
+}
+
+impl Reporter for LibtestJsonReporter {
+    type Started = StartedLibtestJsonReporter;
+
+    fn start(&self) -> Self::Started {
+        StartedLibtestJsonReporter::default()
+    }
+}
+
+impl StartedLibtestJsonReporter {
+    fn name(&self, spec: &str) -> String {
+        let path = self
+            .suites
+            .iter()
+            .fold(String::new(), |path, suite| spec_path(&path, suite));
+
+        spec_path(&path, spec)
+    }
+
+    fn emit(&self, out: &mut SuiteOutput, event: Event) -> ReportResult {
+        out.raw(serde_json::to_string(&event)?)?;
+        out.raw("\n")?;
+        Ok(())
+    }
+}
+
+impl StartedReporter for StartedLibtestJsonReporter {
+    fn start_run(&mut self, out: &mut SuiteOutput, info: &StartInfo) -> ReportResult {
+        self.filtered_out = info.filtered_out;
+
+        self.emit(
+            out,
+            Event::Suite(SuiteEvent::Started {
+                test_count: info.test_count,
+            }),
+        )
+    }
+
+    fn start_suite(&mut self, _out: &mut SuiteOutput, desc: &SuiteDetails) -> ReportResult {
+        self.suites.push(desc.name().clone());
+        Ok(())
+    }
+
+    /// libtest has no events for hooks, so a failed `after_all` hook is reported as a failed test
+    /// named after the hook.
+    fn end_suite(&mut self, out: &mut SuiteOutput, result: &SuiteFinished) -> ReportResult {
+        for failure in result.hook_failures() {
+            let name = self.name(&format!("\"{}\" hook", failure.hook));
+
+            self.failed += 1;
+            self.emit(out, Event::Test(TestEvent::Started { name: &name }))?;
+            self.emit(
+                out,
+                Event::Test(TestEvent::Failed {
+                    name: &name,
+                    exec_time: 0.0,
+                    stdout: failure.failure.to_string(),
+                }),
+            )?;
+        }
+
+        self.suites.pop();
+        Ok(())
+    }
+
+    /// Like libtest, specs that were filtered out have no events, and are only counted in
+    /// `filtered_out`.
+    fn start_test(&mut self, out: &mut SuiteOutput, desc: &ReporterSpecInfo) -> ReportResult {
+        if desc.filtered {
+            return Ok(());
+        }
+
+        let name = self.name(&desc.name);
+
+        self.emit(out, Event::Test(TestEvent::Started { name: &name }))
+    }
+
+    fn end_test(&mut self, out: &mut SuiteOutput, info: SpecInfo) -> ReportResult {
+        let name = self.name(&info.name);
+        let exec_time = info.duration.as_secs_f64();
+
+        let event = match &info.status {
+            SpecStatus::Success => {
+                self.passed += 1;
+                TestEvent::Ok {
+                    name: &name,
+                    exec_time,
+                    stdout: None,
+                }
+            }
+            SpecStatus::Flaky => {
+                self.passed += 1;
+                TestEvent::Ok {
+                    name: &name,
+                    exec_time,
+                    stdout: Some(format!(
+                        "test {} is flaky: it passed on attempt {}\n",
+                        name,
+                        info.attempts.len()
+                    )),
+                }
+            }
+            SpecStatus::Failure(failure) => {
+                self.failed += 1;
+                TestEvent::Failed {
+                    name: &name,
+                    exec_time,
+                    stdout: failure.to_string(),
+                }
+            }
+            SpecStatus::HookFailure(failure) => {
+                self.failed += 1;
+                TestEvent::Failed {
+                    name: &name,
+                    exec_time,
+                    stdout: format!("\"{}\" hook failed\n{}", failure.hook, failure.failure),
+                }
+            }
+            SpecStatus::Panicked(panic) => {
+                self.failed += 1;
+                TestEvent::Failed {
+                    name: &name,
+                    exec_time,
+                    stdout: format!("thread '{}' {}\n", name, panic),
+                }
+            }
+            SpecStatus::TimedOut(elapsed) => {
+                self.failed += 1;
+                TestEvent::Failed {
+                    name: &name,
+                    exec_time,
+                    stdout: format!("test {} timed out after {}\n", name, elapsed),
+                }
+            }
+            SpecStatus::Skipped => {
+                self.ignored += 1;
+                TestEvent::Ignored { name: &name }
+            }
+            SpecStatus::Filtered => return Ok(()),
+        };
+
+        self.emit(out, Event::Test(event))
+    }
+
+    fn finish(&mut self, out: &mut SuiteOutput, info: FullSuiteInfo) -> ReportResult {
+        let (passed, failed, ignored) = (self.passed, self.failed, self.ignored);
+        let filtered_out = self.filtered_out;
+        let exec_time = info.duration.as_secs_f64();
+
+        let event = if failed == 0 {
+            SuiteEvent::Ok {
+                passed,
+                failed,
+                ignored,
+                measured: 0,
+                filtered_out,
+                exec_time,
+            }
+        } else {
+            SuiteEvent::Failed {
+                passed,
+                failed,
+                ignored,
+                measured: 0,
+                filtered_out,
+                exec_time,
+            }
+        };
+
+        self.emit(out, Event::Suite(event))
+    }
+}
@@ -1,24 +1,21 @@
-#[macro_use]
 pub mod output;
 
 pub mod json;
+pub mod junit;
+pub mod libtest;
 pub mod spec;
+pub mod tap;
 
 use std::fmt::Debug;
 
 use crate::{
     spec_result::{ReporterSpecInfo, SpecInfo},
-    suite::FullSuiteInfo,
+    suite::{described::SuiteDetails, FullSuiteInfo, StartInfo},
     suite_result::SuiteFinished,
-    SuiteDetails,
 };
 
 use self::output::SuiteOutput;
 
-pub trait ToResult {
-    fn to_result(&self) -> Result<(), String>;
-}
-
 pub trait Reporter: Clone {
     type Started: StartedReporter;
 
@@ -44,6 +41,10 @@ where
 pub type ReportResult<T = ()> = Result<T, ReportError>;
 
 pub trait StartedReporter: Debug {
+    fn start_run(&mut self, _out: &mut SuiteOutput, _info: &StartInfo) -> ReportResult {
+        Ok(())
+    }
+
     fn start_suite(&mut self, _out: &mut SuiteOutput, _desc: &SuiteDetails) -> ReportResult {
         Ok(())
     }
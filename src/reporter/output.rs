@@ -1,123 +1,204 @@
 use std::{
     fmt::Display,
-    io::{stderr, stdout, Write},
+    io::{self, stderr, stdout, Write},
+    sync::Arc,
 };
 
-use laboratory_expectations::{emit::write::StyledEmitterTrait, emitter, EmitWriter, Style};
+use spectrum::{
+    compose::StyledArena, Doc, EmitAuto, EmitBackendTrait, EmitPlain, Intern, RenderConfig,
+    RenderContext,
+};
 
 use crate::suite::DurationWithPrecision;
 
 use super::ReportResult;
 
-macro_rules! out {
-    ($out:expr, $($token:tt)*) => {
-        write!($out, $($token)*)?;
-    };
+/// The backend that reporter output is emitted with.
+pub type SharedBackend = Arc<dyn EmitBackendTrait + Send + Sync>;
+
+const DEFAULT_WIDTH: usize = 100;
+
+/// Where reporter output ends up: a stream that the output owns, or one that it borrows.
+enum Target<'a> {
+    Owned(Box<dyn Write + 'a>),
+    Borrowed(&'a mut dyn Write),
 }
 
-macro_rules! outln {
-    ($out:expr) => {{
-        use laboratory_expectations::StyledEmitterTrait;
-        laboratory_expectations::emitln!(&mut $out.boxed())?;
-    }};
+impl<'a> Target<'a> {
+    fn reborrow(&mut self) -> Target<'_> {
+        match self {
+            Target::Owned(write) => Target::Borrowed(&mut **write),
+            Target::Borrowed(write) => Target::Borrowed(&mut **write),
+        }
+    }
+}
 
-    ($out:expr, $($tokens:tt)*) => {{
-        use laboratory_expectations::StyledEmitterTrait;
+impl Write for Target<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Target::Owned(write) => write.write(buf),
+            Target::Borrowed(write) => write.write(buf),
+        }
+    }
 
-        $out.start_line()?;
-        laboratory_expectations::emitln!(&mut $out.boxed(), $($tokens)*)?;
-    }};
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Target::Owned(write) => write.flush(),
+            Target::Borrowed(write) => write.flush(),
+        }
+    }
+}
+
+/// Adapts a `std::fmt::Write` to `std::io::Write`. Reporters only ever write UTF-8.
+struct FmtWrite<'a>(&'a mut dyn std::fmt::Write);
 
-    (+1 => $out:expr, $($tokens:tt)*) => {{
-        use laboratory_expectations::StyledEmitterTrait;
+impl Write for FmtWrite<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0
+            .write_str(&String::from_utf8_lossy(buf))
+            .map_err(|_| io::Error::other("formatter error"))?;
 
-        $out.indent();
-        $out.start_line()?;
-        laboratory_expectations::emitln!(&mut $out.boxed(), $($tokens)*)?;
-        $out.outdent();
-    }}
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
 }
 
-#[derive(Debug)]
+/// The stream that reporters write to, along with the backend that docs are emitted with and
+/// the width that they're laid out at.
 pub struct ReporterOutput<'a> {
-    write: EmitWriter<'a>,
+    write: Target<'a>,
+    backend: SharedBackend,
+    width: usize,
     indent: usize,
 }
 
+impl std::fmt::Debug for ReporterOutput<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReporterOutput")
+            .field("width", &self.width)
+            .field("indent", &self.indent)
+            .finish()
+    }
+}
+
 impl<'a> ReporterOutput<'a> {
+    fn new(write: Target<'a>, backend: SharedBackend) -> ReporterOutput<'a> {
+        ReporterOutput {
+            write,
+            backend,
+            width: DEFAULT_WIDTH,
+            indent: 0,
+        }
+    }
+
     pub fn child(&mut self) -> ReporterOutput<'_> {
         ReporterOutput {
-            write: self.write.child(),
+            write: self.write.reborrow(),
+            backend: self.backend.clone(),
+            width: self.width,
             indent: self.indent,
         }
     }
 
     pub fn write(write: &'a mut dyn std::fmt::Write) -> ReporterOutput<'a> {
-        ReporterOutput {
-            write: EmitWriter::borrowed_fmt(write),
-            indent: 0,
-        }
+        ReporterOutput::new(
+            Target::Owned(Box::new(FmtWrite(write))),
+            Arc::new(EmitPlain),
+        )
     }
 
     pub fn null() -> ReporterOutput<'static> {
-        ReporterOutput {
-            write: EmitWriter::owned_io(vec![]),
-            indent: 0,
-        }
+        ReporterOutput::new(Target::Owned(Box::new(io::sink())), Arc::new(EmitPlain))
     }
 
-    pub fn buffer(s: &'a mut dyn std::io::Write) -> ReporterOutput<'a> {
-        ReporterOutput {
-            write: EmitWriter::borrowed_io(s as &mut dyn std::io::Write),
-            indent: 0,
-        }
+    pub fn buffer(write: &'a mut dyn Write) -> ReporterOutput<'a> {
+        ReporterOutput::new(Target::Borrowed(write), Arc::new(EmitPlain))
     }
 
     pub fn stdout() -> ReporterOutput<'static> {
-        ReporterOutput {
-            write: EmitWriter::owned_io(stdout()),
-            indent: 0,
-        }
+        ReporterOutput::new(
+            Target::Owned(Box::new(stdout())),
+            Arc::new(EmitAuto::stdout()),
+        )
+        .width(terminal_width(console::Term::stdout()))
     }
 
     pub fn stderr() -> ReporterOutput<'static> {
-        ReporterOutput {
-            write: EmitWriter::owned_io(stderr()),
-            indent: 0,
-        }
+        ReporterOutput::new(
+            Target::Owned(Box::new(stderr())),
+            Arc::new(EmitAuto::stderr()),
+        )
+        .width(terminal_width(console::Term::stderr()))
+    }
+
+    /// Emit docs with `backend` instead of the default for this stream.
+    pub fn backend(mut self, backend: impl EmitBackendTrait + Send + Sync) -> ReporterOutput<'a> {
+        self.backend = Arc::new(backend);
+        self
+    }
+
+    /// The width that docs are laid out at.
+    pub fn width(mut self, width: usize) -> ReporterOutput<'a> {
+        self.width = width;
+        self
     }
 
     pub fn for_suite(&mut self, count: usize) -> SuiteOutput<'_> {
         SuiteOutput {
-            write: self.write.child(),
+            write: self.write.reborrow(),
+            backend: self.backend.clone(),
+            width: self.width,
             indent: self.indent,
-            count_size: format!("{}", count).len(),
             count,
         }
     }
 }
 
-#[derive(Debug)]
+fn terminal_width(term: console::Term) -> usize {
+    term.size_checked()
+        .map(|(_, columns)| columns as usize)
+        .unwrap_or(DEFAULT_WIDTH)
+}
+
+/// The output of a single suite. Lines are indented by the suite's nesting, and docs that span
+/// several lines keep that indentation on every line.
 pub struct SuiteOutput<'a> {
-    write: EmitWriter<'a>,
+    write: Target<'a>,
+    backend: SharedBackend,
+    width: usize,
     indent: usize,
-    count_size: usize,
     count: usize,
 }
 
+impl std::fmt::Debug for SuiteOutput<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SuiteOutput")
+            .field("width", &self.width)
+            .field("indent", &self.indent)
+            .field("count", &self.count)
+            .finish()
+    }
+}
+
 impl<'a> SuiteOutput<'a> {
     pub fn child(&mut self) -> SuiteOutput<'_> {
         SuiteOutput {
-            write: self.write.child(),
+            write: self.write.reborrow(),
+            backend: self.backend.clone(),
+            width: self.width,
             indent: self.indent,
-            count_size: self.count_size,
             count: self.count,
         }
     }
 
     pub fn for_nested(&mut self) -> ReporterOutput<'_> {
         ReporterOutput {
-            write: self.write.child(),
+            write: self.write.reborrow(),
+            backend: self.backend.clone(),
+            width: self.width,
             indent: self.indent,
         }
     }
@@ -126,20 +207,56 @@ impl<'a> SuiteOutput<'a> {
         Ok(writeln!(self.write)?)
     }
 
-    pub fn next_line(&mut self) -> ReportResult {
-        writeln!(self.write)?;
-        self.start_line()
+    /// The indentation at the start of each line: a bit of indent no matter what, plus two
+    /// columns per level of nesting.
+    fn prefix(&self) -> String {
+        "  ".repeat(self.indent + 1)
     }
 
-    pub fn start_line(&mut self) -> ReportResult {
-        // start with a bit of indent no matter what
-        let mut indent = "  ".to_string();
-
-        for _ in 0..self.indent {
-            indent.push_str("  ");
+    /// Emit `doc` on a line of its own.
+    pub fn line(&mut self, doc: impl Doc) -> ReportResult {
+        let prefix = self.prefix();
+        let rendered = self.render(&doc, self.width.saturating_sub(prefix.len()))?;
+
+        for line in rendered.split('\n') {
+            if line.is_empty() {
+                writeln!(self.write)?;
+            } else {
+                writeln!(self.write, "{}{}", prefix, line)?;
+            }
         }
 
-        Ok(write!(self.write, "{}", indent)?)
+        Ok(())
+    }
+
+    /// Emit `doc` on a line of its own, one level of nesting deeper than the current one.
+    pub fn nested_line(&mut self, doc: impl Doc) -> ReportResult {
+        self.indent();
+        let result = self.line(doc);
+        self.outdent();
+
+        result
+    }
+
+    /// Write `text` as it is, without indentation or styling. This is for reporters whose
+    /// output is meant to be read by other programs.
+    pub fn raw(&mut self, text: impl Display) -> ReportResult {
+        Ok(write!(self.write, "{}", text)?)
+    }
+
+    fn render(&self, doc: &dyn Doc, width: usize) -> ReportResult<String> {
+        let intern = Intern::new();
+        let mut buffer = vec![];
+
+        let mut context = RenderContext::new(StyledArena::new(&intern));
+        context.render(
+            doc,
+            self.backend.clone(),
+            &mut buffer,
+            RenderConfig::width(width),
+        )?;
+
+        Ok(String::from_utf8_lossy(&buffer).into_owned())
     }
 
     pub fn enumerate(&self, num: usize) -> String {
@@ -167,6 +284,8 @@ impl<'a> SuiteOutput<'a> {
     pub fn next(self) -> ReporterOutput<'a> {
         ReporterOutput {
             write: self.write,
+            backend: self.backend,
+            width: self.width,
             indent: self.indent,
         }
     }
@@ -1,13 +1,12 @@
-use std::fmt::{Display, Write};
-
-use laboratory_expectations::{block, MatchError};
+use spectrum::{list, plain, styled, Color, Style};
 
 use crate::{
-    spec_result::{SpecInfo, SpecStatus},
+    spec_result::{HookFailure, SpecInfo, SpecStatus},
     suite::{described::SuiteDetails, FullSuiteInfo},
+    suite_result::SuiteFinished,
 };
 
-use super::{output::SuiteOutput, ReportResult, Reporter, StartedReporter, ToResult};
+use super::{output::SuiteOutput, ReportResult, Reporter, StartedReporter};
 
 #[derive(Debug, Clone)]
 pub struct SpecReporter;
@@ -19,21 +18,24 @@ pub struct MinimalReporter;
 pub struct StartedSpecReporter {
     full: bool,
     passed: Vec<SpecInfo>,
-    failed: Vec<(SpecInfo, MatchError)>,
+    failed: Vec<SpecInfo>,
+    flaky: Vec<SpecInfo>,
     skipped: Vec<SpecInfo>,
+    filtered: usize,
+    hook_failures: Vec<HookFailure>,
 }
 
-pub struct FinishedSpecReporter {}
-
-impl Display for FinishedSpecReporter {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "FinishedSpecReporter")
-    }
-}
-
-impl ToResult for FinishedSpecReporter {
-    fn to_result(&self) -> Result<(), String> {
-        todo!()
+impl StartedSpecReporter {
+    fn new(full: bool) -> StartedSpecReporter {
+        StartedSpecReporter {
+            full,
+            passed: vec![],
+            failed: vec![],
+            flaky: vec![],
+            skipped: vec![],
+            filtered: 0,
+            hook_failures: vec![],
+        }
     }
 }
 
@@ -41,12 +43,7 @@ impl Reporter for MinimalReporter {
     type Started = StartedSpecReporter;
 
     fn start(&self) -> Self::Started {
-        StartedSpecReporter {
-            full: false,
-            passed: vec![],
-            failed: vec![],
-            skipped: vec![],
-        }
+        StartedSpecReporter::new(false)
     }
 }
 
@@ -54,12 +51,62 @@ impl Reporter for SpecReporter {
     type Started = StartedSpecReporter;
 
     fn start(&self) -> Self::Started {
-        StartedSpecReporter {
-            full: true,
-            passed: vec![],
-            failed: vec![],
-            skipped: vec![],
+        StartedSpecReporter::new(true)
+    }
+}
+
+fn dim() -> Style {
+    Style::default().attr(console::Attribute::Dim)
+}
+
+/// How a failed spec is listed. A spec that failed because of a hook is listed by the hook.
+fn failed_title(info: &SpecInfo) -> String {
+    match &info.status {
+        SpecStatus::HookFailure(failure) => {
+            format!("\"{}\" hook for \"should {}\"", failure.hook(), info.name)
         }
+        _ => format!("should {}", info.name),
+    }
+}
+
+/// Write why a spec, or one attempt of it, didn't pass.
+fn write_status(out: &mut SuiteOutput, status: &SpecStatus) -> ReportResult {
+    match status {
+        SpecStatus::Failure(failure) | SpecStatus::HookFailure(HookFailure { failure, .. }) => {
+            out.nested_line(failure.clone())?;
+        }
+        SpecStatus::Panicked(panic) => {
+            for line in panic.to_string().lines() {
+                out.nested_line(plain(line.to_string()))?;
+            }
+        }
+        SpecStatus::TimedOut(elapsed) => {
+            out.nested_line(plain(format!("timed out after {}", elapsed)))?;
+        }
+        SpecStatus::Success | SpecStatus::Flaky | SpecStatus::Skipped | SpecStatus::Filtered => {}
+    }
+
+    Ok(())
+}
+
+impl StartedSpecReporter {
+    /// Note the specs that were left out of the run, by focus or by a filter.
+    fn write_left_out(&self, out: &mut SuiteOutput, focused: bool) -> ReportResult {
+        if focused {
+            out.line(styled(
+                "⚠ focus is active: only specs and suites marked with only ran".to_string(),
+                Color::Yellow.into(),
+            ))?;
+        }
+
+        if self.filtered > 0 {
+            out.line(styled(
+                format!("{} tests filtered out", self.filtered),
+                dim(),
+            ))?;
+        }
+
+        Ok(())
     }
 }
 
@@ -67,50 +114,95 @@ impl StartedReporter for StartedSpecReporter {
     fn start_suite(&mut self, out: &mut SuiteOutput, desc: &SuiteDetails) -> ReportResult {
         if self.full {
             out.nest(desc.nesting);
-            outln!(out, block! { (desc.name()) });
+            out.line(plain(desc.name().clone()))?;
         }
 
         Ok(())
     }
 
     fn end_test(&mut self, out: &mut SuiteOutput, desc: SpecInfo) -> ReportResult {
-        match desc.clone() {
-            SpecInfo {
-                name,
-                duration,
-                status: SpecStatus::Success,
-                ..
-            } => {
+        match &desc.status {
+            SpecStatus::Success => {
                 if self.full {
-                    outln!(+1 => out, block! { " ✓  should " name " (" duration ")" });
+                    out.nested_line(list![
+                        styled(" ✓", Color::Green.into()),
+                        plain(format!("  should {} ", desc.name)),
+                        styled(format!("({})", desc.duration), dim())
+                    ])?;
                 }
 
                 self.passed.push(desc);
             }
-            SpecInfo {
-                name,
-                number,
-                duration,
-                status: SpecStatus::Failure(err),
-                ..
-            } => {
+            SpecStatus::Flaky => {
+                if self.full {
+                    out.nested_line(list![
+                        styled(" ✓", Color::Yellow.into()),
+                        styled(
+                            format!(
+                                "  should {} (flaky, passed on attempt {}) ",
+                                desc.name,
+                                desc.attempts.len()
+                            ),
+                            Color::Yellow.into()
+                        ),
+                        styled(format!("({})", desc.duration), dim())
+                    ])?;
+                }
+
+                self.flaky.push(desc);
+            }
+            SpecStatus::Failure(_)
+            | SpecStatus::HookFailure(_)
+            | SpecStatus::Panicked(_)
+            | SpecStatus::TimedOut(_) => {
                 if self.full {
-                    outln!(+1 => out, block! { (out.enumerate(number)) ") should " name "(" duration ")" })
+                    out.nested_line(list![
+                        styled(
+                            format!("{})", out.enumerate(desc.number)),
+                            Color::Red.into()
+                        ),
+                        styled(format!(" {} ", failed_title(&desc)), Color::Red.into()),
+                        styled(format!("({})", desc.duration), dim())
+                    ])?;
                 }
 
-                self.failed.push((desc, err));
+                self.failed.push(desc);
             }
-            SpecInfo {
-                name,
-                status: SpecStatus::Skipped,
-                ..
-            } => {
+            SpecStatus::Skipped => {
                 if self.full {
-                    outln!(+1 => out, block! { "    should " name });
+                    out.nested_line(styled(
+                        format!("    should {}", desc.name),
+                        Color::Cyan.into(),
+                    ))?;
                 }
 
                 self.skipped.push(desc);
             }
+            SpecStatus::Filtered => {
+                if self.full {
+                    out.nested_line(styled(
+                        format!("    should {} (filtered)", desc.name),
+                        dim(),
+                    ))?;
+                }
+
+                self.filtered += 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn end_suite(&mut self, out: &mut SuiteOutput, result: &SuiteFinished) -> ReportResult {
+        for failure in result.hook_failures() {
+            if self.full {
+                out.nested_line(styled(
+                    format!(" ✖  \"{}\" hook", failure.hook()),
+                    Color::Red.into(),
+                ))?;
+            }
+
+            self.hook_failures.push(failure.clone());
         }
 
         Ok(())
@@ -118,35 +210,89 @@ impl StartedReporter for StartedSpecReporter {
 
     fn finish(&mut self, out: &mut SuiteOutput, info: FullSuiteInfo) -> ReportResult {
         if self.full {
-            out!(out, "\n\n");
+            out.blank_line()?;
+            out.blank_line()?;
         }
 
         out.nest(0);
 
         let fail_count = self.failed.len();
-        let pass_count = self.passed.len();
+        let pass_count = self.passed.len() + self.flaky.len();
+        let hook_count = self.hook_failures.len();
 
-        if fail_count == 0 {
-            outln!(
-                out,
-                block! { "✓ " pass_count " tests completed (" (info.duration) ")" }
-            );
+        if fail_count == 0 && hook_count == 0 {
+            out.line(list![
+                styled("✓", Color::Green.into()),
+                plain(format!(" {} tests completed ", pass_count)),
+                styled(format!("({})", info.duration), dim())
+            ])?;
+            self.write_left_out(out, info.focused)?;
         } else {
-            outln!(
-                out,
-                block! { "✖ {} of {} tests failed:" (fail_count) (fail_count + pass_count) }
-            );
-
-            outln!(out);
-
-            for (info, err) in &self.failed {
-                outln!(
-                    out,
-                    block! {
-                        (out.enumerate(info.number))
-                        ") " (info.suite_name()) " should " (info.name()) ": " (err.as_terse())
+            if fail_count > 0 {
+                out.line(styled(
+                    format!(
+                        "✖ {} of {} tests failed:",
+                        fail_count,
+                        fail_count + pass_count
+                    ),
+                    Color::Red.into(),
+                ))?;
+            }
+
+            if hook_count > 0 {
+                out.line(styled(
+                    format!("✖ {} after_all hooks failed:", hook_count),
+                    Color::Red.into(),
+                ))?;
+            }
+
+            self.write_left_out(out, info.focused)?;
+
+            for info in &self.failed {
+                out.blank_line()?;
+                out.line(plain(format!(
+                    "{}) {} {}",
+                    out.enumerate(info.number),
+                    info.suite_name(),
+                    failed_title(info)
+                )))?;
+
+                write_status(out, &info.status)?;
+            }
+
+            for hook in &self.hook_failures {
+                out.blank_line()?;
+                out.line(plain(format!(
+                    "✖ {} \"{}\" hook",
+                    hook.suite_name(),
+                    hook.hook()
+                )))?;
+                out.nested_line(hook.failure().clone())?;
+            }
+        }
+
+        if !self.flaky.is_empty() {
+            out.blank_line()?;
+            out.line(styled(
+                format!("⚠ {} tests only passed on a retry:", self.flaky.len()),
+                Color::Yellow.into(),
+            ))?;
+
+            for info in &self.flaky {
+                out.blank_line()?;
+                out.line(plain(format!(
+                    "{} should {} (passed on attempt {})",
+                    info.suite_name(),
+                    info.name,
+                    info.attempts.len()
+                )))?;
+
+                for (i, attempt) in info.attempts.iter().enumerate() {
+                    if attempt.status().is_failure() {
+                        out.nested_line(plain(format!("attempt {}:", i + 1)))?;
+                        write_status(out, attempt.status())?;
                     }
-                );
+                }
             }
         }
 
@@ -0,0 +1,228 @@
+use veritas::Failure;
+
+use crate::{
+    spec_result::{HookKind, SpecInfo, SpecStatus},
+    suite::{described::SuiteDetails, finalized::spec_path, FullSuiteInfo},
+    suite_result::SuiteFinished,
+};
+
+use super::{output::SuiteOutput, ReportResult, Reporter, StartedReporter};
+
+/// Reports specs in TAP version 13, as each one finishes.
+///
+/// Specs are numbered across every suite in the run, so the plan (`1..N`) comes at the end.
+/// Failures have a YAML block with the failure message, the duration of the spec and the path of
+/// the suite that it's in. A failed `after_all` hook is reported as a failing test point of its
+/// own.
+#[derive(Debug, Clone)]
+pub struct TapReporter;
+
+#[derive(Debug, Clone)]
+pub struct StartedTapReporter {
+    started: bool,
+    count: usize,
+    suites: Vec<String>,
+}
+
+impl Reporter for TapReporter {
+    type Started = StartedTapReporter;
+
+    fn start(&self) -> Self::Started {
+        StartedTapReporter {
+            started: false,
+            count: 0,
+            suites: vec![],
+        }
+    }
+}
+
+/// A description can't contain a newline, and a `#` would start a directive.
+fn description(text: &str) -> String {
+    text.replace('\n', " ").replace('#', "\\#")
+}
+
+/// A double-quoted YAML string.
+fn yaml_string(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
+impl StartedTapReporter {
+    fn path(&self) -> String {
+        self.suites
+            .iter()
+            .fold(String::new(), |path, suite| spec_path(&path, suite))
+    }
+
+    fn not_ok(
+        &mut self,
+        out: &mut SuiteOutput,
+        name: &str,
+        failure: &Failure,
+        hook: Option<HookKind>,
+        duration: &str,
+    ) -> ReportResult {
+        out.raw(format!("not ok {} - {}\n", self.count, name))?;
+        out.raw("  ---\n")?;
+        out.raw(format!("  message: {}\n", yaml_string(failure.message())))?;
+        out.raw("  severity: fail\n")?;
+
+        if let Some(hook) = hook {
+            out.raw(format!("  hook: {}\n", hook))?;
+        }
+
+        out.raw(format!("  duration: {}\n", yaml_string(duration)))?;
+        out.raw(format!("  suite: {}\n", yaml_string(&self.path())))?;
+        out.raw("  diagnostic: |\n")?;
+
+        for line in failure.to_string().lines() {
+            if line.is_empty() {
+                out.raw("\n")?;
+            } else {
+                out.raw(format!("    {}\n", line))?;
+            }
+        }
+
+        out.raw("  ...\n")?;
+        Ok(())
+    }
+}
+
+impl StartedReporter for StartedTapReporter {
+    fn start_suite(&mut self, out: &mut SuiteOutput, desc: &SuiteDetails) -> ReportResult {
+        if !self.started {
+            self.started = true;
+            out.raw("TAP version 13\n")?;
+        }
+
+        self.suites.push(desc.name().clone());
+        Ok(())
+    }
+
+    fn end_suite(&mut self, out: &mut SuiteOutput, result: &SuiteFinished) -> ReportResult {
+        for failure in result.hook_failures() {
+            self.count += 1;
+
+            let name = description(&spec_path(
+                &self.path(),
+                &format!("\"{}\" hook", failure.hook),
+            ));
+            let duration = result.duration().to_string();
+
+            self.not_ok(out, &name, &failure.failure, Some(failure.hook), &duration)?;
+        }
+
+        self.suites.pop();
+        Ok(())
+    }
+
+    fn end_test(&mut self, out: &mut SuiteOutput, info: SpecInfo) -> ReportResult {
+        self.count += 1;
+
+        let name = description(&spec_path(&self.path(), &info.name));
+
+        match &info.status {
+            SpecStatus::Success => out.raw(format!("ok {} - {}\n", self.count, name))?,
+            SpecStatus::Flaky => {
+                out.raw(format!("ok {} - {}\n", self.count, name))?;
+                out.raw("  ---\n")?;
+                out.raw(format!(
+                    "  message: {}\n",
+                    yaml_string(&format!("passed on attempt {}", info.attempts.len()))
+                ))?;
+                out.raw("  severity: flaky\n")?;
+                out.raw(format!("  attempts: {}\n", info.attempts.len()))?;
+                out.raw(format!(
+                    "  duration: {}\n",
+                    yaml_string(&info.duration.to_string())
+                ))?;
+                out.raw(format!("  suite: {}\n", yaml_string(&self.path())))?;
+                out.raw("  ...\n")?;
+            }
+            SpecStatus::Skipped => out.raw(format!("ok {} - {} # SKIP\n", self.count, name))?,
+            SpecStatus::Filtered => out.raw(format!(
+                "ok {} - {} # SKIP filtered out\n",
+                self.count, name
+            ))?,
+            SpecStatus::Failure(failure) => {
+                let duration = info.duration.to_string();
+                self.not_ok(out, &name, failure, None, &duration)?;
+            }
+            SpecStatus::Panicked(panic) => {
+                out.raw(format!("not ok {} - {}\n", self.count, name))?;
+                out.raw("  ---\n")?;
+                out.raw(format!("  message: {}\n", yaml_string(&panic.message)))?;
+                out.raw("  severity: panic\n")?;
+
+                if let Some(location) = &panic.location {
+                    out.raw(format!("  at: {}\n", yaml_string(location)))?;
+                }
+
+                out.raw(format!(
+                    "  duration: {}\n",
+                    yaml_string(&info.duration.to_string())
+                ))?;
+                out.raw(format!("  suite: {}\n", yaml_string(&self.path())))?;
+                out.raw("  ...\n")?;
+            }
+            SpecStatus::TimedOut(elapsed) => {
+                out.raw(format!("not ok {} - {}\n", self.count, name))?;
+                out.raw("  ---\n")?;
+                out.raw(format!(
+                    "  message: {}\n",
+                    yaml_string(&format!("timed out after {}", elapsed))
+                ))?;
+                out.raw("  severity: timeout\n")?;
+                out.raw(format!(
+                    "  duration: {}\n",
+                    yaml_string(&info.duration.to_string())
+                ))?;
+                out.raw(format!("  suite: {}\n", yaml_string(&self.path())))?;
+                out.raw("  ...\n")?;
+            }
+            SpecStatus::HookFailure(failure) => {
+                let duration = info.duration.to_string();
+                self.not_ok(out, &name, &failure.failure, Some(failure.hook), &duration)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn finish(&mut self, out: &mut SuiteOutput, _info: FullSuiteInfo) -> ReportResult {
+        if !self.started {
+            out.raw("TAP version 13\n")?;
+        }
+
+        out.raw(format!("1..{}\n", self.count))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_text() {
+        assert_eq!(description("return #1\nor 2"), "return \\#1 or 2");
+        assert_eq!(
+            yaml_string("say \"hi\"\\\n\u{7}"),
+            r#""say \"hi\"\\\n\u0007""#
+        );
+    }
+}
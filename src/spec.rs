@@ -1,40 +1,166 @@
 use derive_new::new;
-use laboratory_expectations::traits::MatchResult;
+use veritas::{contains, expect, Described, Failure};
 
 use crate::{
-    curry::Function, curry::TypedFunction, spec_result::FinishedSpec, suite::mutable::WeakRef,
+    curry::Function,
+    curry::TypedFunction,
+    spec_result::SpecStatus,
+    suite::{
+        mutable::{HeldStates, WeakRef},
+        DurationWithPrecision,
+    },
+    unwind, DurationPrecision,
 };
 
-use std::fmt::Debug;
+use std::{cell::RefCell, fmt::Debug, rc::Rc, sync::mpsc, thread, time::Duration, time::Instant};
 
-// type Callback<T: 'static> = Box<dyn FnMut(&mut T) -> Result<(), String> + 'static>;
+/// The result of a spec: `Ok` if it passed, or the assertion that failed.
+pub type MatchResult = Result<(), Failure>;
 
 type Callback<T> = TypedFunction<T, MatchResult>;
 
+/// A spec that passes if `spec` panics, with a message that contains `expected` if it's given.
+fn expect_panic<T>(
+    expected: Option<String>,
+    spec: impl Fn(&mut T) + Send + 'static,
+) -> impl Fn(&mut T) -> MatchResult + Send + 'static {
+    move |state| match unwind::catch(|| spec(state)) {
+        Ok(()) => Err(Failure::new(
+            "expected the spec to panic",
+            Described::new("the spec", "returned without panicking".to_string()),
+        )),
+        Err(panic) => match &expected {
+            Some(expected) => expect(panic.message().as_str())
+                .described_as("the panic message")
+                .to(contains(expected.as_str())),
+            None => Ok(()),
+        },
+    }
+}
+
+/// The specs added to an `It`, shared with the handles it returns for them.
+type Specs<T> = Rc<RefCell<Vec<TypedSpec<T>>>>;
+
+/// Add `spec` to `specs`, and return a handle to it so that its options can be set.
+fn push<T>(specs: &Specs<T>, spec: TypedSpec<T>) -> SpecHandle<T> {
+    let mut list = specs.borrow_mut();
+    list.push(spec);
+
+    SpecHandle {
+        specs: specs.clone(),
+        index: list.len() - 1,
+    }
+}
+
+/// Take the specs that were added to an `It`.
+fn take<T>(specs: Specs<T>) -> Vec<TypedSpec<T>> {
+    specs.borrow_mut().drain(..).collect()
+}
+
+/// A spec that was just added by `should`, `only`, `skip` or `should_panic`, to set its options.
+/// It doesn't borrow the `It`, so a `specs` callback can end with it.
+pub struct SpecHandle<T>
+where
+    T: 'static,
+{
+    specs: Specs<T>,
+    index: usize,
+}
+
+impl<T> SpecHandle<T>
+where
+    T: 'static,
+{
+    fn set(self, option: impl FnOnce(&mut TypedSpec<T>)) -> SpecHandle<T> {
+        option(&mut self.specs.borrow_mut()[self.index]);
+        self
+    }
+
+    /// Fail the spec with `SpecStatus::TimedOut` if it runs for longer than `timeout`, instead of
+    /// the timeout of its suite.
+    pub fn timeout(self, timeout: Duration) -> SpecHandle<T> {
+        self.set(|spec| spec.timeout = Some(timeout))
+    }
+
+    /// Run the spec again, up to `retries` times, while it fails or panics. A spec that passes on
+    /// a retry is reported as `SpecStatus::Flaky`. This overrides the retries of its suite.
+    pub fn retries(self, retries: usize) -> SpecHandle<T> {
+        self.set(|spec| spec.retries = Some(retries))
+    }
+
+    /// Give the spec `tags`, so that a `Filter` can select it by them.
+    pub fn tagged(self, tags: impl IntoIterator<Item = impl Into<String>>) -> SpecHandle<T> {
+        self.set(|spec| spec.tags.extend(tags.into_iter().map(Into::into)))
+    }
+}
+
 #[derive(new)]
 pub struct It {
     #[new(default)]
-    specs: Vec<TypedSpec<()>>,
+    specs: Specs<()>,
 }
 
 impl It {
     pub(crate) fn specs(self) -> Vec<TypedSpec<()>> {
-        self.specs
+        take(self.specs)
     }
 
-    pub fn should(&mut self, name: impl Into<String>, spec: impl Fn() -> MatchResult + 'static) {
-        self.specs
-            .push(TypedSpec::new(name, move |_: &mut ()| spec()));
+    pub fn should(
+        &mut self,
+        name: impl Into<String>,
+        spec: impl Fn() -> MatchResult + Send + 'static,
+    ) -> SpecHandle<()> {
+        push(&self.specs, TypedSpec::new(name, move |_: &mut ()| spec()))
     }
 
-    pub fn only(&mut self, name: impl Into<String>, spec: impl Fn() -> MatchResult + 'static) {
-        self.specs
-            .push(TypedSpec::new(name, move |_: &mut ()| spec()).only());
+    pub fn only(
+        &mut self,
+        name: impl Into<String>,
+        spec: impl Fn() -> MatchResult + Send + 'static,
+    ) -> SpecHandle<()> {
+        push(
+            &self.specs,
+            TypedSpec::new(name, move |_: &mut ()| spec()).only(),
+        )
     }
 
-    pub fn skip(&mut self, name: impl Into<String>, spec: impl Fn() -> MatchResult + 'static) {
-        self.specs
-            .push(TypedSpec::new(name, move |_: &mut ()| spec()).skip());
+    pub fn skip(
+        &mut self,
+        name: impl Into<String>,
+        spec: impl Fn() -> MatchResult + Send + 'static,
+    ) -> SpecHandle<()> {
+        push(
+            &self.specs,
+            TypedSpec::new(name, move |_: &mut ()| spec()).skip(),
+        )
+    }
+
+    /// A spec that passes if `spec` panics.
+    pub fn should_panic(
+        &mut self,
+        name: impl Into<String>,
+        spec: impl Fn() + Send + 'static,
+    ) -> SpecHandle<()> {
+        push(
+            &self.specs,
+            TypedSpec::new(name, expect_panic(None, move |_: &mut ()| spec())),
+        )
+    }
+
+    /// A spec that passes if `spec` panics with a message that contains `expected`.
+    pub fn should_panic_with(
+        &mut self,
+        name: impl Into<String>,
+        expected: impl Into<String>,
+        spec: impl Fn() + Send + 'static,
+    ) -> SpecHandle<()> {
+        push(
+            &self.specs,
+            TypedSpec::new(
+                name,
+                expect_panic(Some(expected.into()), move |_: &mut ()| spec()),
+            ),
+        )
     }
 }
 
@@ -44,7 +170,7 @@ where
     T: 'static,
 {
     #[new(default)]
-    specs: Vec<TypedSpec<WeakRef<T>>>,
+    specs: Specs<WeakRef<T>>,
 }
 
 impl<T> TypedMutableIt<T>
@@ -52,18 +178,18 @@ where
     T: 'static,
 {
     pub(crate) fn specs(self) -> Vec<TypedSpec<WeakRef<T>>> {
-        self.specs
+        take(self.specs)
     }
 
     fn callback(
-        spec: impl Fn(&mut T) -> MatchResult + 'static,
-    ) -> impl Fn(&mut WeakRef<T>) -> MatchResult {
-        move |input: &mut WeakRef<T>| input.mut_ref(|v| spec(v))
+        spec: impl Fn(&mut T) -> MatchResult + Send + 'static,
+    ) -> impl Fn(&mut WeakRef<T>) -> MatchResult + Send {
+        move |input: &mut WeakRef<T>| input.mut_ref(|v| spec(v))?
     }
 
     fn spec(
         name: impl Into<String>,
-        spec: impl Fn(&mut T) -> MatchResult + 'static,
+        spec: impl Fn(&mut T) -> MatchResult + Send + 'static,
     ) -> TypedSpec<WeakRef<T>> {
         TypedSpec::new(name, TypedMutableIt::callback(spec))
     }
@@ -71,25 +197,50 @@ where
     pub fn should(
         &mut self,
         name: impl Into<String>,
-        spec: impl Fn(&mut T) -> MatchResult + 'static,
-    ) {
-        self.specs.push(TypedMutableIt::spec(name, spec));
+        spec: impl Fn(&mut T) -> MatchResult + Send + 'static,
+    ) -> SpecHandle<WeakRef<T>> {
+        push(&self.specs, TypedMutableIt::spec(name, spec))
     }
 
     pub fn only(
         &mut self,
         name: impl Into<String>,
-        spec: impl Fn(&mut T) -> MatchResult + 'static,
-    ) {
-        self.specs.push(TypedMutableIt::spec(name, spec).only());
+        spec: impl Fn(&mut T) -> MatchResult + Send + 'static,
+    ) -> SpecHandle<WeakRef<T>> {
+        push(&self.specs, TypedMutableIt::spec(name, spec).only())
     }
 
     pub fn skip(
         &mut self,
         name: impl Into<String>,
-        spec: impl Fn(&mut T) -> MatchResult + 'static,
-    ) {
-        self.specs.push(TypedMutableIt::spec(name, spec).skip());
+        spec: impl Fn(&mut T) -> MatchResult + Send + 'static,
+    ) -> SpecHandle<WeakRef<T>> {
+        push(&self.specs, TypedMutableIt::spec(name, spec).skip())
+    }
+
+    /// A spec that passes if `spec` panics.
+    pub fn should_panic(
+        &mut self,
+        name: impl Into<String>,
+        spec: impl Fn(&mut T) + Send + 'static,
+    ) -> SpecHandle<WeakRef<T>> {
+        push(
+            &self.specs,
+            TypedMutableIt::spec(name, expect_panic(None, spec)),
+        )
+    }
+
+    /// A spec that passes if `spec` panics with a message that contains `expected`.
+    pub fn should_panic_with(
+        &mut self,
+        name: impl Into<String>,
+        expected: impl Into<String>,
+        spec: impl Fn(&mut T) + Send + 'static,
+    ) -> SpecHandle<WeakRef<T>> {
+        push(
+            &self.specs,
+            TypedMutableIt::spec(name, expect_panic(Some(expected.into()), spec)),
+        )
     }
 }
 
@@ -99,7 +250,7 @@ where
     T: Debug + Clone + 'static,
 {
     #[new(default)]
-    specs: Vec<TypedSpec<T>>,
+    specs: Specs<T>,
 }
 
 impl<T> TypedIt<T>
@@ -107,31 +258,53 @@ where
     T: Debug + Clone + 'static,
 {
     pub(crate) fn specs(self) -> Vec<TypedSpec<T>> {
-        self.specs
+        take(self.specs)
     }
 
     pub fn should(
         &mut self,
         name: impl Into<String>,
-        spec: impl Fn(&mut T) -> MatchResult + 'static,
-    ) {
-        self.specs.push(TypedSpec::new(name, spec));
+        spec: impl Fn(&mut T) -> MatchResult + Send + 'static,
+    ) -> SpecHandle<T> {
+        push(&self.specs, TypedSpec::new(name, spec))
     }
 
     pub fn only(
         &mut self,
         name: impl Into<String>,
-        spec: impl Fn(&mut T) -> MatchResult + 'static,
-    ) {
-        self.specs.push(TypedSpec::new(name, spec));
+        spec: impl Fn(&mut T) -> MatchResult + Send + 'static,
+    ) -> SpecHandle<T> {
+        push(&self.specs, TypedSpec::new(name, spec).only())
     }
 
     pub fn skip(
         &mut self,
         name: impl Into<String>,
-        spec: impl Fn(&mut T) -> MatchResult + 'static,
-    ) {
-        self.specs.push(TypedSpec::new(name, spec));
+        spec: impl Fn(&mut T) -> MatchResult + Send + 'static,
+    ) -> SpecHandle<T> {
+        push(&self.specs, TypedSpec::new(name, spec).skip())
+    }
+
+    /// A spec that passes if `spec` panics.
+    pub fn should_panic(
+        &mut self,
+        name: impl Into<String>,
+        spec: impl Fn(&mut T) + Send + 'static,
+    ) -> SpecHandle<T> {
+        push(&self.specs, TypedSpec::new(name, expect_panic(None, spec)))
+    }
+
+    /// A spec that passes if `spec` panics with a message that contains `expected`.
+    pub fn should_panic_with(
+        &mut self,
+        name: impl Into<String>,
+        expected: impl Into<String>,
+        spec: impl Fn(&mut T) + Send + 'static,
+    ) -> SpecHandle<T> {
+        push(
+            &self.specs,
+            TypedSpec::new(name, expect_panic(Some(expected.into()), spec)),
+        )
     }
 }
 
@@ -145,29 +318,91 @@ pub enum ShouldRun {
 #[derive(Debug)]
 pub struct Spec {
     pub name: String,
-    callback: Function<'static, MatchResult>,
+    /// `None` once the spec has timed out, since its callback is still running on another
+    /// thread.
+    callback: Option<Function<'static, MatchResult>>,
     running: ShouldRun,
+    timeout: Option<Duration>,
+    retries: Option<usize>,
+    tags: Vec<String>,
+    filtered: bool,
 }
 
 impl Spec {
-    pub(crate) fn run(self, suite_name: impl Into<String>) -> FinishedSpec {
-        let Spec {
-            name,
-            running,
-            mut callback,
-        } = self;
-
-        match running {
-            ShouldRun::Never => FinishedSpec::skipped(suite_name, name),
-            _ => {
-                let result = callback.call();
-
-                FinishedSpec::ran(suite_name, name, result)
+    /// Run the spec once. If it has a timeout of its own, or its suite gives it one, it runs on
+    /// its own thread, which is abandoned if the spec takes longer than that, along with any
+    /// mutable state that it locked.
+    pub(crate) fn attempt(
+        &mut self,
+        suite_timeout: Option<Duration>,
+        precision: DurationPrecision,
+    ) -> SpecStatus {
+        let mut callback = self
+            .callback
+            .take()
+            .expect("a spec that timed out was run again");
+
+        let result = match self.timeout.or(suite_timeout) {
+            None => {
+                let result = unwind::catch(|| callback.call());
+                self.callback = Some(callback);
+                result
+            }
+            Some(timeout) => {
+                let (sender, receiver) = mpsc::channel();
+                let start = Instant::now();
+                let held = HeldStates::default();
+                let tracked = held.clone();
+
+                thread::spawn(move || {
+                    tracked.track();
+                    let result = unwind::catch(|| callback.call());
+                    // The receiver is gone if the spec timed out, and nobody wants the result
+                    let _ = sender.send((callback, result));
+                });
+
+                match receiver.recv_timeout(timeout) {
+                    Ok((callback, result)) => {
+                        self.callback = Some(callback);
+                        result
+                    }
+                    Err(_) => {
+                        held.abandon();
+                        let elapsed = DurationWithPrecision::new(start.elapsed(), precision);
+                        return SpecStatus::TimedOut(elapsed);
+                    }
+                }
             }
+        };
+
+        match result {
+            Ok(Ok(())) => SpecStatus::Success,
+            Ok(Err(failure)) => SpecStatus::Failure(failure),
+            Err(panic) => SpecStatus::Panicked(panic),
         }
     }
 
-    pub(crate) fn in_only_suite(mut self) -> Spec {
+    /// The number of times the spec is run again if it fails, if it sets one itself.
+    pub(crate) fn retries(&self) -> Option<usize> {
+        self.retries
+    }
+
+    pub(crate) fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    /// Leave the spec out of the run, and report it as filtered.
+    pub(crate) fn filter_out(&mut self) {
+        self.filtered = true;
+    }
+
+    pub(crate) fn is_filtered(&self) -> bool {
+        self.filtered
+    }
+
+    /// Skip the spec, unless it's focused with `only`. Used when something else in the run is
+    /// focused, and neither the spec nor a suite that it's in is.
+    pub(crate) fn unfocused(mut self) -> Spec {
         match self.running {
             ShouldRun::Always | ShouldRun::Never => {
                 self.running = ShouldRun::Never;
@@ -181,6 +416,18 @@ impl Spec {
     pub(crate) fn is_only(&self) -> bool {
         self.running == ShouldRun::Only
     }
+
+    pub(crate) fn is_skipped(&self) -> bool {
+        self.running == ShouldRun::Never
+    }
+
+    pub(crate) fn unskip(mut self) -> Spec {
+        if self.running == ShouldRun::Never {
+            self.running = ShouldRun::Always;
+        }
+
+        self
+    }
 }
 
 pub struct TypedSpec<T>
@@ -190,6 +437,9 @@ where
     pub name: String,
     callback: Callback<T>,
     running: ShouldRun,
+    timeout: Option<Duration>,
+    retries: Option<usize>,
+    tags: Vec<String>,
 }
 
 impl<T> Debug for TypedSpec<T>
@@ -200,6 +450,9 @@ where
         f.debug_struct("TypedSpec")
             .field("name", &self.name)
             .field("running", &self.running)
+            .field("timeout", &self.timeout)
+            .field("retries", &self.retries)
+            .field("tags", &self.tags)
             .finish()
     }
 }
@@ -210,12 +463,15 @@ where
 {
     pub(crate) fn new(
         name: impl Into<String>,
-        callback: impl Fn(&mut T) -> MatchResult + 'static,
+        callback: impl Fn(&mut T) -> MatchResult + Send + 'static,
     ) -> TypedSpec<T> {
         TypedSpec {
             name: name.into(),
             callback: TypedFunction::new(callback),
             running: ShouldRun::Always,
+            timeout: None,
+            retries: None,
+            tags: vec![],
         }
     }
 
@@ -229,11 +485,15 @@ where
         self
     }
 
-    pub fn with_state(self, state: impl FnMut() -> T + 'static) -> Spec {
+    pub fn with_state(self, state: impl FnMut() -> T + Send + 'static) -> Spec {
         Spec {
             name: self.name,
-            callback: self.callback.hide_fn(state),
+            callback: Some(self.callback.hide_fn(state)),
             running: self.running,
+            timeout: self.timeout,
+            retries: self.retries,
+            tags: self.tags,
+            filtered: false,
         }
     }
 }
@@ -1,13 +1,11 @@
 use derive_new::new;
 use getset::Getters;
-use laboratory_expectations::{traits::MatchResult, MatchError};
+use serde::{Deserialize, Serialize};
+use veritas::Failure;
 
-use std::{
-    fmt::Display,
-    time::{Duration, Instant},
-};
+use std::{fmt::Display, time::Duration};
 
-use crate::{suite::DurationWithPrecision, DurationPrecision};
+use crate::{spec::MatchResult, suite::DurationWithPrecision, DurationPrecision};
 
 #[derive(Debug, Clone)]
 pub struct SpecDesc {
@@ -20,28 +18,38 @@ pub struct ReporterSpecInfo {
     pub name: String,
     pub suite_name: String,
     pub number: usize,
+    pub only: bool,
+    /// Whether the spec was left out of the run by a `Filter`.
+    pub filtered: bool,
 }
 
 impl ReporterSpecInfo {
-    pub(crate) fn done(&self, status: SpecStatus, duration: DurationWithPrecision) -> SpecInfo {
+    pub(crate) fn done(
+        &self,
+        status: SpecStatus,
+        duration: DurationWithPrecision,
+        attempts: Vec<SpecAttempt>,
+    ) -> SpecInfo {
         SpecInfo {
             name: self.name.clone(),
             suite_name: self.suite_name.clone(),
             number: self.number,
+            only: self.only,
             status,
             duration,
+            attempts,
         }
     }
 
     pub(crate) fn skipped(&self, precision: DurationPrecision) -> SpecInfo {
-        let duration = Instant::now().elapsed();
-
         SpecInfo {
             name: self.name.clone(),
             suite_name: self.suite_name.clone(),
             number: self.number,
+            only: self.only,
             status: SpecStatus::Skipped,
-            duration: DurationWithPrecision::new(duration, precision),
+            duration: DurationWithPrecision::new(Duration::ZERO, precision),
+            attempts: vec![],
         }
     }
 }
@@ -54,40 +62,111 @@ pub struct SpecInfo {
     pub suite_name: String,
     #[get = "pub"]
     pub number: usize,
+    /// Whether the spec was focused with `only`.
+    #[get = "pub"]
+    pub only: bool,
     #[get = "pub"]
     pub status: SpecStatus,
     #[get = "pub"]
     pub duration: DurationWithPrecision,
+    /// Every time the spec ran, in order. A spec that was retried has more than one attempt, and
+    /// one that was skipped, or blocked by a `before_all` hook, has none.
+    #[get = "pub"]
+    pub attempts: Vec<SpecAttempt>,
+}
+
+/// One run of a spec, between its suite's `before_each` and `after_each` hooks.
+#[derive(Debug, Clone, Getters, new)]
+#[getset(get = "pub")]
+pub struct SpecAttempt {
+    pub(crate) status: SpecStatus,
+    pub(crate) duration: DurationWithPrecision,
 }
 
 #[derive(Debug, Clone, new)]
 pub struct FinishedSpec {
     pub(crate) desc: SpecDesc,
     pub(crate) result: SpecStatus,
+    pub(crate) attempts: Vec<SpecAttempt>,
+}
+
+#[derive(Debug, Clone)]
+pub enum SpecStatus {
+    Success,
+    Failure(Failure),
+    /// The spec didn't pass because a hook failed: a `before_all` of its suite or of a suite it's
+    /// nested in, its suite's `before_each`, or its suite's `after_each`.
+    HookFailure(HookFailure),
+    /// The spec panicked.
+    Panicked(SpecPanic),
+    /// The spec ran for longer than its timeout, and was left running on its own thread. The
+    /// duration is how long it ran before it was given up on.
+    TimedOut(DurationWithPrecision),
+    /// The spec failed, but passed when it was retried. The attempts that failed are in
+    /// `SpecInfo::attempts`.
+    Flaky,
+    Skipped,
+    /// The spec wasn't selected by the run's `Filter`, so it didn't run.
+    Filtered,
 }
 
-#[derive(Debug, Copy, Clone)]
-pub struct SuccessResult {
-    duration: Duration,
+impl SpecStatus {
+    /// Whether the spec failed, either on its own or because of a hook.
+    pub fn is_failure(&self) -> bool {
+        matches!(
+            self,
+            SpecStatus::Failure(_)
+                | SpecStatus::HookFailure(_)
+                | SpecStatus::Panicked(_)
+                | SpecStatus::TimedOut(_)
+        )
+    }
 }
 
-#[derive(Debug, Clone)]
-pub struct ErrorResult {
-    error: String,
-    duration: Duration,
+/// A panic in a spec: its message, and where it happened if the panic hook saw it.
+#[derive(Debug, Clone, Getters, new)]
+#[getset(get = "pub")]
+pub struct SpecPanic {
+    pub(crate) message: String,
+    pub(crate) location: Option<String>,
+}
+
+impl Display for SpecPanic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.location {
+            Some(location) => write!(f, "panicked at {}:\n{}", location, self.message),
+            None => write!(f, "panicked:\n{}", self.message),
+        }
+    }
 }
 
-impl Display for ErrorResult {
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookKind {
+    BeforeAll,
+    BeforeEach,
+    AfterEach,
+    AfterAll,
+}
+
+impl Display for HookKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.error)
+        match self {
+            HookKind::BeforeAll => write!(f, "before_all"),
+            HookKind::BeforeEach => write!(f, "before_each"),
+            HookKind::AfterEach => write!(f, "after_each"),
+            HookKind::AfterAll => write!(f, "after_all"),
+        }
     }
 }
 
-#[derive(Debug, Clone)]
-pub enum SpecStatus {
-    Success,
-    Failure(MatchError),
-    Skipped,
+/// A hook that failed, and the suite that it belongs to.
+#[derive(Debug, Clone, Getters, new)]
+#[getset(get = "pub")]
+pub struct HookFailure {
+    pub(crate) hook: HookKind,
+    pub(crate) suite_name: String,
+    pub(crate) failure: Failure,
 }
 
 impl FinishedSpec {
@@ -98,6 +177,18 @@ impl FinishedSpec {
                 suite_name: suite_name.into(),
             },
             result: SpecStatus::Skipped,
+            attempts: vec![],
+        }
+    }
+
+    pub(crate) fn filtered(suite_name: impl Into<String>, name: impl Into<String>) -> FinishedSpec {
+        FinishedSpec {
+            desc: SpecDesc {
+                name: name.into(),
+                suite_name: suite_name.into(),
+            },
+            result: SpecStatus::Filtered,
+            attempts: vec![],
         }
     }
 
@@ -114,6 +205,38 @@ impl FinishedSpec {
                 Ok(_) => SpecStatus::Success,
                 Err(err) => SpecStatus::Failure(err),
             },
+            attempts: vec![],
+        }
+    }
+
+    pub(crate) fn hook_failed(
+        suite_name: impl Into<String>,
+        name: impl Into<String>,
+        failure: HookFailure,
+    ) -> FinishedSpec {
+        FinishedSpec {
+            desc: SpecDesc {
+                name: name.into(),
+                suite_name: suite_name.into(),
+            },
+            result: SpecStatus::HookFailure(failure),
+            attempts: vec![],
+        }
+    }
+
+    pub(crate) fn attempted(
+        suite_name: impl Into<String>,
+        name: impl Into<String>,
+        result: SpecStatus,
+        attempts: Vec<SpecAttempt>,
+    ) -> FinishedSpec {
+        FinishedSpec {
+            desc: SpecDesc {
+                name: name.into(),
+                suite_name: suite_name.into(),
+            },
+            result,
+            attempts,
         }
     }
 
@@ -1,13 +1,22 @@
 use getset::Getters;
-use std::{fmt::Debug, path::PathBuf};
+use std::{
+    fmt::Debug,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 use crate::{
-    reporter::StartedReporter, spec::It, test::RunnableSuite, DurationPrecision, ReportResult,
-    Reporter, ReporterOutput, SuiteOutcome,
+    reporter::StartedReporter, spec::It, spec_result::HookKind, test::RunnableSuite,
+    DurationPrecision, ReportResult, Reporter, ReporterOutput, SuiteOutcome,
 };
 
 use super::{
-    finalized::FinalizedSuite, mutable::SuiteWithMutableState, state::SuiteWithState,
+    filter::Filter,
+    finalized::FinalizedSuite,
+    hooks::{HookResult, Hooks},
+    mutable::SuiteWithMutableState,
+    state::SuiteWithState,
     traits::SuiteExt,
 };
 
@@ -17,8 +26,14 @@ pub struct SuiteDetails {
     name: String,
     pub(crate) nesting: usize,
     pub(crate) skip: bool,
+    pub(crate) only: bool,
     pub(crate) export: Option<PathBuf>,
     pub(crate) precision: DurationPrecision,
+    pub(crate) threads: usize,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) retries: Option<usize>,
+    pub(crate) tags: Vec<String>,
+    pub(crate) filter: Option<Filter>,
 }
 
 impl SuiteDetails {
@@ -32,6 +47,7 @@ pub struct DescribedSuite {
     details: SuiteDetails,
     reporter: Option<Box<dyn StartedReporter>>,
     nested: Vec<FinalizedSuite>,
+    hooks: Hooks,
 }
 
 impl RunnableSuite for DescribedSuite {
@@ -48,9 +64,9 @@ impl SuiteExt for DescribedSuite {
     }
 }
 
-impl Into<FinalizedSuite> for DescribedSuite {
-    fn into(self) -> FinalizedSuite {
-        self.state(()).into()
+impl From<DescribedSuite> for FinalizedSuite {
+    fn from(suite: DescribedSuite) -> FinalizedSuite {
+        suite.state(()).into()
     }
 }
 
@@ -65,9 +81,16 @@ impl DescribedSuite {
                 export: None,
                 precision: DurationPrecision::Millis,
                 skip: false,
+                only: false,
+                threads: 1,
+                timeout: None,
+                retries: None,
+                tags: vec![],
+                filter: None,
             },
             reporter: None,
             nested: vec![],
+            hooks: Hooks::default(),
         }
     }
 
@@ -78,9 +101,18 @@ impl DescribedSuite {
 
     pub fn state<T>(self, state: T) -> SuiteWithState<T>
     where
-        T: Clone + Debug + 'static,
+        T: Clone + Debug + Send + 'static,
+    {
+        self.shared_state(Arc::new(Mutex::new(state)))
+    }
+
+    /// Give the suite state that is shared with the suite that it's nested in, so that the hooks
+    /// of both suites see the same state.
+    pub(crate) fn shared_state<T>(self, state: Arc<Mutex<T>>) -> SuiteWithState<T>
+    where
+        T: Clone + Debug + Send + 'static,
     {
-        let mut suite = SuiteWithState::new(self.details, self.reporter, state);
+        let mut suite = SuiteWithState::new(self.details, self.reporter, self.hooks, state);
 
         for nested in self.nested {
             suite = suite.suite(nested);
@@ -91,9 +123,9 @@ impl DescribedSuite {
 
     pub fn mutable_state<T>(self, state: T) -> SuiteWithMutableState<T>
     where
-        T: Debug + 'static,
+        T: Debug + Send + 'static,
     {
-        let mut suite = SuiteWithMutableState::top(self.details, self.reporter, state);
+        let mut suite = SuiteWithMutableState::top(self.details, self.reporter, self.hooks, state);
 
         for nested in self.nested {
             suite = suite.suite(nested);
@@ -102,6 +134,45 @@ impl DescribedSuite {
         suite
     }
 
+    fn hook<R: HookResult>(
+        mut self,
+        kind: HookKind,
+        mut hook: impl FnMut(&mut ()) -> R + Send + 'static,
+    ) -> Self {
+        self.hooks
+            .add(kind, Box::new(move || hook(&mut ()).into_result()));
+        self
+    }
+
+    /// Run `hook` once, before any of the suite's specs or nested suites.
+    pub fn before_all<R: HookResult>(
+        self,
+        hook: impl FnMut(&mut ()) -> R + Send + 'static,
+    ) -> Self {
+        self.hook(HookKind::BeforeAll, hook)
+    }
+
+    /// Run `hook` before each of the suite's specs, and of the specs of the suites nested in it.
+    pub fn before_each<R: HookResult>(
+        self,
+        hook: impl FnMut(&mut ()) -> R + Send + 'static,
+    ) -> Self {
+        self.hook(HookKind::BeforeEach, hook)
+    }
+
+    /// Run `hook` after each of the suite's specs, and of the specs of the suites nested in it.
+    pub fn after_each<R: HookResult>(
+        self,
+        hook: impl FnMut(&mut ()) -> R + Send + 'static,
+    ) -> Self {
+        self.hook(HookKind::AfterEach, hook)
+    }
+
+    /// Run `hook` once, after the suite's specs and nested suites.
+    pub fn after_all<R: HookResult>(self, hook: impl FnMut(&mut ()) -> R + Send + 'static) -> Self {
+        self.hook(HookKind::AfterAll, hook)
+    }
+
     pub fn suite(mut self, suite: impl Into<FinalizedSuite>) -> Self {
         self.nested.push(suite.into());
         self
@@ -115,7 +186,9 @@ impl DescribedSuite {
         self
     }
 
-    pub fn specs(self, callback: impl FnOnce(&mut It)) -> SuiteWithState<()> {
+    /// Add the specs that `callback` gives to `it`. It can end with one of them, since what it
+    /// returns is ignored.
+    pub fn specs<R>(self, callback: impl FnOnce(&mut It) -> R) -> SuiteWithState<()> {
         let mut suite = self.state(());
 
         let mut it = It::new();
@@ -0,0 +1,157 @@
+/// The full path of a spec or suite for a `Filter`: the names of the suites that it's nested in
+/// and its own name, separated by ` > `.
+pub(crate) fn filter_path(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{} > {}", path, name)
+    }
+}
+
+/// Whether `text` matches `pattern`, where `*` matches any number of characters and `?` matches
+/// exactly one.
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    // Where the last `*` was, and where in the text it started matching
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                // Let the last `*` match one more character, and try again after it
+                Some((star_p, star_t)) => {
+                    star = Some((star_p, star_t + 1));
+                    p = star_p + 1;
+                    t = star_t + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Selects the specs of a run by their full path, like `Library > add_one() > return 1`, and by
+/// their tags. A spec's tags include the tags of the suites that it's nested in.
+///
+/// A spec is selected if it matches any of the names and globs, or there are none; if it has any
+/// of the included tags, or none are included; and if it has none of the excluded tags. Specs
+/// that aren't selected are reported as `SpecStatus::Filtered`.
+///
+/// ```
+/// use laboratory::Filter;
+///
+/// let filter = Filter::new().glob("Library > add_*").tag("fast").exclude_tag("db");
+/// ```
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Filter {
+    names: Vec<String>,
+    globs: Vec<String>,
+    tags: Vec<String>,
+    excluded_tags: Vec<String>,
+}
+
+impl Filter {
+    pub fn new() -> Filter {
+        Filter::default()
+    }
+
+    /// Select the specs whose full path contains `name`.
+    pub fn name(mut self, name: impl Into<String>) -> Filter {
+        self.names.push(name.into());
+        self
+    }
+
+    /// Select the specs whose full path matches `glob`, where `*` matches any number of
+    /// characters and `?` matches one.
+    pub fn glob(mut self, glob: impl Into<String>) -> Filter {
+        self.globs.push(glob.into());
+        self
+    }
+
+    /// Select the specs that have `tag`.
+    pub fn tag(mut self, tag: impl Into<String>) -> Filter {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Leave out the specs that have `tag`, even if they're selected otherwise.
+    pub fn exclude_tag(mut self, tag: impl Into<String>) -> Filter {
+        self.excluded_tags.push(tag.into());
+        self
+    }
+
+    /// Whether the spec with the full path `path` and the tags `tags` is selected.
+    pub fn selects(&self, path: &str, tags: &[String]) -> bool {
+        let named = (self.names.is_empty() && self.globs.is_empty())
+            || self.names.iter().any(|name| path.contains(name.as_str()))
+            || self.globs.iter().any(|glob| glob_matches(glob, path));
+
+        let tagged = self.tags.is_empty() || self.tags.iter().any(|tag| tags.contains(tag));
+        let excluded = self.excluded_tags.iter().any(|tag| tags.contains(tag));
+
+        named && tagged && !excluded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn globs() {
+        assert!(glob_matches(
+            "Library > *",
+            "Library > add_one() > return 1"
+        ));
+        assert!(glob_matches("*return ?", "Library > add_one() > return 1"));
+        assert!(glob_matches(
+            "*add*return*",
+            "Library > add_one() > return 1"
+        ));
+        assert!(!glob_matches(
+            "*return ?",
+            "Library > add_one() > return 10"
+        ));
+        assert!(!glob_matches(
+            "add_one()*",
+            "Library > add_one() > return 1"
+        ));
+        assert!(glob_matches("", ""));
+        assert!(glob_matches("**", ""));
+    }
+
+    #[test]
+    fn selects_by_name_and_tag() {
+        let tags = vec!["db".to_string(), "slow".to_string()];
+
+        assert!(Filter::new().selects("Library > save", &tags));
+        assert!(Filter::new().name("sav").selects("Library > save", &tags));
+        assert!(!Filter::new().name("load").selects("Library > save", &tags));
+        assert!(Filter::new()
+            .name("load")
+            .glob("* > s*")
+            .selects("Library > save", &tags));
+        assert!(Filter::new()
+            .tag("fast")
+            .tag("db")
+            .selects("Library > save", &tags));
+        assert!(!Filter::new().tag("fast").selects("Library > save", &tags));
+        assert!(!Filter::new()
+            .name("save")
+            .exclude_tag("slow")
+            .selects("Library > save", &tags));
+    }
+}
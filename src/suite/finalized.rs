@@ -1,15 +1,40 @@
-use std::time::Instant;
+use std::{
+    convert::Infallible,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 
 use crate::{
-    reporter::StartedReporter, spec_result::ReporterSpecInfo, spec_result::SpecStatus,
-    suite_result::BuildSuiteResult, ReportResult, ReporterOutput, Spec, SuiteOutcome,
+    reporter::{output::SuiteOutput, ReportError, StartedReporter},
+    spec_result::{
+        FinishedSpec, HookFailure, HookKind, ReporterSpecInfo, SpecAttempt, SpecInfo, SpecStatus,
+    },
+    suite_result::{BuildSuiteResult, SuiteFinished},
+    unwind::PanicHookGuard,
+    ReportResult, ReporterOutput, Spec, SuiteOutcome,
 };
 
 use super::{
-    described::SuiteDetails, traits::RunnableSuite, traits::SuiteExt, DurationWithPrecision,
-    FullSuiteInfo,
+    described::SuiteDetails,
+    filter::{filter_path, Filter},
+    hooks::{EachHooks, Hooks},
+    lock,
+    parallel::ThreadBudget,
+    traits::RunnableSuite,
+    traits::SuiteExt,
+    DurationPrecision, DurationWithPrecision, FullSuiteInfo, StartInfo,
 };
 
+/// The full name of a spec or suite: the names of the suites that it's nested in and its own
+/// name, separated by `::`, like the paths that libtest reports.
+pub(crate) fn spec_path(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}::{}", path, name)
+    }
+}
+
 #[derive(Debug)]
 pub struct FinalizedTopSuite {
     suite: FinalizedSuite,
@@ -25,7 +50,105 @@ impl RunnableSuite for FinalizedTopSuite {
             mut reporter,
         } = self;
 
-        suite.run_with_reporter(&mut output, &mut *reporter, 0)
+        let focused = suite.has_focus();
+        let suite = if focused { suite.focus(false) } else { suite };
+
+        let total = suite.spec_count();
+        let suite = suite.select_own();
+
+        reporter.start_run(
+            &mut output.for_suite(0),
+            &StartInfo {
+                test_count: suite.spec_count(),
+                filtered_out: total - suite.spec_count(),
+            },
+        )?;
+
+        let precision = suite.details.precision;
+        let threads = suite.details.threads;
+        let start = Instant::now();
+
+        let outcome = if threads > 1 {
+            run_parallel(vec![suite], threads, &mut output, &mut *reporter)?.remove(0)
+        } else {
+            suite.run_with_reporter(&mut output, &mut *reporter)?
+        };
+
+        reporter.finish(
+            &mut output.for_suite(0),
+            FullSuiteInfo {
+                duration: DurationWithPrecision::new(start.elapsed(), precision),
+                focused,
+            },
+        )?;
+
+        Ok(outcome)
+    }
+}
+
+/// Run `suites` on up to `threads` threads, and report each one as soon as it and the suites
+/// before it have finished. A suite's results are kept until then, so that reporters see the same
+/// callbacks, in the same order, as they would if the suites ran one at a time.
+pub(crate) fn run_parallel(
+    suites: Vec<FinalizedSuite>,
+    threads: usize,
+    output: &mut ReporterOutput,
+    reporter: &mut dyn StartedReporter,
+) -> ReportResult<Vec<SuiteOutcome>> {
+    let _hook = PanicHookGuard::install();
+    let budget = ThreadBudget::new(threads);
+    let mut outcomes = vec![];
+
+    budget.for_each(
+        suites,
+        |suite| {
+            suite
+                .inherit(None, None)
+                .execute(None, &EachHooks::default(), &budget)
+        },
+        |record| -> ReportResult {
+            outcomes.push(SuiteOutcome::Finished(record.replay(output, reporter)?));
+            Ok(())
+        },
+    )?;
+
+    Ok(outcomes)
+}
+
+/// A suite that ran without a reporter: what the reporter would have been told, in order.
+struct SuiteRecord {
+    details: SuiteDetails,
+    specs: Vec<(ReporterSpecInfo, SpecInfo)>,
+    nested: Vec<SuiteRecord>,
+    finished: SuiteFinished,
+}
+
+impl SuiteRecord {
+    fn replay(
+        self,
+        output: &mut ReporterOutput,
+        reporter: &mut dyn StartedReporter,
+    ) -> ReportResult<SuiteFinished> {
+        let mut suite_output = output.for_suite(self.specs.len());
+
+        reporter.start_suite(&mut suite_output, &self.details)?;
+
+        for (info, done) in self.specs {
+            reporter.start_test(&mut suite_output, &info)?;
+            reporter.end_test(&mut suite_output, done)?;
+        }
+
+        let mut output = suite_output.child();
+
+        for suite in self.nested {
+            suite.replay(&mut output.for_nested(), reporter)?;
+        }
+
+        drop(output);
+
+        reporter.end_suite(&mut suite_output, &self.finished)?;
+
+        Ok(self.finished)
     }
 }
 
@@ -34,6 +157,7 @@ pub struct FinalizedSuite {
     details: SuiteDetails,
     specs: Vec<Spec>,
     nested: Vec<FinalizedSuite>,
+    hooks: Hooks,
 }
 
 impl SuiteExt for FinalizedSuite {
@@ -47,15 +171,17 @@ impl FinalizedSuite {
         details: SuiteDetails,
         specs: Vec<Spec>,
         nested: Vec<FinalizedSuite>,
+        hooks: Hooks,
     ) -> FinalizedSuite {
         Self {
             details,
             specs,
             nested,
+            hooks,
         }
     }
 
-    fn nest(mut self, nesting: usize) -> Self {
+    pub(crate) fn nest(mut self, nesting: usize) -> Self {
         self.details.nesting = nesting;
 
         self.nested = self
@@ -79,95 +205,462 @@ impl FinalizedSuite {
         self
     }
 
-    pub fn run_with_reporter(
+    pub(crate) fn precision(&self) -> DurationPrecision {
+        self.details.precision
+    }
+
+    /// The number of threads that the suite asked to run on with `parallel`.
+    pub(crate) fn threads(&self) -> usize {
+        self.details.threads
+    }
+
+    /// Apply the suite's own filter, if it has one.
+    pub(crate) fn select_own(self) -> FinalizedSuite {
+        match self.details.filter.clone() {
+            Some(filter) => self.select(&filter, "", &[]),
+            None => self,
+        }
+    }
+
+    /// The number of specs in this suite and the suites nested in it, leaving out the ones that
+    /// were filtered out.
+    pub(crate) fn spec_count(&self) -> usize {
+        self.specs.iter().filter(|spec| !spec.is_filtered()).count()
+            + self
+                .nested
+                .iter()
+                .map(|suite| suite.spec_count())
+                .sum::<usize>()
+    }
+
+    /// The full name of every spec in this suite and the suites nested in it, along with whether
+    /// the spec is skipped.
+    pub(crate) fn spec_names(&self, path: &str) -> Vec<(String, bool)> {
+        let path = spec_path(path, self.details.suite_name());
+
+        let mut names: Vec<(String, bool)> = self
+            .specs
+            .iter()
+            .filter(|spec| !spec.is_filtered())
+            .map(|spec| {
+                (
+                    spec_path(&path, &spec.name),
+                    self.details.skip || spec.is_skipped(),
+                )
+            })
+            .collect();
+
+        for suite in &self.nested {
+            names.extend(suite.spec_names(&path));
+        }
+
+        names
+    }
+
+    /// Keep the specs that `keep` accepts, given their full name and whether they're skipped.
+    /// Suites that are left without any specs are dropped.
+    pub(crate) fn retain(
+        mut self,
+        path: &str,
+        skipped: bool,
+        keep: &mut dyn FnMut(&str, bool) -> bool,
+    ) -> Option<FinalizedSuite> {
+        let path = spec_path(path, self.details.suite_name());
+        let skipped = skipped || self.details.skip;
+
+        self.specs
+            .retain(|spec| keep(&spec_path(&path, &spec.name), skipped || spec.is_skipped()));
+
+        self.nested = self
+            .nested
+            .into_iter()
+            .filter_map(|suite| suite.retain(&path, skipped, keep))
+            .collect();
+
+        if self.specs.is_empty() && self.nested.is_empty() {
+            None
+        } else {
+            Some(self)
+        }
+    }
+
+    /// Mark the specs that `filter` doesn't select as filtered, in this suite and the suites nested
+    /// in it. `path` and `tags` are the path and tags of the suite that this one is nested in.
+    pub(crate) fn select(mut self, filter: &Filter, path: &str, tags: &[String]) -> FinalizedSuite {
+        let path = filter_path(path, self.details.suite_name());
+        let tags: Vec<String> = tags.iter().chain(&self.details.tags).cloned().collect();
+
+        for spec in &mut self.specs {
+            let spec_tags: Vec<String> = tags.iter().chain(spec.tags()).cloned().collect();
+
+            if !filter.selects(&filter_path(&path, &spec.name), &spec_tags) {
+                spec.filter_out();
+            }
+        }
+
+        self.nested = self
+            .nested
+            .into_iter()
+            .map(|suite| suite.select(filter, &path, &tags))
+            .collect();
+        self
+    }
+
+    /// Whether this suite, or any spec or suite in it, is focused with `only`.
+    pub(crate) fn has_focus(&self) -> bool {
+        self.details.only
+            || self.specs.iter().any(|spec| spec.is_only())
+            || self.nested.iter().any(|suite| suite.has_focus())
+    }
+
+    /// Skip the specs that aren't focused, unless they're in a focused suite, in this suite and
+    /// the suites nested in it. `focused` is whether a suite that this one is nested in is
+    /// focused.
+    pub(crate) fn focus(mut self, focused: bool) -> FinalizedSuite {
+        let focused = focused || self.details.only;
+
+        if !focused {
+            self.specs = self
+                .specs
+                .into_iter()
+                .map(|spec| spec.unfocused())
+                .collect();
+        }
+
+        self.nested = self
+            .nested
+            .into_iter()
+            .map(|suite| suite.focus(focused))
+            .collect();
+        self
+    }
+
+    /// Run the specs that were skipped, in this suite and the suites nested in it.
+    pub(crate) fn unskip(mut self) -> FinalizedSuite {
+        self.details.skip = false;
+        self.specs = self.specs.into_iter().map(|spec| spec.unskip()).collect();
+        self.nested = self
+            .nested
+            .into_iter()
+            .map(|suite| suite.unskip())
+            .collect();
+        self
+    }
+
+    pub(crate) fn run_with_reporter(
         self,
         output: &mut ReporterOutput,
         reporter: &mut dyn StartedReporter,
-        depth: usize,
     ) -> ReportResult<SuiteOutcome> {
-        let Self {
-            details,
-            specs,
-            nested,
-            ..
-        } = self;
+        let _hook = PanicHookGuard::install();
+
+        let finished =
+            self.inherit(None, None)
+                .run_hooked(output, reporter, None, &EachHooks::default())?;
+
+        Ok(SuiteOutcome::Finished(finished))
+    }
+
+    /// Give the suites that don't have a timeout or a number of retries of their own the ones of
+    /// the suite that they're nested in.
+    fn inherit(mut self, timeout: Option<Duration>, retries: Option<usize>) -> FinalizedSuite {
+        let timeout = self.details.timeout.or(timeout);
+        let retries = self.details.retries.or(retries);
+
+        self.details.timeout = timeout;
+        self.details.retries = retries;
+        self.nested = self
+            .nested
+            .into_iter()
+            .map(|suite| suite.inherit(timeout, retries))
+            .collect();
+        self
+    }
+
+    /// Run the suite, with its hooks, reporting it as it goes. `blocked` is the failure of a
+    /// `before_all` hook in a suite that this one is nested in: none of the hooks or specs in
+    /// this suite run, and every spec fails with it.
+    fn run_hooked(
+        mut self,
+        output: &mut ReporterOutput,
+        reporter: &mut dyn StartedReporter,
+        blocked: Option<HookFailure>,
+        each: &EachHooks,
+    ) -> ReportResult<SuiteFinished> {
+        let mut reported = Reported {
+            output: output.for_suite(self.specs.len()),
+            reporter,
+        };
+
+        reported
+            .reporter
+            .start_suite(&mut reported.output, &self.details)?;
 
-        let mut suite_output = output.for_suite(specs.len());
+        let finished = self.run(blocked, each, &mut reported)?;
+
+        reported
+            .reporter
+            .end_suite(&mut reported.output, &finished)?;
+
+        Ok(finished)
+    }
+
+    /// Run the suite like `run_hooked`, without a reporter, running its nested suites on the
+    /// spare threads in `budget`.
+    fn execute(
+        mut self,
+        blocked: Option<HookFailure>,
+        each: &EachHooks,
+        budget: &ThreadBudget,
+    ) -> SuiteRecord {
+        let mut recorded = Recorded {
+            budget,
+            specs: vec![],
+            nested: vec![],
+        };
+
+        let finished = match self.run(blocked, each, &mut recorded) {
+            Ok(finished) => finished,
+            Err(never) => match never {},
+        };
+
+        SuiteRecord {
+            details: self.details,
+            specs: recorded.specs,
+            nested: recorded.nested,
+            finished,
+        }
+    }
+
+    /// Run the suite's `before_all` hooks, its specs, its nested suites and its `after_all`
+    /// hooks, telling `run` about each spec and leaving the nested suites to it. `each` is the
+    /// `before_each` and `after_each` hooks of the suites that this one is nested in.
+    fn run<R: SuiteRun>(
+        &mut self,
+        blocked: Option<HookFailure>,
+        each: &EachHooks,
+        run: &mut R,
+    ) -> Result<SuiteFinished, R::Error> {
+        let selected = self.spec_count() > 0;
+        let specs = std::mem::take(&mut self.specs);
+        let nested = std::mem::take(&mut self.nested);
+        let hooks = Arc::new(Mutex::new(std::mem::take(&mut self.hooks)));
+        let details = &self.details;
 
         let suite_name = details.suite_name();
         let precision = details.precision;
+        // A suite whose specs were all filtered out doesn't need its hooks
+        let run_hooks = !details.skip && blocked.is_none() && selected;
 
-        reporter.start_suite(&mut suite_output, &details)?;
+        let each = if run_hooks {
+            each.nested(suite_name, hooks.clone())
+        } else {
+            each.clone()
+        };
 
         let mut results = BuildSuiteResult::new(suite_name);
 
         let suite_start = Instant::now();
 
+        let blocked = if run_hooks {
+            lock(&hooks).run(HookKind::BeforeAll, suite_name).err()
+        } else {
+            blocked
+        };
+
         for (i, spec) in specs.into_iter().enumerate() {
             let info = ReporterSpecInfo {
                 suite_name: suite_name.to_string(),
                 name: spec.name.clone(),
                 number: i,
+                only: spec.is_only(),
+                filtered: spec.is_filtered(),
             };
 
-            if details.skip {
-                reporter.end_test(&mut suite_output, info.skipped(precision))?;
-            } else {
-                let start = Instant::now();
-                // reporter.start_test(&mut suite_output, &info)?;
-
-                let finished = spec.run(suite_name);
-
-                let duration = DurationWithPrecision::new(start.elapsed(), precision);
-                // let done_info = info.done(DurationWithPrecision::new(start.elapsed(), precision));
-
-                match &finished.result {
-                    SpecStatus::Success => {
-                        reporter.end_test(
-                            &mut suite_output,
-                            info.done(SpecStatus::Success, duration),
-                        )?;
-                    }
-                    SpecStatus::Failure(error) => {
-                        reporter.end_test(
-                            &mut suite_output,
-                            info.done(SpecStatus::Failure(error.clone()), duration),
-                        )?;
-                    }
-                    SpecStatus::Skipped => {
-                        reporter.end_test(
-                            &mut suite_output,
-                            info.done(SpecStatus::Skipped, duration),
-                        )?;
-                    }
-                }
-
-                results.add_finished(finished);
+            run.start_test(&info)?;
+
+            if details.skip && !spec.is_filtered() {
+                let done = info.skipped(precision);
+                run.end_test(info, done)?;
+                continue;
             }
+
+            let start = Instant::now();
+            let finished = run_in_suite(spec, details, blocked.as_ref(), &each);
+            let duration = DurationWithPrecision::new(start.elapsed(), precision);
+
+            let done = info.done(finished.result.clone(), duration, finished.attempts.clone());
+            run.end_test(info, done)?;
+            results.add_finished(finished);
         }
 
-        let mut output = suite_output.child();
+        for finished in run.run_nested(nested, blocked, &each)? {
+            results.add_suite(finished);
+        }
 
-        for suite in nested {
-            println!("{:#?}", suite);
-            let mut output = output.for_nested();
-            suite.run_with_reporter(&mut output, reporter, depth + 1)?;
+        if run_hooks {
+            if let Err(failure) = lock(&hooks).run(HookKind::AfterAll, suite_name) {
+                results.add_hook_failure(failure);
+            }
         }
 
-        drop(output);
+        Ok(results.finish(DurationWithPrecision::new(suite_start.elapsed(), precision)))
+    }
+}
+
+/// Where a suite's run goes as it happens: straight to a reporter when suites run one at a time,
+/// or into a `SuiteRecord` when they run in parallel.
+trait SuiteRun {
+    type Error;
+
+    fn start_test(&mut self, info: &ReporterSpecInfo) -> Result<(), Self::Error>;
+
+    fn end_test(&mut self, info: ReporterSpecInfo, done: SpecInfo) -> Result<(), Self::Error>;
 
-        let finished = results.finish(DurationWithPrecision::new(suite_start.elapsed(), precision));
-        reporter.end_suite(&mut suite_output, &finished)?;
+    /// Run the suites nested in the one that's running, after its specs.
+    fn run_nested(
+        &mut self,
+        nested: Vec<FinalizedSuite>,
+        blocked: Option<HookFailure>,
+        each: &EachHooks,
+    ) -> Result<Vec<SuiteFinished>, Self::Error>;
+}
+
+struct Reported<'o, 'r> {
+    output: SuiteOutput<'o>,
+    reporter: &'r mut dyn StartedReporter,
+}
+
+impl SuiteRun for Reported<'_, '_> {
+    type Error = ReportError;
+
+    fn start_test(&mut self, info: &ReporterSpecInfo) -> ReportResult {
+        self.reporter.start_test(&mut self.output, info)
+    }
+
+    fn end_test(&mut self, _info: ReporterSpecInfo, done: SpecInfo) -> ReportResult {
+        self.reporter.end_test(&mut self.output, done)
+    }
+
+    fn run_nested(
+        &mut self,
+        nested: Vec<FinalizedSuite>,
+        blocked: Option<HookFailure>,
+        each: &EachHooks,
+    ) -> ReportResult<Vec<SuiteFinished>> {
+        let Self { output, reporter } = self;
+        let mut output = output.child();
 
-        if depth == 0 {
-            reporter.finish(
-                &mut suite_output,
-                FullSuiteInfo {
-                    duration: DurationWithPrecision::new(suite_start.elapsed(), precision),
-                },
-            )?;
+        nested
+            .into_iter()
+            .map(|suite| {
+                suite.run_hooked(&mut output.for_nested(), *reporter, blocked.clone(), each)
+            })
+            .collect()
+    }
+}
+
+struct Recorded<'b> {
+    budget: &'b ThreadBudget,
+    specs: Vec<(ReporterSpecInfo, SpecInfo)>,
+    nested: Vec<SuiteRecord>,
+}
+
+impl SuiteRun for Recorded<'_> {
+    type Error = Infallible;
+
+    fn start_test(&mut self, _info: &ReporterSpecInfo) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    fn end_test(&mut self, info: ReporterSpecInfo, done: SpecInfo) -> Result<(), Infallible> {
+        self.specs.push((info, done));
+        Ok(())
+    }
+
+    fn run_nested(
+        &mut self,
+        nested: Vec<FinalizedSuite>,
+        blocked: Option<HookFailure>,
+        each: &EachHooks,
+    ) -> Result<Vec<SuiteFinished>, Infallible> {
+        let budget = self.budget;
+
+        self.nested = budget.map(nested, |suite| suite.execute(blocked.clone(), each, budget));
+
+        Ok(self
+            .nested
+            .iter()
+            .map(|suite| suite.finished.clone())
+            .collect())
+    }
+}
+
+/// Run one of a suite's specs, unless it's filtered out, it's skipped, or a `before_all` hook that
+/// it depends on failed.
+fn run_in_suite(
+    spec: Spec,
+    details: &SuiteDetails,
+    blocked: Option<&HookFailure>,
+    each: &EachHooks,
+) -> FinishedSpec {
+    let suite_name = details.suite_name();
+
+    if spec.is_filtered() {
+        FinishedSpec::filtered(suite_name, spec.name)
+    } else if spec.is_skipped() {
+        FinishedSpec::skipped(suite_name, spec.name)
+    } else if let Some(failure) = blocked {
+        FinishedSpec::hook_failed(suite_name, spec.name, failure.clone())
+    } else {
+        run_spec(spec, details, each)
+    }
+}
+
+/// Run a spec, and run it again while it fails or panics, up to its number of retries. A spec
+/// that passes on a retry is flaky. A failing hook or a timeout isn't retried.
+fn run_spec(mut spec: Spec, details: &SuiteDetails, each: &EachHooks) -> FinishedSpec {
+    let retries = spec.retries().or(details.retries).unwrap_or(0);
+    let mut attempts: Vec<SpecAttempt> = vec![];
+
+    loop {
+        let start = Instant::now();
+        let status = attempt(&mut spec, details, each);
+        let duration = DurationWithPrecision::new(start.elapsed(), details.precision);
+
+        let retry = attempts.len() < retries
+            && matches!(status, SpecStatus::Failure(_) | SpecStatus::Panicked(_));
+
+        attempts.push(SpecAttempt::new(status, duration));
+
+        if !retry {
+            break;
         }
+    }
 
-        Ok(SuiteOutcome::Finished(finished))
+    let status = match attempts.last().map(|attempt| &attempt.status) {
+        Some(SpecStatus::Success) if attempts.len() > 1 => SpecStatus::Flaky,
+        Some(status) => status.clone(),
+        None => unreachable!("a spec ran without any attempts"),
+    };
+
+    FinishedSpec::attempted(details.suite_name(), spec.name, status, attempts)
+}
+
+/// Run a spec once, between the `before_each` and `after_each` hooks of its suite and the suites
+/// that it's nested in. If a `before_each` fails, the spec doesn't run, and only the `after_each`
+/// hooks of the suites whose `before_each` hooks passed run. A failing `after_each` only fails a
+/// spec that passed, so that the spec's own failure is the one that's reported.
+fn attempt(spec: &mut Spec, details: &SuiteDetails, each: &EachHooks) -> SpecStatus {
+    let (passed, failure) = each.before_each();
+
+    let status = match failure {
+        Some(failure) => SpecStatus::HookFailure(failure),
+        None => spec.attempt(details.timeout, details.precision),
+    };
+
+    match each.after_each(passed) {
+        Some(failure) if matches!(status, SpecStatus::Success) => SpecStatus::HookFailure(failure),
+        _ => status,
     }
 }
@@ -0,0 +1,121 @@
+use std::{
+    fmt::Debug,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    spec::MatchResult,
+    spec_result::{HookFailure, HookKind},
+};
+
+use super::lock;
+
+/// What a hook can return: nothing, or the result of an assertion.
+pub trait HookResult {
+    fn into_result(self) -> MatchResult;
+}
+
+impl HookResult for () {
+    fn into_result(self) -> MatchResult {
+        Ok(())
+    }
+}
+
+impl HookResult for MatchResult {
+    fn into_result(self) -> MatchResult {
+        self
+    }
+}
+
+/// A hook that has already been given its suite's state.
+pub(crate) type HookFn = Box<dyn FnMut() -> MatchResult + Send>;
+
+/// The hooks of a single suite. A suite's `before_all` and `after_all` hooks run once, around its
+/// specs and every suite nested in it, while `before_each` and `after_each` run around each of
+/// those specs, outside the hooks of the suites that they're nested in.
+#[derive(Default)]
+pub(crate) struct Hooks {
+    before_all: Vec<HookFn>,
+    before_each: Vec<HookFn>,
+    after_each: Vec<HookFn>,
+    after_all: Vec<HookFn>,
+}
+
+impl Debug for Hooks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Hooks")
+            .field("before_all", &self.before_all.len())
+            .field("before_each", &self.before_each.len())
+            .field("after_each", &self.after_each.len())
+            .field("after_all", &self.after_all.len())
+            .finish()
+    }
+}
+
+impl Hooks {
+    fn of_kind(&mut self, kind: HookKind) -> &mut Vec<HookFn> {
+        match kind {
+            HookKind::BeforeAll => &mut self.before_all,
+            HookKind::BeforeEach => &mut self.before_each,
+            HookKind::AfterEach => &mut self.after_each,
+            HookKind::AfterAll => &mut self.after_all,
+        }
+    }
+
+    pub(crate) fn add(&mut self, kind: HookKind, hook: HookFn) {
+        self.of_kind(kind).push(hook);
+    }
+
+    /// Run the hooks of one kind in the order they were added, stopping at the first one that
+    /// fails.
+    pub(crate) fn run(&mut self, kind: HookKind, suite_name: &str) -> Result<(), HookFailure> {
+        for hook in self.of_kind(kind).iter_mut() {
+            if let Err(failure) = hook() {
+                return Err(HookFailure::new(kind, suite_name.to_string(), failure));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The `before_each` and `after_each` hooks that run around a spec: those of its own suite and of
+/// every suite that it's nested in, with their suite's names, outermost first.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct EachHooks(Vec<(String, Arc<Mutex<Hooks>>)>);
+
+impl EachHooks {
+    /// The hooks around the specs of a suite named `suite_name`, nested in the suite whose hooks
+    /// these are.
+    pub(crate) fn nested(&self, suite_name: &str, hooks: Arc<Mutex<Hooks>>) -> EachHooks {
+        let mut suites = self.0.clone();
+        suites.push((suite_name.to_string(), hooks));
+        EachHooks(suites)
+    }
+
+    /// Run the `before_each` hooks, outermost first, stopping at the first one that fails. Returns
+    /// the number of suites whose hooks all passed, and the failure.
+    pub(crate) fn before_each(&self) -> (usize, Option<HookFailure>) {
+        for (passed, (suite_name, hooks)) in self.0.iter().enumerate() {
+            if let Err(failure) = lock(hooks).run(HookKind::BeforeEach, suite_name) {
+                return (passed, Some(failure));
+            }
+        }
+
+        (self.0.len(), None)
+    }
+
+    /// Run the `after_each` hooks of the outermost `suites` suites, innermost first. They all run,
+    /// even if one fails, and the first failure is returned.
+    pub(crate) fn after_each(&self, suites: usize) -> Option<HookFailure> {
+        let mut first = None;
+
+        for (suite_name, hooks) in self.0[..suites].iter().rev() {
+            if let Err(failure) = lock(hooks).run(HookKind::AfterEach, suite_name) {
+                first = first.or(Some(failure));
+            }
+        }
+
+        first
+    }
+}
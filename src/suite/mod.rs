@@ -1,14 +1,28 @@
 pub mod described;
+pub mod filter;
 pub mod finalized;
+pub mod hooks;
 pub mod mutable;
+mod parallel;
 pub mod state;
 pub mod traits;
 
 use derive_new::new;
-use std::{fmt::Display, ops::Deref, time::Duration};
+use std::{
+    fmt::Display,
+    ops::Deref,
+    sync::{Mutex, MutexGuard, PoisonError},
+    time::Duration,
+};
 
 pub use traits::Suite;
 
+/// Lock a suite's state. A spec that panicked while it held the lock has already been reported,
+/// so the state is still usable.
+pub(crate) fn lock<T>(state: &Mutex<T>) -> MutexGuard<'_, T> {
+    state.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
 #[derive(Debug, Copy, Clone)]
 pub enum DurationPrecision {
     Micros,
@@ -34,6 +48,12 @@ pub struct DurationWithPrecision {
     precision: DurationPrecision,
 }
 
+impl DurationWithPrecision {
+    pub fn precision(&self) -> DurationPrecision {
+        self.precision
+    }
+}
+
 impl Deref for DurationWithPrecision {
     type Target = Duration;
 
@@ -65,6 +85,18 @@ impl Display for DurationWithPrecision {
     }
 }
 
+/// What reporters know about a run before any suite starts.
+pub struct StartInfo {
+    /// The number of specs that will be reported, including skipped ones but not filtered ones.
+    pub test_count: usize,
+    /// The number of specs that were filtered out of the run. The ones that the harness's
+    /// arguments filter out aren't reported at all, while the ones that a `Filter` leaves out are
+    /// reported as `SpecStatus::Filtered`.
+    pub filtered_out: usize,
+}
+
 pub struct FullSuiteInfo {
     pub duration: DurationWithPrecision,
+    /// Whether a spec or suite in the run was focused with `only`, so that the others were skipped.
+    pub focused: bool,
 }
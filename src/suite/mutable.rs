@@ -1,12 +1,17 @@
 use std::{
     cell::RefCell,
-    cell::RefMut,
-    fmt::Debug,
-    rc::{Rc, Weak},
+    error::Error,
+    fmt::{Debug, Display},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, PoisonError, Weak,
+    },
 };
 
+use veritas::{Described, Failure};
+
 use crate::{
-    reporter::StartedReporter, spec::TypedIt, spec::TypedMutableIt, spec::TypedSpec,
+    reporter::StartedReporter, spec::TypedMutableIt, spec::TypedSpec, spec_result::HookKind,
     test::RunnableSuite, ReportResult, Reporter, ReporterOutput, Spec, SpecReporter, SuiteExt,
     SuiteOutcome,
 };
@@ -14,85 +19,158 @@ use crate::{
 use super::{
     described::{DescribedSuite, SuiteDetails},
     finalized::FinalizedSuite,
+    hooks::{HookResult, Hooks},
+    lock,
 };
 
+thread_local! {
+    /// Where to record the states that the spec running on this thread locks, if it runs on a
+    /// thread of its own.
+    static HELD: RefCell<Option<HeldStates>> = const { RefCell::new(None) };
+}
+
+/// The mutable states locked by a spec that runs on a thread of its own. If the spec times out,
+/// its thread keeps them locked, so they're abandoned: the specs and hooks that use them later
+/// fail instead of waiting for them forever.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct HeldStates(Arc<Mutex<Vec<Arc<AtomicBool>>>>);
+
+impl HeldStates {
+    /// Record the states that are locked on this thread from now on.
+    pub(crate) fn track(&self) {
+        HELD.with(|held| *held.borrow_mut() = Some(self.clone()));
+    }
+
+    /// Abandon every state that was locked, since the spec holding them won't let them go.
+    pub(crate) fn abandon(&self) {
+        for abandoned in lock(&self.0).iter() {
+            abandoned.store(true, Ordering::SeqCst);
+        }
+    }
+
+    fn hold(abandoned: &Arc<AtomicBool>) {
+        HELD.with(|held| {
+            if let Some(held) = &*held.borrow() {
+                lock(&held.0).push(abandoned.clone());
+            }
+        });
+    }
+}
+
+/// The error of a suite whose mutable state is still locked by a spec that timed out, so it
+/// can't be handed back once the suite has run.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct StateAbandoned;
+
+impl Display for StateAbandoned {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "the suite's state is still locked by a spec that timed out"
+        )
+    }
+}
+
+impl Error for StateAbandoned {}
+
 #[derive(Debug)]
 pub struct WeakRef<T> {
-    cell: Weak<RefCell<T>>,
+    cell: Weak<Mutex<T>>,
+    abandoned: Arc<AtomicBool>,
 }
 
 impl<T> Clone for WeakRef<T> {
     fn clone(&self) -> Self {
         WeakRef {
             cell: self.cell.clone(),
+            abandoned: self.abandoned.clone(),
         }
     }
 }
 
 impl<T> WeakRef<T> {
-    pub fn mut_ref<U>(&self, callback: impl Fn(&mut T) -> U) -> U {
-        let cell: Rc<RefCell<T>> = self.cell.upgrade().unwrap();
-        let mut value: RefMut<_> = cell.borrow_mut();
+    /// Call `callback` with the state, or fail if a spec that timed out still has it locked.
+    pub fn mut_ref<U>(&self, callback: impl FnOnce(&mut T) -> U) -> Result<U, Failure> {
+        // Recorded before locking, so that a spec that times out while waiting for the state
+        // abandons it too
+        HeldStates::hold(&self.abandoned);
+
+        if self.abandoned.load(Ordering::SeqCst) {
+            return Err(Failure::new(
+                "the suite's state was abandoned",
+                Described::new(
+                    "the state",
+                    "still locked by a spec that timed out".to_string(),
+                ),
+            ));
+        }
+
+        let cell: Arc<Mutex<T>> = self.cell.upgrade().unwrap();
+        let mut value = lock(&cell);
 
-        callback(&mut *value)
+        Ok(callback(&mut *value))
     }
 }
 
 #[derive(Debug)]
 pub struct UniqueStrongRef<T> {
-    cell: Rc<RefCell<T>>,
+    cell: Arc<Mutex<T>>,
+    abandoned: Arc<AtomicBool>,
 }
 
 impl<T> UniqueStrongRef<T> {
     pub(crate) fn new(value: T) -> UniqueStrongRef<T> {
         UniqueStrongRef {
-            cell: Rc::new(RefCell::new(value)),
+            cell: Arc::new(Mutex::new(value)),
+            abandoned: Arc::new(AtomicBool::new(false)),
         }
     }
 
-    pub fn unwrap(self) -> T {
-        let inner = match Rc::try_unwrap(self.cell) {
-            Ok(inner) => inner,
-            Err(_) => unreachable!(),
-        };
-
-        inner.into_inner()
+    /// The state, unless a spec that timed out still has it.
+    pub fn unwrap(self) -> Result<T, StateAbandoned> {
+        match Arc::try_unwrap(self.cell) {
+            Ok(inner) => Ok(inner.into_inner().unwrap_or_else(PoisonError::into_inner)),
+            Err(_) => Err(StateAbandoned),
+        }
     }
 
     pub(crate) fn weak(&self) -> WeakRef<T> {
         WeakRef {
-            cell: Rc::downgrade(&self.cell),
+            cell: Arc::downgrade(&self.cell),
+            abandoned: self.abandoned.clone(),
         }
     }
 }
 
 pub struct SuiteWithMutableState<T>
 where
-    T: Debug + 'static,
+    T: Debug + Send + 'static,
 {
     details: SuiteDetails,
     reporter: Option<Box<dyn StartedReporter>>,
     specs: Vec<Spec>,
     state: UniqueStrongRef<T>,
     nested: Vec<FinalizedSuite>,
+    hooks: Hooks,
 }
 
 impl<T> Debug for SuiteWithMutableState<T>
 where
-    T: Debug + 'static,
+    T: Debug + Send + 'static,
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("SuiteWithMutableState")
             .field("details", &self.details)
             .field("specs", &self.specs)
             .field("nested", &self.nested)
+            .field("hooks", &self.hooks)
             .finish()
     }
 }
 
 impl<T> RunnableSuite for SuiteWithMutableState<T>
 where
-    T: Debug + 'static,
+    T: Debug + Send + 'static,
 {
     type State = T;
 
@@ -101,12 +179,12 @@ where
         let reporter = self
             .reporter
             .unwrap_or_else(|| Box::new(SpecReporter.start()));
-        let finalized = FinalizedSuite::new(self.details, self.specs, self.nested);
+        let finalized = FinalizedSuite::new(self.details, self.specs, self.nested, self.hooks);
         let state = self.state;
 
         let run = finalized.top(reporter).run_with(output)?;
         // println!("FINALIZE");
-        let state = state.unwrap();
+        let state = state.unwrap()?;
 
         Ok(run.with_state(state))
     }
@@ -114,41 +192,38 @@ where
 
 impl<T> SuiteExt for SuiteWithMutableState<T>
 where
-    T: Debug + 'static,
+    T: Debug + Send + 'static,
 {
     fn details_mut(&mut self) -> &mut SuiteDetails {
         &mut self.details
     }
 }
 
-impl<T> Into<FinalizedSuite> for SuiteWithMutableState<T>
+impl<T> From<SuiteWithMutableState<T>> for FinalizedSuite
 where
-    T: Debug + 'static,
+    T: Debug + Send + 'static,
 {
-    fn into(self) -> FinalizedSuite {
-        let has_only = self.specs.iter().any(|s| s.is_only());
-        let Self {
+    fn from(suite: SuiteWithMutableState<T>) -> FinalizedSuite {
+        let SuiteWithMutableState {
             details,
-            mut specs,
+            specs,
             nested,
+            hooks,
             ..
-        } = self;
+        } = suite;
 
-        if has_only {
-            specs = specs.into_iter().map(|s| s.in_only_suite()).collect();
-        }
-
-        FinalizedSuite::new(details, specs, nested)
+        FinalizedSuite::new(details, specs, nested, hooks)
     }
 }
 
 impl<T> SuiteWithMutableState<T>
 where
-    T: Debug + 'static,
+    T: Debug + Send + 'static,
 {
     pub(crate) fn top(
         details: SuiteDetails,
         reporter: Option<Box<dyn StartedReporter>>,
+        hooks: Hooks,
         state: T,
     ) -> SuiteWithMutableState<T> {
         SuiteWithMutableState {
@@ -157,9 +232,47 @@ where
             specs: vec![],
             state: UniqueStrongRef::new(state),
             nested: vec![],
+            hooks,
         }
     }
 
+    fn hook<R: HookResult>(
+        mut self,
+        kind: HookKind,
+        mut hook: impl FnMut(&mut T) -> R + Send + 'static,
+    ) -> Self {
+        let state = self.state.weak();
+
+        self.hooks.add(
+            kind,
+            Box::new(move || state.mut_ref(|value| hook(value).into_result())?),
+        );
+        self
+    }
+
+    /// Run `hook` once, before any of the suite's specs or nested suites.
+    pub fn before_all<R: HookResult>(self, hook: impl FnMut(&mut T) -> R + Send + 'static) -> Self {
+        self.hook(HookKind::BeforeAll, hook)
+    }
+
+    /// Run `hook` before each of the suite's specs, and of the specs of the suites nested in it.
+    pub fn before_each<R: HookResult>(
+        self,
+        hook: impl FnMut(&mut T) -> R + Send + 'static,
+    ) -> Self {
+        self.hook(HookKind::BeforeEach, hook)
+    }
+
+    /// Run `hook` after each of the suite's specs, and of the specs of the suites nested in it.
+    pub fn after_each<R: HookResult>(self, hook: impl FnMut(&mut T) -> R + Send + 'static) -> Self {
+        self.hook(HookKind::AfterEach, hook)
+    }
+
+    /// Run `hook` once, after the suite's specs and nested suites.
+    pub fn after_all<R: HookResult>(self, hook: impl FnMut(&mut T) -> R + Send + 'static) -> Self {
+        self.hook(HookKind::AfterAll, hook)
+    }
+
     pub fn spec(mut self, spec: TypedSpec<WeakRef<T>>) -> SuiteWithMutableState<T> {
         let state = self.state.weak();
 
@@ -167,10 +280,10 @@ where
         self
     }
 
-    pub fn describe(
+    pub fn describe<R>(
         mut self,
         name: impl Into<String>,
-        callback: impl FnOnce(&mut TypedMutableIt<T>),
+        callback: impl FnOnce(&mut TypedMutableIt<T>) -> R,
     ) -> Self {
         let mut suite = DescribedSuite::new(name).state(self.state.weak());
 
@@ -185,11 +298,15 @@ where
         self
     }
 
-    pub fn specs(self, callback: impl FnOnce(&mut TypedIt<Rc<RefCell<T>>>)) -> Self {
-        let mut it = TypedIt::<Rc<RefCell<T>>>::new();
+    pub fn specs<R>(mut self, callback: impl FnOnce(&mut TypedMutableIt<T>) -> R) -> Self {
+        let mut it = TypedMutableIt::<T>::new();
 
         callback(&mut it);
 
+        for spec in it.specs() {
+            self = self.spec(spec);
+        }
+
         self
     }
 
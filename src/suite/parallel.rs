@@ -0,0 +1,182 @@
+use std::{
+    collections::BTreeMap,
+    panic::{self, AssertUnwindSafe},
+    sync::{mpsc, Mutex},
+    thread::{self, ScopedJoinHandle},
+};
+
+/// The threads that suites can run on, besides the ones that are already running suites. A suite
+/// that can't get a thread runs on the thread that wanted to start it, so a run never uses more
+/// threads than it was given, and never waits for one.
+pub(crate) struct ThreadBudget {
+    spare: Mutex<usize>,
+}
+
+enum Job<'scope, R> {
+    Done(R),
+    Running(ScopedJoinHandle<'scope, R>),
+}
+
+impl ThreadBudget {
+    /// A budget of `threads` threads, counting the one that the run starts on.
+    pub(crate) fn new(threads: usize) -> ThreadBudget {
+        ThreadBudget {
+            spare: Mutex::new(threads.saturating_sub(1)),
+        }
+    }
+
+    fn take(&self) -> bool {
+        let mut spare = self.spare.lock().unwrap_or_else(|err| err.into_inner());
+
+        if *spare == 0 {
+            false
+        } else {
+            *spare -= 1;
+            true
+        }
+    }
+
+    fn give_back(&self) {
+        *self.spare.lock().unwrap_or_else(|err| err.into_inner()) += 1;
+    }
+
+    /// Run `job` for each item, on a spare thread if there is one, and return the results in the
+    /// same order as the items.
+    pub(crate) fn map<T, R>(&self, items: Vec<T>, job: impl Fn(T) -> R + Sync) -> Vec<R>
+    where
+        T: Send,
+        R: Send,
+    {
+        let job = &job;
+
+        thread::scope(|scope| {
+            let jobs: Vec<Job<R>> = items
+                .into_iter()
+                .map(|item| {
+                    if self.take() {
+                        Job::Running(scope.spawn(move || {
+                            let result = job(item);
+                            self.give_back();
+                            result
+                        }))
+                    } else {
+                        Job::Done(job(item))
+                    }
+                })
+                .collect();
+
+            jobs.into_iter()
+                .map(|job| match job {
+                    Job::Done(result) => result,
+                    Job::Running(handle) => handle
+                        .join()
+                        .unwrap_or_else(|payload| panic::resume_unwind(payload)),
+                })
+                .collect()
+        })
+    }
+
+    /// Run `job` for each item like `map` does, and hand each result to `each` on the calling
+    /// thread as soon as it and the results before it are ready. The items are started from a
+    /// thread of their own, which takes the calling thread's place in the budget while the calling
+    /// thread waits for results. If `each` fails, no more items are started.
+    pub(crate) fn for_each<T, R, E>(
+        &self,
+        items: Vec<T>,
+        job: impl Fn(T) -> R + Sync,
+        mut each: impl FnMut(R) -> Result<(), E>,
+    ) -> Result<(), E>
+    where
+        T: Send,
+        R: Send,
+    {
+        let job = &job;
+        let count = items.len();
+        let (sender, receiver) = mpsc::channel();
+
+        thread::scope(|scope| {
+            scope.spawn(move || {
+                for (index, item) in items.into_iter().enumerate() {
+                    let sender = sender.clone();
+                    let run = move || panic::catch_unwind(AssertUnwindSafe(|| job(item)));
+
+                    if self.take() {
+                        scope.spawn(move || {
+                            let result = run();
+                            self.give_back();
+                            let _ = sender.send((index, result));
+                        });
+                    } else if sender.send((index, run())).is_err() {
+                        return;
+                    }
+                }
+            });
+
+            // Results can arrive out of order, so they wait here until their turn
+            let mut ready = BTreeMap::new();
+
+            for next in 0..count {
+                let result = loop {
+                    if let Some(result) = ready.remove(&next) {
+                        break result;
+                    }
+
+                    // Every item sends its result, even if its job panics
+                    let (index, result) = receiver.recv().unwrap();
+                    ready.insert(index, result);
+                };
+
+                if let Err(err) =
+                    each(result.unwrap_or_else(|payload| panic::resume_unwind(payload)))
+                {
+                    drop(receiver);
+                    return Err(err);
+                }
+            }
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{thread, time::Duration};
+
+    use super::*;
+
+    #[test]
+    fn keeps_order() {
+        let budget = ThreadBudget::new(3);
+        let results = budget.map((0..8).collect(), |n: u64| {
+            thread::sleep(Duration::from_millis(8 - n));
+            n * 2
+        });
+
+        assert_eq!(results, vec![0, 2, 4, 6, 8, 10, 12, 14]);
+        assert_eq!(*budget.spare.lock().unwrap(), 2);
+    }
+
+    #[test]
+    fn hands_results_over_in_order() {
+        let budget = ThreadBudget::new(3);
+        let mut results = vec![];
+
+        budget
+            .for_each(
+                (0..8).collect(),
+                |n: u64| {
+                    thread::sleep(Duration::from_millis(8 - n));
+                    n * 2
+                },
+                |n| {
+                    results.push(n);
+                    Ok::<_, ()>(())
+                },
+            )
+            .unwrap();
+
+        assert_eq!(results, vec![0, 2, 4, 6, 8, 10, 12, 14]);
+        assert_eq!(*budget.spare.lock().unwrap(), 2);
+    }
+}
@@ -1,18 +1,24 @@
-use std::fmt::Debug;
+use std::{
+    fmt::Debug,
+    sync::{Arc, Mutex},
+};
 
 use crate::{
-    reporter::StartedReporter, spec::TypedIt, spec::TypedSpec, test::RunnableSuite, ReportResult,
-    Reporter, ReporterOutput, Spec, SpecReporter, SuiteExt, SuiteOutcome,
+    reporter::StartedReporter, spec::TypedIt, spec::TypedSpec, spec_result::HookKind,
+    test::RunnableSuite, ReportResult, Reporter, ReporterOutput, Spec, SpecReporter, SuiteExt,
+    SuiteOutcome,
 };
 
 use super::{
     described::{DescribedSuite, SuiteDetails},
     finalized::FinalizedSuite,
+    hooks::{HookResult, Hooks},
+    lock,
 };
 
 impl<T> RunnableSuite for SuiteWithState<T>
 where
-    T: Clone + Debug + 'static,
+    T: Clone + Debug + Send + 'static,
 {
     type State = T;
 
@@ -21,73 +27,75 @@ where
         let reporter = self
             .reporter
             .unwrap_or_else(|| Box::new(SpecReporter.start()));
-        let finalized = FinalizedSuite::new(self.details, self.specs, self.nested);
-        Ok(finalized.top(reporter).run_with(output)?.with_state(state))
+        let finalized = FinalizedSuite::new(self.details, self.specs, self.nested, self.hooks);
+        let outcome = finalized.top(reporter).run_with(output)?;
+        let state = lock(&state).clone();
+
+        Ok(outcome.with_state(state))
     }
 }
 
 pub struct SuiteWithState<T>
 where
-    T: Clone + 'static,
+    T: Clone + Send + 'static,
 {
     details: SuiteDetails,
     reporter: Option<Box<dyn StartedReporter>>,
     specs: Vec<Spec>,
-    state: T,
+    state: Arc<Mutex<T>>,
     nested: Vec<FinalizedSuite>,
+    hooks: Hooks,
 }
 
 impl<T> Debug for SuiteWithState<T>
 where
-    T: Clone + 'static,
+    T: Clone + Send + 'static,
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("SuiteWithState")
             .field("details", &self.details)
             .field("specs", &self.specs)
             .field("nested", &self.nested)
+            .field("hooks", &self.hooks)
             .finish()
     }
 }
 
 impl<T> SuiteExt for SuiteWithState<T>
 where
-    T: Clone + 'static,
+    T: Clone + Send + 'static,
 {
     fn details_mut(&mut self) -> &mut SuiteDetails {
         &mut self.details
     }
 }
 
-impl<T> Into<FinalizedSuite> for SuiteWithState<T>
+impl<T> From<SuiteWithState<T>> for FinalizedSuite
 where
-    T: Clone + 'static,
+    T: Clone + Send + 'static,
 {
-    fn into(self) -> FinalizedSuite {
-        let has_only = self.specs.iter().any(|s| s.is_only());
-        let Self {
+    fn from(suite: SuiteWithState<T>) -> FinalizedSuite {
+        let SuiteWithState {
             details,
-            mut specs,
+            specs,
             nested,
+            hooks,
             ..
-        } = self;
-
-        if has_only {
-            specs = specs.into_iter().map(|s| s.in_only_suite()).collect();
-        }
+        } = suite;
 
-        FinalizedSuite::new(details, specs, nested)
+        FinalizedSuite::new(details, specs, nested, hooks)
     }
 }
 
 impl<T> SuiteWithState<T>
 where
-    T: Clone + Debug + 'static,
+    T: Clone + Debug + Send + 'static,
 {
     pub(crate) fn new(
         details: SuiteDetails,
         reporter: Option<Box<dyn StartedReporter>>,
-        state: T,
+        hooks: Hooks,
+        state: Arc<Mutex<T>>,
     ) -> SuiteWithState<T> {
         SuiteWithState {
             details,
@@ -95,22 +103,63 @@ where
             specs: vec![],
             state,
             nested: vec![],
+            hooks,
         }
     }
 
+    /// Each spec gets its own copy of the state, as the suite's hooks have left it when the spec
+    /// starts.
     pub fn spec(mut self, spec: TypedSpec<T>) -> SuiteWithState<T> {
         let state = self.state.clone();
 
-        self.specs.push(spec.with_state(move || state.clone()));
+        self.specs
+            .push(spec.with_state(move || lock(&state).clone()));
         self
     }
 
-    pub fn describe(
+    fn hook<R: HookResult>(
+        mut self,
+        kind: HookKind,
+        mut hook: impl FnMut(&mut T) -> R + Send + 'static,
+    ) -> Self {
+        let state = self.state.clone();
+
+        self.hooks.add(
+            kind,
+            Box::new(move || hook(&mut lock(&state)).into_result()),
+        );
+        self
+    }
+
+    /// Run `hook` once, before any of the suite's specs or nested suites.
+    pub fn before_all<R: HookResult>(self, hook: impl FnMut(&mut T) -> R + Send + 'static) -> Self {
+        self.hook(HookKind::BeforeAll, hook)
+    }
+
+    /// Run `hook` before each of the suite's specs, and of the specs of the suites nested in it.
+    pub fn before_each<R: HookResult>(
+        self,
+        hook: impl FnMut(&mut T) -> R + Send + 'static,
+    ) -> Self {
+        self.hook(HookKind::BeforeEach, hook)
+    }
+
+    /// Run `hook` after each of the suite's specs, and of the specs of the suites nested in it.
+    pub fn after_each<R: HookResult>(self, hook: impl FnMut(&mut T) -> R + Send + 'static) -> Self {
+        self.hook(HookKind::AfterEach, hook)
+    }
+
+    /// Run `hook` once, after the suite's specs and nested suites.
+    pub fn after_all<R: HookResult>(self, hook: impl FnMut(&mut T) -> R + Send + 'static) -> Self {
+        self.hook(HookKind::AfterAll, hook)
+    }
+
+    pub fn describe<R>(
         mut self,
         name: impl Into<String>,
-        callback: impl FnOnce(&mut TypedIt<T>),
+        callback: impl FnOnce(&mut TypedIt<T>) -> R,
     ) -> Self {
-        let mut suite = DescribedSuite::new(name).state(self.state.clone());
+        let mut suite = DescribedSuite::new(name).shared_state(self.state.clone());
 
         let mut it = TypedIt::new();
         callback(&mut it);
@@ -123,11 +172,15 @@ where
         self
     }
 
-    pub fn specs(self, callback: impl FnOnce(&mut TypedIt<T>)) -> Self {
+    pub fn specs<R>(mut self, callback: impl FnOnce(&mut TypedIt<T>) -> R) -> Self {
         let mut it = TypedIt::<T>::new();
 
         callback(&mut it);
 
+        for spec in it.specs() {
+            self = self.spec(spec);
+        }
+
         self
     }
 
@@ -138,7 +191,8 @@ where
 
     pub fn nest(mut self, suite: DescribedSuite) -> Self {
         // for suite in suites.drain(..) {
-        self.nested.push(suite.state(self.state.clone()).into());
+        self.nested
+            .push(suite.shared_state(self.state.clone()).into());
         // }
 
         self
@@ -1,8 +1,8 @@
-use std::{fmt::Debug, io::BufWriter, path::PathBuf};
+use std::{fmt::Debug, io::BufWriter, path::PathBuf, time::Duration};
 
 use crate::{DurationPrecision, ReportResult, ReporterOutput, SuiteOutcome};
 
-use super::described::SuiteDetails;
+use super::{described::SuiteDetails, filter::Filter};
 
 // pub trait FinalizedSuiteTrait: Sized {}
 
@@ -49,6 +49,13 @@ pub trait SuiteExt: Sized + Debug {
         self
     }
 
+    /// Focus the suite: if anything in a run is focused, only the focused suites and specs run,
+    /// and everything else is skipped.
+    fn only(mut self) -> Self {
+        self.details_mut().only = true;
+        self
+    }
+
     fn export_to(mut self, path: impl Into<PathBuf>) -> Self {
         self.details_mut().export = Some(path.into());
         self
@@ -74,4 +81,46 @@ pub trait SuiteExt: Sized + Debug {
     fn in_seconds(self) -> Self {
         self.precision(DurationPrecision::Seconds)
     }
+
+    /// Run the suites nested in this one on up to `threads` threads at a time. The specs of a
+    /// single suite still run one after another, between that suite's hooks, and are reported in
+    /// the order they were declared once their suite has finished. Only the top-level suite's
+    /// setting is used, and a harness's `--test-threads` takes its place.
+    fn parallel(mut self, threads: usize) -> Self {
+        self.details_mut().threads = threads.max(1);
+        self
+    }
+
+    /// Fail a spec with `SpecStatus::TimedOut` if it runs for longer than `timeout`, and go on
+    /// with the next one. It applies to the specs of this suite and of the suites nested in it,
+    /// unless they set a timeout of their own. A spec that times out is left running on its own
+    /// thread, and any mutable state that it locked is abandoned: the specs and hooks that use it
+    /// afterwards fail.
+    fn timeout(mut self, timeout: Duration) -> Self {
+        self.details_mut().timeout = Some(timeout);
+        self
+    }
+
+    /// Run a failing or panicking spec again, up to `retries` times. A spec that passes on a retry
+    /// is reported as `SpecStatus::Flaky`. It applies to the specs of this suite and of the
+    /// suites nested in it, unless they set a number of retries of their own.
+    fn retries(mut self, retries: usize) -> Self {
+        self.details_mut().retries = Some(retries);
+        self
+    }
+
+    /// Give the suite `tags`, which the specs in it and in the suites nested in it have as well.
+    fn tagged(mut self, tags: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.details_mut()
+            .tags
+            .extend(tags.into_iter().map(Into::into));
+        self
+    }
+
+    /// Only run the specs that `filter` selects. The others are reported as
+    /// `SpecStatus::Filtered`. Only the top-level suite's filter is used.
+    fn filter(mut self, filter: Filter) -> Self {
+        self.details_mut().filter = Some(filter);
+        self
+    }
 }
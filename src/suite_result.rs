@@ -1,8 +1,13 @@
-use crate::{spec_result::FinishedSpec, suite::DurationWithPrecision};
+use crate::{
+    spec_result::{FinishedSpec, HookFailure},
+    suite::DurationWithPrecision,
+};
 
 pub struct BuildSuiteResult {
     name: String,
     tests: Vec<FinishedSpec>,
+    suites: Vec<SuiteFinished>,
+    hook_failures: Vec<HookFailure>,
 }
 
 impl BuildSuiteResult {
@@ -10,6 +15,8 @@ impl BuildSuiteResult {
         BuildSuiteResult {
             name: name.into(),
             tests: vec![],
+            suites: vec![],
+            hook_failures: vec![],
         }
     }
 
@@ -17,10 +24,20 @@ impl BuildSuiteResult {
         self.tests.push(result)
     }
 
+    pub fn add_suite(&mut self, result: SuiteFinished) {
+        self.suites.push(result)
+    }
+
+    pub fn add_hook_failure(&mut self, failure: HookFailure) {
+        self.hook_failures.push(failure)
+    }
+
     pub fn finish(self, duration: DurationWithPrecision) -> SuiteFinished {
         SuiteFinished {
             name: self.name,
             tests: self.tests,
+            suites: self.suites,
+            hook_failures: self.hook_failures,
             duration,
             state: (),
         }
@@ -47,12 +64,22 @@ impl<State> SuiteOutcome<State> {
             SuiteOutcome::Skipped => panic!("A skipped suite does not have state"),
         }
     }
+
+    /// The number of specs and hooks that failed. A skipped suite has no failures.
+    pub fn failures(&self) -> usize {
+        match self {
+            SuiteOutcome::Finished(finished) => finished.failures(),
+            SuiteOutcome::Skipped => 0,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct SuiteFinished<State = ()> {
     name: String,
     tests: Vec<FinishedSpec>,
+    suites: Vec<SuiteFinished>,
+    hook_failures: Vec<HookFailure>,
     duration: DurationWithPrecision,
     state: State,
 }
@@ -62,15 +89,49 @@ impl<State> SuiteFinished<State> {
         SuiteFinished {
             name: self.name,
             tests: self.tests,
+            suites: self.suites,
+            hook_failures: self.hook_failures,
             duration: self.duration,
             state,
         }
     }
 
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
     pub fn tests(&self) -> impl Iterator<Item = &FinishedSpec> {
         self.tests.iter()
     }
 
+    /// The results of the suites nested in this one.
+    pub fn suites(&self) -> impl Iterator<Item = &SuiteFinished> {
+        self.suites.iter()
+    }
+
+    /// The `after_all` hooks of this suite that failed. A failure in any other hook is reported
+    /// with the specs that it failed.
+    pub fn hook_failures(&self) -> &[HookFailure] {
+        &self.hook_failures
+    }
+
+    /// The number of specs and `after_all` hooks that failed, in this suite and every suite
+    /// nested in it.
+    pub fn failures(&self) -> usize {
+        let own = self
+            .tests
+            .iter()
+            .filter(|test| test.result.is_failure())
+            .count();
+
+        own + self.hook_failures.len()
+            + self
+                .suites
+                .iter()
+                .map(|suite| suite.failures())
+                .sum::<usize>()
+    }
+
     pub fn duration(&self) -> DurationWithPrecision {
         self.duration
     }
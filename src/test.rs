@@ -1,11 +1,17 @@
 pub use crate::reporter::{
-    json::JsonReporter,
+    json::{JsonReport, JsonReporter, JsonStatus, JSON_SCHEMA_VERSION},
+    junit::JunitReporter,
+    libtest::LibtestJsonReporter,
     output::ReporterOutput,
     spec::{MinimalReporter, SpecReporter},
+    tap::TapReporter,
     ReportResult, Reporter,
 };
-pub use crate::spec::Spec;
+pub use crate::spec::{MatchResult, Spec};
 pub use crate::suite::traits::{RunnableSuite, StateSuite, Suite, SuiteExt};
 pub use crate::{describe, describe_skip};
-pub use laboratory_expectations::{expect, Expect};
 pub use serde::{Deserialize, Serialize};
+pub use veritas::{
+    contains, eq, expect, ge, gt, is_err, is_none, is_ok, is_some, le, lt, matches, ne, Failure,
+    Matcher, MatcherExt,
+};
@@ -0,0 +1,103 @@
+use std::{
+    any::Any,
+    cell::{Cell, RefCell},
+    panic::{self, AssertUnwindSafe, PanicHookInfo},
+    sync::{Arc, Mutex},
+};
+
+use crate::spec_result::SpecPanic;
+
+type PanicHook = Box<dyn Fn(&PanicHookInfo<'_>) + Sync + Send + 'static>;
+
+thread_local! {
+    static CATCHING: Cell<bool> = const { Cell::new(false) };
+    static CAUGHT: RefCell<Option<SpecPanic>> = const { RefCell::new(None) };
+}
+
+fn payload_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Box<dyn Any>".to_string()
+    }
+}
+
+/// The hook that was installed before ours, and the number of guards that are keeping ours in
+/// place. Suites can run at the same time on different threads, so the first guard installs the
+/// hook and the last one puts the previous hook back.
+static INSTALLED: Mutex<Option<(usize, Arc<PanicHook>)>> = Mutex::new(None);
+
+/// Replaces the panic hook while a suite runs. Panics in specs are recorded, with their location,
+/// instead of being printed over the reporter's output. Any other panic goes to the hook that was
+/// there before, which is put back when the last guard is dropped.
+pub(crate) struct PanicHookGuard;
+
+impl PanicHookGuard {
+    pub(crate) fn install() -> PanicHookGuard {
+        let mut installed = INSTALLED.lock().unwrap_or_else(|err| err.into_inner());
+
+        match installed.as_mut() {
+            Some((guards, _)) => *guards += 1,
+            None => {
+                let previous = Arc::new(panic::take_hook());
+                let fallback = previous.clone();
+
+                panic::set_hook(Box::new(move |info| {
+                    if CATCHING.with(Cell::get) {
+                        let location = info.location().map(|location| location.to_string());
+                        let panic = SpecPanic::new(payload_message(info.payload()), location);
+
+                        CAUGHT.with(|caught| *caught.borrow_mut() = Some(panic));
+                    } else {
+                        fallback(info);
+                    }
+                }));
+
+                *installed = Some((1, previous));
+            }
+        }
+
+        PanicHookGuard
+    }
+}
+
+impl Drop for PanicHookGuard {
+    fn drop(&mut self) {
+        let mut installed = INSTALLED.lock().unwrap_or_else(|err| err.into_inner());
+
+        if let Some((guards, _)) = installed.as_mut() {
+            *guards -= 1;
+
+            // The hook can't be changed while the thread is panicking, so it's left in place.
+            // It passes panics on to the previous hook anyway.
+            if *guards > 0 || std::thread::panicking() {
+                return;
+            }
+        }
+
+        if let Some((_, previous)) = installed.take() {
+            // Dropping our hook drops its handle on the previous one, so it can be unwrapped
+            drop(panic::take_hook());
+
+            match Arc::try_unwrap(previous) {
+                Ok(previous) => panic::set_hook(previous),
+                Err(previous) => panic::set_hook(Box::new(move |info| previous(info))),
+            }
+        }
+    }
+}
+
+/// Run `f`, turning a panic into a `SpecPanic`.
+pub(crate) fn catch<T>(f: impl FnOnce() -> T) -> Result<T, SpecPanic> {
+    let catching = CATCHING.with(|flag| flag.replace(true));
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    CATCHING.with(|flag| flag.set(catching));
+
+    result.map_err(|payload| {
+        CAUGHT
+            .with(|caught| caught.borrow_mut().take())
+            .unwrap_or_else(|| SpecPanic::new(payload_message(&*payload), None))
+    })
+}
@@ -0,0 +1,27 @@
+use laboratory::test::*;
+use laboratory::SuiteWithState;
+
+fn add_one(n: i32) -> i32 {
+    n + 1
+}
+
+fn math() -> SuiteWithState<()> {
+    describe("math")
+        .suite(describe("add_one()").specs(|it| {
+            it.should("return 1", || expect(add_one(0)).to(eq(1)));
+            it.should("return 2", || expect(add_one(1)).to(eq(2)));
+        }))
+        .specs(|it| {
+            it.skip("not be run by default", || expect(add_one(0)).to(eq(1)));
+        })
+}
+
+fn strings() -> SuiteWithState<()> {
+    describe("strings").specs(|it| {
+        it.should("concatenate", || {
+            expect(format!("{}{}", "lab", "oratory")).to(eq("laboratory".to_string()))
+        });
+    })
+}
+
+laboratory::main!(math(), strings());
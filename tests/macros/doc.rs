@@ -5,7 +5,9 @@ mod tests {
     use spectrum_macros::doc;
 
     use spectrum::prelude::test::*;
-    use spectrum::{prelude::*, EmitBackendTrait, Style};
+    use spectrum::{
+        prelude::*, render::Nesting, Border, Column, EmitBackendTrait, Fill, Style, Table,
+    };
     use textwrap::dedent;
 
     #[test]
@@ -229,6 +231,95 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn compose_fill_macros() -> TestResult {
+        let failed = 2;
+
+        let actual = doc![
+            "summary:" SP
+            fill [ "expected " ({Color::Green}: "all specs") " to pass, but " {failed.to_string()} " of them failed" ]
+        ];
+
+        assert_eq!(
+            EmitPlain.render(&actual, 80)?,
+            "summary: expected all specs to pass, but 2 of them failed"
+        );
+
+        assert_eq!(
+            EmitForTest.render(&actual, 20)?,
+            strip(
+                r#"
+                [normal:summary:]
+                [normal:expected][normal: ][Green:all][normal: ][Green:specs]
+                [normal:to][normal: ][normal:pass,][normal: ][normal:but][normal: ][normal:2][normal: ][normal:of]
+                [normal:them][normal: ][normal:failed]
+                "#
+            )
+        );
+
+        let actual = doc![
+            fill(Fill::default().hang(Nesting::Configured(1))) [
+                "the quick brown fox jumps over the lazy dog" ; "the end"
+            ]
+        ];
+
+        assert_eq!(
+            EmitPlain.render(&actual, 20)?,
+            "the quick brown fox\n  jumps over the\n  lazy dog\n  the end"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn compose_table_macros() -> TestResult {
+        let durations = ["2ms", "120ms"];
+
+        let actual = doc![
+            "results:" BK
+            table [
+                header [ "name" | "status" | "duration" ]
+                [ "parses input" | ({Color::Green}: "ok") | {durations[0]} ]
+                [ "emits" SP "output" | (Red: "failed") | {durations[1]} ]
+                [ "skipped" | | ]
+            ]
+        ];
+
+        assert_eq!(
+            EmitPlain.render(&actual, 80)?,
+            strip(
+                r#"
+                results:
+                name          status  duration
+                parses input  ok      2ms
+                emits output  failed  120ms
+                skipped
+                "#
+            )
+        );
+
+        let columns = vec![Column::left(), Column::left(), Column::right()];
+
+        let actual = doc![
+            table(Table::new(columns).border(Border::ascii())) [
+                [ "parses input" | ({Color::Green}: "ok") | {durations[0]} ]
+                [ "emits" | (Red: "failed") | {durations[1]} ]
+            ]
+        ];
+
+        assert_eq!(
+            EmitForTest.render(&actual, 80)?,
+            test_emit![
+                | "---------------------------"
+                | "parses input" "  " [Green: "ok"] "        " "2ms"
+                | "emits" "         " [Red: "failed"] "  " "120ms"
+                | "---------------------------"
+            ]
+        );
+
+        Ok(())
+    }
+
     // #[test]
     // fn compose_smoke_macros() -> TestResult {
     //     let expected_block = strip(
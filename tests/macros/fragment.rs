@@ -26,22 +26,22 @@ macro_rules! test_case {
     };
 }
 
-// struct Stringy {
-//     value: String,
-// }
+struct Stringy {
+    value: String,
+}
 
-// impl Stringy {
-//     fn value(&self) -> &str {
-//         &self.value
-//     }
-// }
+impl Stringy {
+    fn value(&self) -> &str {
+        &self.value
+    }
+}
 
 #[test]
 fn test_line() -> EmitResult {
     let value = ("outer-value",);
-    // let stringy = Stringy {
-    //     value: "Niko".to_string(),
-    // };
+    let stringy = Stringy {
+        value: "Niko".to_string(),
+    };
 
     test_case![ (Red: "hello")
         => plain: "hello"
@@ -59,13 +59,13 @@ fn test_line() -> EmitResult {
         => plain: "helloouter-valueworld"
         => colored: "[Red:hello][normal:outer-value][Green:world]" );
 
-    // test_case!(([Red: "hello"] stringy.value().to_string() [Green: "world"])
-    //     => plain: "helloNikoworld"
-    //     => colored: "[Red:hello][normal:Niko][Green:world]" );
+    test_case!({ (Red: "hello") {stringy.value().to_string()} (Green: "world") }
+        => plain: "helloNikoworld"
+        => colored: "[Red:hello][normal:Niko][Green:world]" );
 
-    // test_case!(([Red: "hello"] arena.intern(1 + 1) [Green: "world"])
-    //     => plain: "hello2world"
-    //     => colored: "[Red:hello][normal:2][Green:world]" );
+    test_case!({ (Red: "hello") {format!("{}", 1 + 1)} (Green: "world") }
+        => plain: "hello2world"
+        => colored: "[Red:hello][normal:2][Green:world]" );
 
     Ok(())
 }
@@ -73,9 +73,9 @@ fn test_line() -> EmitResult {
 #[test]
 fn test_block() -> EmitResult {
     let value = ("value-1", "value-2");
-    // let stringy = Stringy {
-    //     value: "Niko".to_string(),
-    // };
+    let stringy = Stringy {
+        value: "Niko".to_string(),
+    };
 
     test_case!({ (Red: "hello") ; (Green: "world") }
         => plain: "hello\nworld"
@@ -93,13 +93,32 @@ fn test_block() -> EmitResult {
         => plain: "hellovalue-1world\ngoodbyevalue-2world"
         => colored: "[Red:hello][normal:value-1][Green:world]\n[Red:goodbye][normal:value-2][Green:world]" );
 
-    // test_case!(([Red: "hello"] stringy.value() [Green: "world"] ; [Red: stringy.value()])
-    //     => plain: "helloNikoworld\nNiko"
-    //     => colored: "[Red:hello][normal:Niko][Green:world]\n[Red:Niko]" );
+    test_case!({ (Red: "hello") {stringy.value.clone()} (Green: "world") ; (Red: stringy.value.clone()) }
+        => plain: "helloNikoworld\nNiko"
+        => colored: "[Red:hello][normal:Niko][Green:world]\n[Red:Niko]" );
+
+    test_case!({ (Red: "hello") {(1 + 1).to_string()} (Green: "world") ; (Red: (1 + 1).to_string()) }
+        => plain: "hello2world\n2"
+        => colored: "[Red:hello][normal:2][Green:world]\n[Red:2]" );
+
+    Ok(())
+}
 
-    // test_case!(([Red: "hello"] arena.intern(1 + 1) [Green: "world"] ; [Red: arena.intern(1 + 1)])
-    //     => plain: "hello2world\n2"
-    //     => colored: "[Red:hello][normal:2][Green:world]\n[Red:2]" );
+#[test]
+fn test_palette_colors() -> EmitResult {
+    let index = 42;
+
+    test_case![ (rgb(255, 135, 0): "hello")
+        => plain: "hello"
+        => colored: "[#ff8700:hello]" ];
+
+    test_case![ ("#ff8700": "hello")
+        => plain: "hello"
+        => colored: "[#ff8700:hello]" ];
+
+    test_case!({ (ansi256(index): "hello") (Green: "world") }
+        => plain: "helloworld"
+        => colored: "[Color256(42):hello][Green:world]" );
 
     Ok(())
 }
@@ -1,7 +1,8 @@
 use std::borrow::Cow;
 
 use spectrum::{
-    BoxedDoc, Doc, Intern, InternedBoxedDoc, InternedStyledFragment, Style, StyledFragment,
+    group, BoxedDoc, Doc, EmitBackendTrait, EmitPlain, Intern, InternedBoxedDoc,
+    InternedStyledFragment, Style, StyledFragment,
 };
 
 /// The goal of this test is to show how to build sub-documents that you can compose together using
@@ -109,4 +110,38 @@ struct NamedFunctionDeclaration<'ctx> {
 }
 
 #[test]
-fn compose_subdocs() {}
+fn compose_subdocs() -> Result<(), std::fmt::Error> {
+    let mut intern = Intern::new();
+
+    let params: Vec<String> = vec!["y".to_string(), format!("z{}", 1)];
+
+    let mut args: Vec<BoxedDoc> = params
+        .into_iter()
+        .map(|param| {
+            let style = TokenStyle {
+                style: BasicTokenStyle::Parameter,
+                modifier: TokenModifier::Declaration,
+            };
+
+            Arg {
+                ident: StyledToken {
+                    style,
+                    item: Cow::Owned(param),
+                },
+            }
+            .intern(&mut intern)
+        })
+        .collect();
+
+    let z = args.pop().unwrap();
+    let y = args.pop().unwrap();
+
+    let doc = group!["function hello(", y, ", ", z, ")"];
+
+    assert_eq!(
+        EmitPlain.render_with_intern(&doc, &intern, 80)?,
+        "function hello(y, z1)"
+    );
+
+    Ok(())
+}
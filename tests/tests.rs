@@ -1,414 +1,1352 @@
 mod macros;
 mod spectrum;
 
-// use ansi_term::{ANSIGenericStrings, Color, Style};
-// use difference::Difference;
-// use laboratory::test::*;
-// use laboratory_expectations::ShouldSugar;
-// use laboratory_test_helpers::assert_eq;
-// use std::fs::read_to_string;
-
-// const EXPECTED_FOLDER: &str = "./tests/expected";
-// const OUTPUT_FOLDER: &str = "./tests/output";
-
-// fn get_output_path(test_name: &str) -> String {
-//     let mut path = String::from(OUTPUT_FOLDER);
-//     path += &format!("/{}", test_name);
-//     path
-// }
-
-// fn get_expected_path(test_name: &str) -> String {
-//     let mut path = String::from(EXPECTED_FOLDER);
-//     path += &format!("/{}", test_name);
-//     path
-// }
-
-// fn get_approval_file(test_name: &str) -> String {
-//     read_to_string(get_expected_path(test_name))
-//         .unwrap_or_else(|_| panic!("Could not find {}", get_expected_path(test_name)))
-// }
-
-// #[test]
-// fn get_aprv_file() {
-//     let result = get_expected_path("my-test");
-//     assert_eq!("./tests/expected/my-test".to_string(), result);
-// }
-
-// #[test]
-// fn simple_pass() -> ReportResult<()> {
-//     fn return_one() -> i32 {
-//         1
-//     }
-
-//     const TEST_NAME: &str = "simple";
-
-//     let actual = describe("add_one()")
-//         .specs(|it| {
-//             it.should("return 1", || return_one().should().eq(1));
-//         })
-//         .export_to(&get_output_path(TEST_NAME))
-//         .to_string()?;
-
-//     let control = get_approval_file(TEST_NAME);
-//     assert_eq!(actual, control);
-
-//     Ok(())
-// }
-
-// #[test]
-// fn simple_fail() -> ReportResult {
-//     fn add_one() -> i32 {
-//         0
-//     }
-
-//     const TEST_NAME: &str = "simple_fail";
-
-//     let actual = describe("add_one")
-//         .reporter(SpecReporter)
-//         .specs(|it| it.should("return 1", || expect(add_one()).to_equal(1)))
-//         .export_to(&get_output_path(TEST_NAME))
-//         .to_string()?;
-
-//     let control = get_approval_file(TEST_NAME);
-//     assert_eq!(actual, control);
-
-//     Ok(())
-// }
-
-// #[test]
-// fn min() -> ReportResult {
-//     fn add_one() -> i32 {
-//         1
-//     }
-
-//     const TEST_NAME: &str = "min";
-
-//     let actual = describe("add_one")
-//         .reporter(MinimalReporter)
-//         .specs(|it| it.should("return 1", || expect(add_one()).to_equal(1)))
-//         .export_to(&get_output_path(TEST_NAME))
-//         .to_string()?;
-
-//     let control = get_approval_file(TEST_NAME);
-//     assert_eq!(actual, control);
-
-//     Ok(())
-// }
-
-// #[test]
-// fn min_fail() -> ReportResult {
-//     fn return1() -> i32 {
-//         0
-//     }
-
-//     const TEST_NAME: &str = "min_fail";
-
-//     let actual = describe("return1")
-//         .reporter(MinimalReporter)
-//         .specs(|it| it.should("return 1", || expect(return1()).to_equal(1)))
-//         .export_to(&get_output_path(TEST_NAME))
-//         .to_string()?;
-
-//     let control = get_approval_file(TEST_NAME);
-//     assert_eq!(actual, control);
-
-//     Ok(())
-// }
-
-// #[test]
-// fn json() -> ReportResult {
-//     #[allow(unused)]
-//     use serde_json::from_str;
-
-//     fn add_one() -> i32 {
-//         1
-//     }
-
-//     const TEST_NAME: &str = "output_json.json";
-
-//     describe("add_one")
-//         .reporter(JsonReporter::new())
-//         .specs(|it| it.should("return 1", || expect(add_one()).to_equal(1)))
-//         .export_to(&get_output_path(TEST_NAME))
-//         .to_string()?;
-
-//     // let _result: SuiteResult = from_str(&actual).expect("could not serialize the result");
-
-//     Ok(())
-// }
-
-// #[test]
-// fn json_pretty() -> ReportResult {
-//     #[allow(unused)]
-//     use serde_json::from_str;
-
-//     fn add_one() -> i32 {
-//         1
-//     }
-
-//     const TEST_NAME: &str = "output_json_pretty.json";
-
-//     describe("add_one")
-//         .reporter(JsonReporter::pretty())
-//         .specs(|it| it.should("return 1", || expect(add_one()).to_equal(1)))
-//         .export_to(&get_output_path(TEST_NAME))
-//         .to_string()?;
-
-//     // let _result: SuiteResult = from_str(&result_str).expect("could not serialize the result");
-
-//     Ok(())
-// }
-
-// #[test]
-// fn suite_skip() -> ReportResult {
-//     fn add_one() -> i32 {
-//         1
-//     }
-
-//     fn return_two() -> i32 {
-//         2
-//     }
-
-//     const TEST_NAME: &str = "suite_skip";
-//     let actual = describe("Library")
-//         .suites(vec![
-//             describe_skip("add_one()")
-//                 .specs(|it| it.should("return 1", || expect(add_one()).to_equal(1))),
-//             describe("return_two()")
-//                 .specs(|it| it.should("return 2", || expect(return_two()).to_equal(2))),
-//         ])
-//         .export_to(&get_output_path(TEST_NAME))
-//         .to_string()?;
-
-//     let control = get_approval_file(TEST_NAME);
-//     assert_eq!(actual, control);
-
-//     Ok(())
-// }
-
-// #[test]
-// fn spec_skip() -> ReportResult {
-//     fn add_one() -> i32 {
-//         1
-//     }
-
-//     fn return_two() -> i32 {
-//         2
-//     }
-
-//     const TEST_NAME: &str = "spec_skip";
-
-//     let actual = describe("Library")
-//         .suites(vec![
-//             describe("add_one()").specs(|it| {
-//                 it.skip("return 1", || expect(add_one()).to_equal(1));
-//                 it.should("return 1", || expect(add_one()).to_equal(1));
-//             }),
-//             describe("return_two()")
-//                 .specs(|it| it.should("return 2", || expect(return_two()).to_equal(2))),
-//         ])
-//         .export_to(&get_output_path(TEST_NAME))
-//         .to_string()?;
-
-//     let control = get_approval_file(TEST_NAME);
-//     assert_eq!(actual, control);
-
-//     Ok(())
-// }
-
-// #[test]
-// fn spec_only() -> ReportResult {
-//     fn add_one() -> i32 {
-//         1
-//     }
-
-//     fn return_two() -> i32 {
-//         2
-//     }
-
-//     const TEST_NAME: &str = "spec_only";
-//     let result_str = describe("Library")
-//         .suites(vec![
-//             describe("add_one()").specs(|it| {
-//                 it.only("return 1", || expect(add_one()).to_equal(1));
-//                 it.should("return 3", || expect(add_one()).to_equal(3));
-//             }),
-//             describe("return_two()")
-//                 .specs(|it| it.should("return 2", || expect(return_two()).to_equal(2))),
-//         ])
-//         .export_to(&get_output_path(TEST_NAME))
-//         .to_string()?;
-
-//     let control = get_approval_file(TEST_NAME);
-//     assert_eq!(result_str, control);
-
-//     Ok(())
-// }
-
-// #[test]
-// fn state_passing() -> ReportResult {
-//     #[derive(Clone, Debug)]
-//     struct Counter {
-//         count: i32,
-//     }
-
-//     impl Counter {
-//         pub fn new() -> Counter {
-//             Counter { count: 0 }
-//         }
-//     }
-
-//     fn return_count(counter: &mut Counter) -> i32 {
-//         counter.count
-//     }
-//     fn return_incr_count(counter: &mut Counter) -> i32 {
-//         counter.count + 1
-//     }
-
-//     let actual = describe("Library")
-//         .state(Counter::new())
-//         .describe("return_count()", |it| {
-//             it.should("return 1", |counter| {
-//                 counter.count += 1;
-//                 expect(return_count(counter)).to_equal(1)
-//             });
-//             it.should("return 1 again", |counter| {
-//                 counter.count += 1;
-//                 expect(return_count(counter)).to_equal(1)
-//             });
-//         })
-//         .describe("return_incr_count()", |it| {
-//             it.should("return 2", |counter| {
-//                 counter.count += 1;
-//                 expect(return_incr_count(counter)).to_equal(2)
-//             })
-//         })
-//         .suite(describe("return_two()").specs(|it| it.should("return 2", || expect(2).to_equal(2))))
-//         .to_string()?;
-
-//     const TEST_NAME: &str = "state_passing";
-//     let control = get_approval_file(TEST_NAME);
-//     assert_eq!(actual, control);
-
-//     Ok(())
-// }
-
-// #[test]
-// fn mutable_state_passing() -> ReportResult {
-//     #[derive(Clone, Debug)]
-//     struct Counter {
-//         count: i32,
-//     }
-
-//     impl Counter {
-//         pub fn new() -> Counter {
-//             Counter { count: 0 }
-//         }
-//     }
-
-//     fn return_one() -> i32 {
-//         1
-//     }
-//     fn return_two() -> i32 {
-//         2
-//     }
-
-//     let counter = describe("Library")
-//         .mutable_state(Counter::new())
-//         .describe("return_one()", |it| {
-//             it.should("return 1", |counter| {
-//                 counter.count += 1;
-//                 expect(return_one()).to_equal(1)
-//             });
-//             it.should("return 1 again", |counter| {
-//                 counter.count += 1;
-//                 expect(return_one()).to_equal(1)
-//             });
-//         })
-//         .suite(
-//             describe("return_two()")
-//                 .specs(|it| it.should("return 2", || expect(return_two()).to_equal(2))),
-//         )
-//         .run_with(ReporterOutput::null())?
-//         .into_state();
-
-//     assert_eq!(counter.count, 2);
-
-//     Ok(())
-// }
-
-// #[test]
-// fn return_result() -> ReportResult {
-//     fn add_one(n: i32) -> i32 {
-//         n + 1
-//     }
-
-//     describe("add_one()")
-//         .specs(|it| {
-//             it.should("return 1", || expect(add_one(0)).to_equal(1));
-//             it.should("return 2", || expect(add_one(0)).to_equal(2));
-//         })
-//         .to_string()?;
-
-//     Ok(())
-// }
-
-// #[test]
-// fn micro() -> ReportResult {
-//     fn return_one() -> i32 {
-//         1
-//     }
-
-//     const TEST_NAME: &str = "micro";
-
-//     let actual = describe("add_one()")
-//         .specs(|it| it.should("return 1", || expect(return_one()).to_equal(1)))
-//         .export_to(&get_output_path(TEST_NAME))
-//         .in_microseconds()
-//         .to_string()?;
-//     // simple spec pass
-//     // let control = get_approval_file(TEST_NAME);
-//     assert!(actual.contains("µs)"), format!("actual: {:?}", actual));
-
-//     Ok(())
-// }
-
-// #[test]
-// fn nano() -> ReportResult {
-//     fn return_one() -> i32 {
-//         1
-//     }
-
-//     const TEST_NAME: &str = "nano";
-
-//     let actual = describe("add_one()")
-//         .specs(|it| it.should("return 1", || expect(return_one()).to_equal(1)))
-//         .export_to(&get_output_path(TEST_NAME))
-//         .in_nanoseconds()
-//         .to_string()?;
-
-//     // let control = get_approval_file(TEST_NAME);
-//     assert!(actual.contains("ns)"));
-
-//     Ok(())
-// }
-
-// #[test]
-// fn seconds() -> ReportResult {
-//     fn return_one() -> i32 {
-//         1
-//     }
-
-//     const TEST_NAME: &str = "seconds";
-
-//     let actual = describe("add_one()")
-//         .specs(|it| it.should("return 1", || expect(return_one()).to_equal(1)))
-//         .export_to(&get_output_path(TEST_NAME))
-//         .in_seconds()
-//         .to_string()?;
-
-//     // let control = get_approval_file(TEST_NAME);
-//     assert!(actual.contains("sec)"), "actual: {:?}", actual);
-
-//     Ok(())
-// }
+use ansi_term::{ANSIGenericStrings, Color, Style};
+use difference::Difference;
+use laboratory::test::*;
+use laboratory::{DescribedSuite, SuiteWithState};
+use laboratory_test_helpers::assert_eq;
+use std::{
+    fs::read_to_string,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+const EXPECTED_FOLDER: &str = "./tests/expected";
+const OUTPUT_FOLDER: &str = "./tests/output";
+
+fn get_output_path(test_name: &str) -> String {
+    let mut path = String::from(OUTPUT_FOLDER);
+    path += &format!("/{}", test_name);
+    path
+}
+
+fn get_expected_path(test_name: &str) -> String {
+    let mut path = String::from(EXPECTED_FOLDER);
+    path += &format!("/{}", test_name);
+    path
+}
+
+fn get_approval_file(test_name: &str) -> String {
+    read_to_string(get_expected_path(test_name))
+        .unwrap_or_else(|_| panic!("Could not find {}", get_expected_path(test_name)))
+}
+
+#[test]
+fn get_aprv_file() {
+    let result = get_expected_path("my-test");
+    assert_eq!("./tests/expected/my-test".to_string(), result);
+}
+
+#[test]
+fn simple_pass() -> ReportResult<()> {
+    fn return_one() -> i32 {
+        1
+    }
+
+    const TEST_NAME: &str = "simple";
+
+    let actual = describe("add_one()")
+        .specs(|it| {
+            it.should("return 1", || expect(return_one()).to(eq(1)));
+        })
+        .export_to(get_output_path(TEST_NAME))
+        .to_string()?;
+
+    let control = get_approval_file(TEST_NAME);
+    assert_eq!(actual, control);
+
+    Ok(())
+}
+
+#[test]
+fn simple_fail() -> ReportResult {
+    fn add_one() -> i32 {
+        0
+    }
+
+    const TEST_NAME: &str = "simple_fail";
+
+    let actual = describe("add_one")
+        .reporter(SpecReporter)
+        .specs(|it| it.should("return 1", || expect(add_one()).to(eq(1))))
+        .export_to(get_output_path(TEST_NAME))
+        .to_string()?;
+
+    let control = get_approval_file(TEST_NAME);
+    assert_eq!(actual, control);
+
+    Ok(())
+}
+
+#[test]
+fn min() -> ReportResult {
+    fn add_one() -> i32 {
+        1
+    }
+
+    const TEST_NAME: &str = "min";
+
+    let actual = describe("add_one")
+        .reporter(MinimalReporter)
+        .specs(|it| it.should("return 1", || expect(add_one()).to(eq(1))))
+        .export_to(get_output_path(TEST_NAME))
+        .to_string()?;
+
+    let control = get_approval_file(TEST_NAME);
+    assert_eq!(actual, control);
+
+    Ok(())
+}
+
+#[test]
+fn min_fail() -> ReportResult {
+    fn return1() -> i32 {
+        0
+    }
+
+    const TEST_NAME: &str = "min_fail";
+
+    let actual = describe("return1")
+        .reporter(MinimalReporter)
+        .specs(|it| it.should("return 1", || expect(return1()).to(eq(1))))
+        .export_to(get_output_path(TEST_NAME))
+        .to_string()?;
+
+    let control = get_approval_file(TEST_NAME);
+    assert_eq!(actual, control);
+
+    Ok(())
+}
+
+#[test]
+fn json() -> ReportResult {
+    use serde_json::from_str;
+
+    fn add_one() -> i32 {
+        1
+    }
+
+    const TEST_NAME: &str = "output_json.json";
+
+    let actual = describe("add_one")
+        .reporter(JsonReporter::new())
+        .specs(|it| it.should("return 1", || expect(add_one()).to(eq(1))))
+        .export_to(get_output_path(TEST_NAME))
+        .to_string()?;
+
+    let result: JsonReport = from_str(&actual).expect("could not deserialize the result");
+    assert_eq!(result.version, JSON_SCHEMA_VERSION);
+    assert_eq!(result.suite.passing, 1);
+
+    Ok(())
+}
+
+#[test]
+fn json_pretty() -> ReportResult {
+    use serde_json::from_str;
+
+    fn add_one() -> i32 {
+        1
+    }
+
+    const TEST_NAME: &str = "output_json_pretty.json";
+
+    let result_str = describe("add_one")
+        .reporter(JsonReporter::pretty())
+        .specs(|it| it.should("return 1", || expect(add_one()).to(eq(1))))
+        .export_to(get_output_path(TEST_NAME))
+        .to_string()?;
+
+    let result: JsonReport = from_str(&result_str).expect("could not deserialize the result");
+    assert_eq!(result.suite.child_tests[0].full_name, "add_one::return 1");
+
+    Ok(())
+}
+
+#[test]
+fn json_round_trip() -> ReportResult {
+    use serde_json::{from_str, to_string};
+
+    fn add_one(n: i32) -> i32 {
+        n + 1
+    }
+
+    let actual = describe("Library")
+        .reporter(JsonReporter::new())
+        .suite(describe("add_one()").specs(|it| {
+            it.should("return 1", || expect(add_one(0)).to(eq(1)));
+            it.should("return 3", || expect(add_one(1)).to(eq(3)));
+        }))
+        .suite(describe_skip("sub_one()").specs(|it| {
+            it.should("return 0", || expect(add_one(0)).to(eq(1)));
+        }))
+        .specs(|it| it.should("be small", || expect(add_one(0)).to(lt(2).and(gt(0)))))
+        .to_string()?;
+
+    let report: JsonReport = from_str(&actual).expect("could not deserialize the result");
+    assert_eq!(to_string(&report).unwrap() + "\n", actual);
+
+    let suite = &report.suite;
+    assert_eq!(report.version, JSON_SCHEMA_VERSION);
+    // The counts include the nested suites
+    std::assert_eq!((suite.passing, suite.failing, suite.ignored), (2, 1, 1));
+    assert_eq!(suite.child_tests[0].full_name, "Library::be small");
+    assert_eq!(suite.child_suites.len(), 2);
+    assert!(suite.child_suites[1].skip);
+
+    let failed = &suite.child_suites[0].child_tests[1];
+    assert_eq!(failed.full_name, "Library::add_one()::return 3");
+    std::assert_eq!(failed.status, JsonStatus::Failed);
+
+    let failure = failed.failure.as_ref().unwrap();
+    assert_eq!(failure.actual.value, "2");
+    assert_eq!(failure.expected.as_ref().unwrap().value, "3");
+    assert!(failure.rendered.starts_with(&failure.message));
+
+    Ok(())
+}
+
+#[test]
+fn junit() -> ReportResult {
+    fn add_one(n: i32) -> i32 {
+        n + 1
+    }
+
+    const TEST_NAME: &str = "junit.xml";
+
+    let actual = describe("Library")
+        .reporter(JunitReporter)
+        .suite(describe("add_one()").specs(|it| {
+            it.should("return 1", || expect(add_one(0)).to(eq(1)));
+            it.should("return <3>", || expect(add_one(1)).to(eq(3)));
+            it.skip("return 0", || expect(add_one(-1)).to(eq(0)));
+        }))
+        .specs(|it| it.should("be small", || expect(add_one(0)).to(lt(2))))
+        .to_string()?;
+
+    let control = get_approval_file(TEST_NAME);
+    assert_eq!(actual, control);
+
+    Ok(())
+}
+
+#[test]
+fn junit_export() -> ReportResult {
+    let path = std::env::temp_dir()
+        .join("laboratory-junit-export")
+        .join("report.xml");
+
+    let actual = describe("Library")
+        .reporter(JunitReporter)
+        .specs(|it| it.should("return 1", || expect(1).to(eq(1))))
+        .export_to(&path)
+        .to_string()?;
+
+    assert_eq!(actual, "");
+
+    let exported = read_to_string(&path).unwrap();
+    assert!(exported.contains(r#"<testcase name="return 1" classname="Library""#));
+
+    Ok(())
+}
+
+#[test]
+fn tap() -> ReportResult {
+    fn add_one(n: i32) -> i32 {
+        n + 1
+    }
+
+    const TEST_NAME: &str = "tap";
+
+    let actual = describe("Library")
+        .reporter(TapReporter)
+        .suite(describe("add_one()").specs(|it| {
+            it.should("return 1", || expect(add_one(0)).to(eq(1)));
+            it.should("return 3", || expect(add_one(1)).to(eq(3)));
+            it.skip("return 0", || expect(add_one(-1)).to(eq(0)));
+        }))
+        .specs(|it| it.should("be small", || expect(add_one(0)).to(lt(2))))
+        .to_string()?;
+
+    let control = get_approval_file(TEST_NAME);
+    assert_eq!(actual, control);
+
+    Ok(())
+}
+
+#[test]
+fn libtest_json() -> ReportResult {
+    use laboratory::{Arguments, Harness};
+    use serde_json::Value;
+
+    fn add_one(n: i32) -> i32 {
+        n + 1
+    }
+
+    let mut actual = String::new();
+    let arguments = Arguments::parse(vec!["--format", "json", "--skip", "slow"]).unwrap();
+
+    Harness::new(arguments)
+        .suite(describe("add_one()").specs(|it| {
+            it.should("return 1", || expect(add_one(0)).to(eq(1)));
+            it.should("return 3", || expect(add_one(1)).to(eq(3)));
+            it.skip("return 0", || expect(add_one(-1)).to(eq(0)));
+            it.should("be slow", || expect(add_one(0)).to(eq(1)));
+        }))
+        .run_with(ReporterOutput::write(&mut actual))?;
+
+    let events: Vec<Value> = actual
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("every line is an event"))
+        .collect();
+
+    let summary: Vec<String> = events
+        .iter()
+        .map(|event| {
+            format!(
+                "{} {} {}",
+                event["type"].as_str().unwrap(),
+                event["event"].as_str().unwrap(),
+                event["name"].as_str().unwrap_or("-")
+            )
+        })
+        .collect();
+
+    std::assert_eq!(
+        summary,
+        vec![
+            "suite started -",
+            "test started add_one()::return 1",
+            "test ok add_one()::return 1",
+            "test started add_one()::return 3",
+            "test failed add_one()::return 3",
+            "test started add_one()::return 0",
+            "test ignored add_one()::return 0",
+            "suite failed -",
+        ]
+    );
+
+    std::assert_eq!(events[0]["test_count"], 3);
+    assert!(events[4]["stdout"]
+        .as_str()
+        .unwrap()
+        .starts_with("expected the value to be equal to 3"));
+
+    let finished = &events[7];
+    std::assert_eq!(
+        (
+            &finished["passed"],
+            &finished["failed"],
+            &finished["ignored"]
+        ),
+        (&Value::from(1), &Value::from(1), &Value::from(1))
+    );
+    std::assert_eq!(finished["filtered_out"], 1);
+
+    Ok(())
+}
+
+#[test]
+fn suite_skip() -> ReportResult {
+    fn add_one() -> i32 {
+        1
+    }
+
+    fn return_two() -> i32 {
+        2
+    }
+
+    const TEST_NAME: &str = "suite_skip";
+    let actual = describe("Library")
+        .suites(vec![
+            describe_skip("add_one()")
+                .specs(|it| it.should("return 1", || expect(add_one()).to(eq(1)))),
+            describe("return_two()")
+                .specs(|it| it.should("return 2", || expect(return_two()).to(eq(2)))),
+        ])
+        .export_to(get_output_path(TEST_NAME))
+        .to_string()?;
+
+    let control = get_approval_file(TEST_NAME);
+    assert_eq!(actual, control);
+
+    Ok(())
+}
+
+#[test]
+fn spec_skip() -> ReportResult {
+    fn add_one() -> i32 {
+        1
+    }
+
+    fn return_two() -> i32 {
+        2
+    }
+
+    const TEST_NAME: &str = "spec_skip";
+
+    let actual = describe("Library")
+        .suites(vec![
+            describe("add_one()").specs(|it| {
+                it.skip("return 1", || expect(add_one()).to(eq(1)));
+                it.should("return 1", || expect(add_one()).to(eq(1)));
+            }),
+            describe("return_two()")
+                .specs(|it| it.should("return 2", || expect(return_two()).to(eq(2)))),
+        ])
+        .export_to(get_output_path(TEST_NAME))
+        .to_string()?;
+
+    let control = get_approval_file(TEST_NAME);
+    assert_eq!(actual, control);
+
+    Ok(())
+}
+
+#[test]
+fn spec_only() -> ReportResult {
+    fn add_one() -> i32 {
+        1
+    }
+
+    fn return_two() -> i32 {
+        2
+    }
+
+    const TEST_NAME: &str = "spec_only";
+    let result_str = describe("Library")
+        .suites(vec![
+            describe("add_one()").specs(|it| {
+                it.only("return 1", || expect(add_one()).to(eq(1)));
+                it.should("return 3", || expect(add_one()).to(eq(3)));
+            }),
+            describe("return_two()")
+                .specs(|it| it.should("return 2", || expect(return_two()).to(eq(2)))),
+        ])
+        .export_to(get_output_path(TEST_NAME))
+        .to_string()?;
+
+    let control = get_approval_file(TEST_NAME);
+    assert_eq!(result_str, control);
+
+    Ok(())
+}
+
+#[test]
+fn state_passing() -> ReportResult {
+    #[derive(Clone, Debug)]
+    struct Counter {
+        count: i32,
+    }
+
+    impl Counter {
+        pub fn new() -> Counter {
+            Counter { count: 0 }
+        }
+    }
+
+    fn return_count(counter: &mut Counter) -> i32 {
+        counter.count
+    }
+    fn return_incr_count(counter: &mut Counter) -> i32 {
+        counter.count + 1
+    }
+
+    let actual = describe("Library")
+        .state(Counter::new())
+        .describe("return_count()", |it| {
+            it.should("return 1", |counter| {
+                counter.count += 1;
+                expect(return_count(counter)).to(eq(1))
+            });
+            it.should("return 1 again", |counter| {
+                counter.count += 1;
+                expect(return_count(counter)).to(eq(1))
+            });
+        })
+        .describe("return_incr_count()", |it| {
+            it.should("return 2", |counter| {
+                counter.count += 1;
+                expect(return_incr_count(counter)).to(eq(2))
+            })
+        })
+        .suite(describe("return_two()").specs(|it| it.should("return 2", || expect(2).to(eq(2)))))
+        .to_string()?;
+
+    const TEST_NAME: &str = "state_passing";
+    let control = get_approval_file(TEST_NAME);
+    assert_eq!(actual, control);
+
+    Ok(())
+}
+
+#[test]
+fn mutable_state_passing() -> ReportResult {
+    #[derive(Clone, Debug)]
+    struct Counter {
+        count: i32,
+    }
+
+    impl Counter {
+        pub fn new() -> Counter {
+            Counter { count: 0 }
+        }
+    }
+
+    fn return_one() -> i32 {
+        1
+    }
+    fn return_two() -> i32 {
+        2
+    }
+
+    let counter = describe("Library")
+        .mutable_state(Counter::new())
+        .describe("return_one()", |it| {
+            it.should("return 1", |counter| {
+                counter.count += 1;
+                expect(return_one()).to(eq(1))
+            });
+            it.should("return 1 again", |counter| {
+                counter.count += 1;
+                expect(return_one()).to(eq(1))
+            });
+        })
+        .suite(
+            describe("return_two()")
+                .specs(|it| it.should("return 2", || expect(return_two()).to(eq(2)))),
+        )
+        .run_with(ReporterOutput::null())?
+        .into_state();
+
+    assert_eq!(counter.count, 2);
+
+    Ok(())
+}
+
+#[test]
+fn hooks_order() -> ReportResult {
+    use std::sync::{Arc, Mutex};
+
+    let log = Arc::new(Mutex::new(vec![]));
+    let record = |entry: &'static str| {
+        let log = log.clone();
+        move |_: &mut ()| log.lock().unwrap().push(entry)
+    };
+    let spec = |entry: &'static str| {
+        let log = log.clone();
+        move || {
+            log.lock().unwrap().push(entry);
+            Ok(())
+        }
+    };
+    let suite = |top: DescribedSuite| {
+        top.before_all(record("before_all"))
+            .before_each(record("before_each"))
+            .after_each(record("after_each"))
+            .after_all(record("after_all"))
+            .suite(
+                describe("add_one()")
+                    .before_all(record("add_one before_all"))
+                    .before_each(record("add_one before_each"))
+                    .after_each(record("add_one after_each"))
+                    .after_all(record("add_one after_all"))
+                    .specs(|it| {
+                        it.should("return 1", spec("add_one spec"));
+                        it.skip("return 0", spec("add_one skipped spec"));
+                    }),
+            )
+            .specs(|it| it.should("be small", spec("spec")))
+    };
+
+    let expected = vec![
+        "before_all",
+        "before_each",
+        "spec",
+        "after_each",
+        "add_one before_all",
+        "before_each",
+        "add_one before_each",
+        "add_one spec",
+        "add_one after_each",
+        "after_each",
+        "add_one after_all",
+        "after_all",
+    ];
+
+    suite(describe("Library")).run_with(ReporterOutput::null())?;
+    std::assert_eq!(*log.lock().unwrap(), expected);
+
+    // The nested suite runs on another thread, and still runs inside the outer suite's hooks
+    log.lock().unwrap().clear();
+    suite(describe("Library").parallel(2)).run_with(ReporterOutput::null())?;
+    std::assert_eq!(*log.lock().unwrap(), expected);
+
+    Ok(())
+}
+
+#[test]
+fn hooks_with_state() -> ReportResult {
+    #[derive(Clone, Debug)]
+    struct Counter {
+        count: i32,
+    }
+
+    let counter = describe("Library")
+        .mutable_state(Counter { count: 0 })
+        .before_all(|counter| counter.count = 10)
+        .before_each(|counter| counter.count += 1)
+        .specs(|it| {
+            it.should("see before_all", |counter| expect(counter.count).to(eq(11)));
+            it.should("see before_each", |counter| {
+                expect(counter.count).to(eq(12))
+            });
+        })
+        .after_all(|counter| counter.count *= 2)
+        .run_with(ReporterOutput::null())?
+        .into_state();
+
+    assert_eq!(counter.count, 24);
+
+    // Specs get a copy of the state that the hooks left, so their own changes don't last, and
+    // the outer suite's `before_each` runs around the nested suite's specs
+    let counter = describe("Library")
+        .state(Counter { count: 0 })
+        .before_each(|counter| counter.count += 1)
+        .describe("count", |it| {
+            it.should("be 1", |counter| {
+                counter.count += 10;
+                expect(counter.count).to(eq(11))
+            });
+            it.should("be 2", |counter| expect(counter.count).to(eq(2)));
+        })
+        .run_with(ReporterOutput::null())?
+        .into_state();
+
+    assert_eq!(counter.count, 2);
+
+    Ok(())
+}
+
+fn failing_hooks() -> SuiteWithState<()> {
+    fn add_one(n: i32) -> i32 {
+        n + 1
+    }
+
+    describe("Library")
+        .suite(
+            describe("add_one()")
+                .before_all(|_| expect(add_one(0)).to(eq(2)))
+                .specs(|it| it.should("return 1", || expect(add_one(0)).to(eq(1)))),
+        )
+        .suite(
+            describe("add_two()")
+                .after_each(|_| expect(add_one(1)).to(eq(3)))
+                .after_all(|_| expect(add_one(2)).to(eq(4)))
+                .specs(|it| {
+                    it.should("return 2", || expect(add_one(add_one(0))).to(eq(2)));
+                    it.should("return 3", || expect(add_one(add_one(0))).to(eq(3)));
+                }),
+        )
+        .specs(|it| it.should("be small", || expect(add_one(0)).to(lt(2))))
+}
+
+#[test]
+fn hook_failure() -> ReportResult {
+    const TEST_NAME: &str = "hook_failure";
+
+    let actual = failing_hooks().to_string()?;
+    let control = get_approval_file(TEST_NAME);
+    assert_eq!(actual, control);
+
+    let outcome = failing_hooks().run_with(ReporterOutput::null())?;
+    std::assert_eq!(outcome.failures(), 4);
+
+    Ok(())
+}
+
+#[test]
+fn panics() -> ReportResult {
+    use serde_json::from_str;
+
+    fn divide(a: i32, b: i32) -> i32 {
+        if b == 0 {
+            panic!("attempted to divide {} by zero", a);
+        }
+
+        a / b
+    }
+
+    let actual = describe("divide()")
+        .reporter(JsonReporter::new())
+        .specs(|it| {
+            it.should("return 2", || expect(divide(4, 0)).to(eq(2)));
+            it.should_panic("panic on zero", || {
+                divide(1, 0);
+            });
+            it.should_panic_with("say what it divided", "divide 1 by zero", || {
+                divide(1, 0);
+            });
+            it.should_panic_with("say something else", "divide 2 by zero", || {
+                divide(1, 0);
+            });
+            it.should_panic("panic on one", || {
+                divide(1, 1);
+            });
+            it.should("return 4", || expect(divide(4, 1)).to(eq(4)));
+        })
+        .to_string()?;
+
+    let result: JsonReport = from_str(&actual).expect("could not deserialize the result");
+    let statuses: Vec<JsonStatus> = result.suite.child_tests.iter().map(|t| t.status).collect();
+
+    std::assert_eq!(
+        statuses,
+        vec![
+            JsonStatus::Failed,
+            JsonStatus::Passed,
+            JsonStatus::Passed,
+            JsonStatus::Failed,
+            JsonStatus::Failed,
+            JsonStatus::Passed,
+        ]
+    );
+
+    let panic = result.suite.child_tests[0]
+        .panic
+        .as_ref()
+        .expect("the panic wasn't reported");
+    assert_eq!(panic.message, "attempted to divide 4 by zero");
+    assert!(panic
+        .location
+        .as_ref()
+        .is_some_and(|location| location.starts_with("tests/tests.rs:")));
+
+    let failure = result.suite.child_tests[3].failure.as_ref().unwrap();
+    assert!(failure.message.contains("divide 2 by zero"));
+    let failure = result.suite.child_tests[4].failure.as_ref().unwrap();
+    assert_eq!(failure.message, "expected the spec to panic");
+
+    Ok(())
+}
+
+#[test]
+fn timeouts() -> ReportResult {
+    use serde_json::from_str;
+    use std::{thread::sleep, time::Instant};
+
+    fn hang() -> MatchResult {
+        sleep(Duration::from_secs(30));
+        Ok(())
+    }
+
+    fn wait(millis: u64) -> MatchResult {
+        sleep(Duration::from_millis(millis));
+        Ok(())
+    }
+
+    let start = Instant::now();
+
+    let actual = describe("timeouts")
+        .reporter(JsonReporter::new())
+        .timeout(Duration::from_millis(100))
+        .specs(|it| {
+            it.should("hang", hang);
+            it.should("run after a spec that hung", || expect(1).to(eq(1)));
+            it.should("wait for longer than the suite", || wait(300))
+                .timeout(Duration::from_secs(5));
+        })
+        .suite(describe("nested").specs(|it| {
+            it.should("hang too", hang);
+        }))
+        .suite(
+            describe("patient")
+                .timeout(Duration::from_millis(10))
+                .specs(|it| {
+                    it.should("give up early", || wait(300));
+                }),
+        )
+        .to_string()?;
+
+    assert!(start.elapsed() < Duration::from_secs(10));
+
+    let result: JsonReport = from_str(&actual).expect("could not deserialize the result");
+    let statuses: Vec<JsonStatus> = result.suite.child_tests.iter().map(|t| t.status).collect();
+
+    std::assert_eq!(
+        statuses,
+        vec![JsonStatus::Failed, JsonStatus::Passed, JsonStatus::Passed]
+    );
+
+    let timed_out = result.suite.child_tests[0]
+        .timed_out
+        .expect("the timeout wasn't reported");
+    assert!(timed_out >= Duration::from_millis(100));
+    assert!(result.suite.child_tests[2].timed_out.is_none());
+
+    let nested = &result.suite.child_suites[0].child_tests[0];
+    std::assert_eq!(nested.status, JsonStatus::Failed);
+    assert!(nested.timed_out.is_some());
+
+    let patient = &result.suite.child_suites[1].child_tests[0];
+    assert!(patient
+        .timed_out
+        .is_some_and(|t| t < Duration::from_millis(300)));
+    assert_eq!(result.suite.failing, 3);
+
+    let actual = describe("wait()")
+        .in_seconds()
+        .timeout(Duration::from_millis(10))
+        .specs(|it| {
+            it.should("return quickly", || wait(300));
+        })
+        .to_string()?;
+
+    assert!(actual.contains("timed out after 0sec"));
+
+    Ok(())
+}
+
+#[test]
+fn timeouts_with_mutable_state() {
+    use laboratory::{ReportError, StateAbandoned};
+    use serde_json::from_str;
+    use std::thread::sleep;
+
+    let mut actual = String::new();
+    let result = describe("counter")
+        .reporter(JsonReporter::new())
+        .mutable_state(0u32)
+        .timeout(Duration::from_millis(100))
+        .specs(|it| {
+            it.should("hang", |_| {
+                sleep(Duration::from_secs(30));
+                Ok(())
+            });
+            it.should("count", |count| {
+                *count += 1;
+                expect(*count).to(eq(1))
+            });
+        })
+        .run_with(ReporterOutput::write(&mut actual));
+
+    // The spec that hung still has the state, so it can't be handed back
+    match result {
+        Err(ReportError::Error(err)) => assert!(err.is::<StateAbandoned>()),
+        other => panic!("expected the state to be abandoned, got {:?}", other),
+    }
+
+    let report: JsonReport = from_str(&actual).expect("could not deserialize the result");
+    let tests = &report.suite.child_tests;
+
+    assert!(tests[0].timed_out.is_some());
+    std::assert_eq!(tests[1].status, JsonStatus::Failed);
+    assert!(tests[1]
+        .failure
+        .as_ref()
+        .is_some_and(|failure| failure.message.contains("abandoned")));
+}
+
+/// Give `top` specs that fail a few times before they pass. The counters are created with the
+/// specs, so that the suite behaves the same every time it's built.
+fn flaky_specs(top: DescribedSuite) -> SuiteWithState<()> {
+    fn fail_until(attempts: &Arc<AtomicUsize>, passing: usize) -> MatchResult {
+        let attempt = attempts.fetch_add(1, Ordering::SeqCst) + 1;
+        expect(attempt).to(gt(passing - 1))
+    }
+
+    let (second, third, never) = (
+        Arc::new(AtomicUsize::new(0)),
+        Arc::new(AtomicUsize::new(0)),
+        Arc::new(AtomicUsize::new(0)),
+    );
+
+    top.in_seconds().retries(2).specs(move |it| {
+        it.should("connect on the second attempt", move || {
+            fail_until(&second, 2)
+        });
+        it.should("connect on the third attempt", move || {
+            fail_until(&third, 3)
+        })
+        .retries(1);
+        it.should("never connect", move || fail_until(&never, 10));
+        it.should("connect right away", || expect(1).to(eq(1)));
+    })
+}
+
+#[test]
+fn flaky() -> ReportResult {
+    use serde_json::from_str;
+
+    const TEST_NAME: &str = "flaky";
+
+    let actual = flaky_specs(describe("connect()")).to_string()?;
+    let control = get_approval_file(TEST_NAME);
+    assert_eq!(actual, control);
+
+    let actual = flaky_specs(describe("connect()").reporter(JsonReporter::new())).to_string()?;
+    let result: JsonReport = from_str(&actual).expect("could not deserialize the result");
+    let tests = &result.suite.child_tests;
+
+    let statuses: Vec<JsonStatus> = tests.iter().map(|t| t.status).collect();
+    std::assert_eq!(
+        statuses,
+        vec![
+            JsonStatus::Flaky,
+            JsonStatus::Failed,
+            JsonStatus::Failed,
+            JsonStatus::Passed,
+        ]
+    );
+
+    let attempts: Vec<usize> = tests.iter().map(|t| t.attempts.len()).collect();
+    std::assert_eq!(attempts, vec![2, 2, 3, 0]);
+    std::assert_eq!(tests[0].attempts[0].status, JsonStatus::Failed);
+    assert!(tests[0].attempts[0].failure.is_some());
+    std::assert_eq!((result.suite.passing, result.suite.failing), (2, 2));
+
+    let panics = Arc::new(AtomicUsize::new(0));
+
+    let actual = describe("reconnect()")
+        .reporter(JsonReporter::new())
+        .specs(move |it| {
+            it.should("recover from a panic", move || {
+                if panics.fetch_add(1, Ordering::SeqCst) == 0 {
+                    panic!("connection reset");
+                }
+
+                Ok(())
+            })
+            .retries(1);
+        })
+        .to_string()?;
+
+    let result: JsonReport = from_str(&actual).expect("could not deserialize the result");
+    let test = &result.suite.child_tests[0];
+    std::assert_eq!(test.status, JsonStatus::Flaky);
+    assert!(test.attempts[0].panic.is_some());
+
+    Ok(())
+}
+
+fn slow_suites(
+    top: DescribedSuite,
+    running: Arc<AtomicUsize>,
+    most: Arc<AtomicUsize>,
+) -> SuiteWithState<()> {
+    let slow = move || {
+        let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+        most.fetch_max(now, Ordering::SeqCst);
+        std::thread::sleep(Duration::from_millis(20));
+        running.fetch_sub(1, Ordering::SeqCst);
+    };
+
+    let mut suite = top.specs(|it| it.should("be first", || Ok(())));
+
+    for name in &["add_one()", "add_two()", "add_three()", "add_four()"] {
+        let slow = slow.clone();
+
+        // Measured in seconds, so that the durations of the failures match
+        suite = suite.suite(describe(*name).in_seconds().specs(move |it| {
+            let (first, second) = (slow.clone(), slow.clone());
+
+            it.should("return 1", move || {
+                first();
+                Ok(())
+            });
+            it.should("return 2", move || {
+                second();
+                expect(1).to(eq(2))
+            });
+            it.skip("return 3", || Ok(()));
+        }));
+    }
+
+    suite
+}
+
+#[test]
+fn parallel() -> ReportResult {
+    let (running, most) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+
+    let library = || describe("Library").reporter(TapReporter);
+
+    let serial = slow_suites(library(), running.clone(), most.clone()).to_string()?;
+    std::assert_eq!(most.swap(0, Ordering::SeqCst), 1);
+
+    let parallel = slow_suites(library(), running, most.clone())
+        .parallel(4)
+        .to_string()?;
+    assert!(most.load(Ordering::SeqCst) > 1);
+
+    // Reporters see the same callbacks in the same order either way
+    assert_eq!(parallel, serial);
+
+    Ok(())
+}
+
+#[test]
+fn parallel_harness() -> ReportResult {
+    use laboratory::{Arguments, Harness};
+
+    // The output of the run, and the most specs that ran at once
+    let run = |args: Vec<&str>, threads: usize| -> ReportResult<(String, usize)> {
+        let (running, most) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+        let mut output = String::new();
+
+        let conclusion = Harness::new(Arguments::parse(args).unwrap())
+            .reporter(TapReporter)
+            .suite(slow_suites(
+                describe("Library").parallel(threads),
+                running.clone(),
+                most.clone(),
+            ))
+            .suite(slow_suites(describe("Strings"), running, most.clone()))
+            .run_with(ReporterOutput::write(&mut output))?;
+
+        std::assert_eq!(conclusion.failures(), 8);
+        Ok((output, most.load(Ordering::SeqCst)))
+    };
+
+    let (serial, most) = run(vec![], 1)?;
+    std::assert_eq!(most, 1);
+
+    let (parallel, most) = run(vec!["--test-threads", "3"], 1)?;
+    assert!(most > 1);
+    assert_eq!(parallel, serial);
+
+    // Without `--test-threads`, the suites' own setting is used
+    let (parallel, most) = run(vec![], 3)?;
+    assert!(most > 1);
+    assert_eq!(parallel, serial);
+
+    let (_, most) = run(vec!["--test-threads", "1"], 3)?;
+    std::assert_eq!(most, 1);
+
+    Ok(())
+}
+
+#[test]
+fn parallel_streams_suites() -> ReportResult {
+    use laboratory::{Arguments, Harness};
+    use std::{io::Write, sync::Mutex, time::Instant};
+
+    // Output that specs can read while the run is still going
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let shared = Shared::default();
+    let seen = shared.clone();
+
+    let conclusion = Harness::new(Arguments::parse(vec!["--test-threads", "2"]).unwrap())
+        .reporter(TapReporter)
+        .suite(describe("First").specs(|it| it.should("finish", || Ok(()))))
+        .suite(describe("Last").specs(move |it| {
+            it.should("see the first suite reported", move || {
+                let start = Instant::now();
+                let reported =
+                    || String::from_utf8_lossy(&seen.0.lock().unwrap()).contains("First");
+
+                while !reported() && start.elapsed() < Duration::from_secs(5) {
+                    std::thread::sleep(Duration::from_millis(5));
+                }
+
+                expect(reported()).to(eq(true))
+            })
+        }))
+        .run_with(ReporterOutput::buffer(&mut shared.clone()))?;
+
+    std::assert_eq!(conclusion.failures(), 0);
+    assert!(String::from_utf8_lossy(&shared.0.lock().unwrap()).contains("ok 2 - Last::"));
+    Ok(())
+}
+
+#[test]
+fn return_result() -> ReportResult {
+    fn add_one(n: i32) -> i32 {
+        n + 1
+    }
+
+    describe("add_one()")
+        .specs(|it| {
+            it.should("return 1", || expect(add_one(0)).to(eq(1)));
+            it.should("return 2", || expect(add_one(0)).to(eq(2)));
+        })
+        .to_string()?;
+
+    Ok(())
+}
+
+#[test]
+fn micro() -> ReportResult {
+    fn return_one() -> i32 {
+        1
+    }
+
+    const TEST_NAME: &str = "micro";
+
+    let actual = describe("add_one()")
+        .specs(|it| it.should("return 1", || expect(return_one()).to(eq(1))))
+        .export_to(get_output_path(TEST_NAME))
+        .in_microseconds()
+        .to_string()?;
+    // simple spec pass
+    // let control = get_approval_file(TEST_NAME);
+    assert!(actual.contains("µs)"), "actual: {:?}", actual);
+
+    Ok(())
+}
+
+#[test]
+fn nano() -> ReportResult {
+    fn return_one() -> i32 {
+        1
+    }
+
+    const TEST_NAME: &str = "nano";
+
+    let actual = describe("add_one()")
+        .specs(|it| it.should("return 1", || expect(return_one()).to(eq(1))))
+        .export_to(get_output_path(TEST_NAME))
+        .in_nanoseconds()
+        .to_string()?;
+
+    // let control = get_approval_file(TEST_NAME);
+    assert!(actual.contains("ns)"));
+
+    Ok(())
+}
+
+#[test]
+fn seconds() -> ReportResult {
+    fn return_one() -> i32 {
+        1
+    }
+
+    const TEST_NAME: &str = "seconds";
+
+    let actual = describe("add_one()")
+        .specs(|it| it.should("return 1", || expect(return_one()).to(eq(1))))
+        .export_to(get_output_path(TEST_NAME))
+        .in_seconds()
+        .to_string()?;
+
+    // let control = get_approval_file(TEST_NAME);
+    assert!(actual.contains("sec)"), "actual: {:?}", actual);
+
+    Ok(())
+}
+
+#[test]
+fn harness_filters() -> ReportResult {
+    use laboratory::{Arguments, Harness};
+
+    fn add_one(n: i32) -> i32 {
+        n + 1
+    }
+
+    let mut actual = String::new();
+    let arguments = Arguments::parse(vec!["add_one", "--skip", "return 2"]).unwrap();
+
+    let conclusion = Harness::new(arguments)
+        .reporter(MinimalReporter)
+        .suite(describe("add_one()").specs(|it| {
+            it.should("return 1", || expect(add_one(0)).to(eq(1)));
+            it.should("return 2", || expect(add_one(0)).to(eq(2)));
+        }))
+        .suite(describe("sub_one()").specs(|it| {
+            it.should("return 0", || expect(add_one(0)).to(eq(0)));
+        }))
+        .run_with(ReporterOutput::write(&mut actual))?;
+
+    assert_eq!(conclusion.failures(), 0);
+    assert_eq!(actual, "  ✓ 1 tests completed (0ms)\n");
+
+    Ok(())
+}
+
+/// A suite tree with tagged specs and suites, and a hook that counts how often it runs.
+fn tagged_suites(top: DescribedSuite, hooks: Arc<AtomicUsize>) -> SuiteWithState<()> {
+    fn add_one(n: i32) -> i32 {
+        n + 1
+    }
+
+    top.suite(describe("add_one()").tagged(["math"]).specs(|it| {
+        it.should("return 1", || expect(add_one(0)).to(eq(1)))
+            .tagged(["fast"]);
+        it.should("return 2", || expect(add_one(1)).to(eq(2)));
+    }))
+    .suite(
+        describe("store()")
+            .tagged(["db"])
+            .before_all(move |_| {
+                hooks.fetch_add(1, Ordering::SeqCst);
+            })
+            .specs(|it| {
+                it.should("save", || expect(1).to(eq(1))).tagged(["fast"]);
+                it.should("load", || expect(1).to(eq(2))).tagged(["slow"]);
+            }),
+    )
+    .specs(|it| {
+        it.should("be small", || expect(add_one(0)).to(lt(2)))
+            .tagged(["fast"]);
+    })
+}
+
+#[test]
+fn filters() -> ReportResult {
+    use laboratory::{Arguments, Filter, Harness};
+    use serde_json::from_str;
+
+    const TEST_NAME: &str = "filtered";
+
+    let hooks = Arc::new(AtomicUsize::new(0));
+    let top = describe("Library")
+        .in_seconds()
+        .filter(Filter::new().tag("fast").exclude_tag("db"));
+
+    let actual = tagged_suites(top, hooks.clone()).to_string()?;
+    let control = get_approval_file(TEST_NAME);
+    assert_eq!(actual, control);
+    // None of the specs in `store()` were selected, so its hook didn't run
+    std::assert_eq!(hooks.load(Ordering::SeqCst), 0);
+
+    let top = describe("Library")
+        .reporter(JsonReporter::new())
+        .filter(Filter::new().glob("Library > * > ?o*").name("be small"));
+
+    let actual = tagged_suites(top, hooks.clone()).to_string()?;
+    let result: JsonReport = from_str(&actual).expect("could not deserialize the result");
+    let suite = &result.suite;
+
+    std::assert_eq!(
+        (suite.passing, suite.failing, suite.ignored, suite.filtered),
+        (1, 1, 0, 3)
+    );
+    std::assert_eq!(suite.child_tests[0].status, JsonStatus::Passed);
+    std::assert_eq!(
+        suite.child_suites[0].child_tests[0].status,
+        JsonStatus::Filtered
+    );
+    std::assert_eq!(
+        suite.child_suites[1].child_tests[1].status,
+        JsonStatus::Failed
+    );
+    std::assert_eq!(hooks.load(Ordering::SeqCst), 1);
+
+    let mut actual = String::new();
+    let conclusion = Harness::new(Arguments::parse(vec!["add_one"]).unwrap())
+        .reporter(MinimalReporter)
+        .filter(Filter::new().tag("fast"))
+        .suite(tagged_suites(describe("Library"), hooks.clone()))
+        .run_with(ReporterOutput::write(&mut actual))?;
+
+    assert_eq!(conclusion.failures(), 0);
+    assert_eq!(
+        actual,
+        "  ✓ 1 tests completed (0ms)\n  1 tests filtered out\n"
+    );
+
+    // A suite's own filter applies in a harness too
+    let mut actual = String::new();
+    let top = describe("Library").filter(Filter::new().tag("fast"));
+    let conclusion = Harness::new(Arguments::parse(vec!["add_one"]).unwrap())
+        .reporter(MinimalReporter)
+        .suite(tagged_suites(top, hooks))
+        .run_with(ReporterOutput::write(&mut actual))?;
+
+    assert_eq!(conclusion.failures(), 0);
+    assert_eq!(
+        actual,
+        "  ✓ 1 tests completed (0ms)\n  1 tests filtered out\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn focus() -> ReportResult {
+    use laboratory::{describe_only, Arguments, Harness};
+    use serde_json::from_str;
+
+    // A focused spec deep in one suite skips the unfocused specs of every other suite, and a
+    // focused suite runs all of its specs
+    let actual = describe("Library")
+        .reporter(JsonReporter::new())
+        .specs(|it| {
+            it.should("load", || Ok(()));
+        })
+        .suite(describe("add_one()").suite(describe("small").specs(|it| {
+            it.only("return 1", || expect(1).to(eq(1)));
+            it.should("return 3", || expect(1).to(eq(3)));
+        })))
+        .suite(describe_only("return_two()").specs(|it| {
+            it.should("return 2", || expect(2).to(eq(2)));
+            it.skip("return 4", || expect(2).to(eq(4)));
+        }))
+        .to_string()?;
+
+    let result: JsonReport = from_str(&actual).expect("could not deserialize the result");
+    let suite = &result.suite;
+
+    std::assert_eq!((suite.passing, suite.failing, suite.ignored), (2, 0, 3));
+    std::assert_eq!(suite.child_tests[0].status, JsonStatus::Skipped);
+    std::assert_eq!(
+        suite.child_suites[0].child_suites[0].child_tests[1].status,
+        JsonStatus::Skipped
+    );
+    std::assert_eq!(
+        suite.child_suites[1].child_tests[0].status,
+        JsonStatus::Passed
+    );
+
+    // `only` on a suite's own specs focuses them, with state too
+    let actual = describe("Library")
+        .reporter(MinimalReporter)
+        .state(0)
+        .specs(|it| {
+            it.only("return 1", |_| expect(1).to(eq(1)));
+            it.should("return 3", |_| expect(1).to(eq(3)));
+        })
+        .to_string()?;
+
+    assert_eq!(
+        actual,
+        "  ✓ 1 tests completed (0ms)\n  ⚠ focus is active: only specs and suites marked with only ran\n"
+    );
+
+    // Focus in one of a harness's suites skips the specs of the others
+    let mut actual = String::new();
+    let conclusion = Harness::new(Arguments::default())
+        .reporter(MinimalReporter)
+        .suite(describe("add_one()").specs(|it| {
+            it.should("return 3", || expect(1).to(eq(3)));
+        }))
+        .suite(describe_only("return_two()").specs(|it| {
+            it.should("return 2", || expect(2).to(eq(2)));
+        }))
+        .run_with(ReporterOutput::write(&mut actual))?;
+
+    assert_eq!(conclusion.failures(), 0);
+    assert_eq!(
+        actual,
+        "  ✓ 1 tests completed (0ms)\n  ⚠ focus is active: only specs and suites marked with only ran\n"
+    );
+
+    Ok(())
+}